@@ -3,6 +3,7 @@ use std::sync::Arc;
 use tracing::info;
 
 use crate::oracle::OracleService;
+use crate::routes::contract_spec;
 use crate::state::{AppState, OrderBookSnapshot, SimulatedTrade};
 
 /// Demo trading bot that simulates market activity
@@ -114,6 +115,7 @@ impl DemoBot {
             Some(pd) => pd.price,
             None => return,
         };
+        let contract = contract_spec(&self.state, commodity).await;
 
         // Generate all random values in a block (rng is dropped before await)
         let trade = {
@@ -130,14 +132,20 @@ impl DemoBot {
 
             // Random size based on commodity
             let size = match commodity {
-                "OIL" => rng.gen_range(100_000..10_000_000),      // 0.1 to 10 barrels
+                "OIL" => rng.gen_range(100_000..10_000_000),      // 0.1 to 10 bbl
                 "GOLD" => rng.gen_range(10_000..1_000_000),       // 0.01 to 1 oz
                 "SILVER" => rng.gen_range(100_000..5_000_000),    // 0.1 to 5 oz
                 "NATGAS" => rng.gen_range(1_000_000..100_000_000), // 1 to 100 MMBtu
-                "COPPER" => rng.gen_range(100_000..10_000_000),   // 0.1 to 10 lbs
+                "COPPER" => rng.gen_range(100_000..10_000_000),   // 0.1 to 10 lb
                 _ => rng.gen_range(1_000_000..10_000_000),
             };
 
+            // Trade in whole lots at tick prices, as the market enforces
+            let (size, trade_price) = match &contract {
+                Some(contract) => (contract.round_size(size), contract.round_price(trade_price)),
+                None => (size, trade_price),
+            };
+
             // Generate fake addresses
             let maker = format!("Demo{}...{}", rng.gen_range(1..100), rng.gen_range(1000..9999));
             let taker = format!("Bot{}...{}", rng.gen_range(1..50), rng.gen_range(1000..9999));
@@ -10,6 +10,8 @@ mod ws;
 mod state;
 mod oracle;
 mod demo_bot;
+mod markets;
+mod sessions;
 
 use state::AppState;
 use demo_bot::DemoBot;
@@ -30,6 +32,10 @@ async fn main() {
     oracle_service.start_background_updates();
     tracing::info!("Started multi-oracle price feed (Pyth → Backup → Cache)");
 
+    // Keep market settings and trading sessions in step with the chain
+    state.markets.clone().start_background_updates();
+    state.session_calendar.clone().start_background_updates();
+
     // Start demo trading bot
     let demo_bot = Arc::new(DemoBot::new(state.oracle_service.clone(), state.clone()));
     demo_bot.start();
@@ -46,6 +52,7 @@ async fn main() {
         // Market endpoints (commodity-specific)
         .route("/api/market/:commodity", get(routes::get_market_by_commodity))
         .route("/api/market/:commodity/stats", get(routes::get_market_stats_by_commodity))
+        .route("/api/market/:commodity/session", get(routes::get_trading_status))
 
         // Order book endpoints (commodity-specific)
         .route("/api/orderbook/:commodity", get(routes::get_orderbook_by_commodity))
@@ -0,0 +1,277 @@
+use base64::Engine;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+// =============================================================================
+// Market Accounts
+// =============================================================================
+//
+// Reads each commodity's perps-core Market account, so the API serves what is set
+// on chain. Market accounts are configured per commodity with MARKET_ACCOUNTS, e.g.
+// "OIL=<address>,GOLD=<address>". Only the fields the API uses are decoded, at their
+// offsets in the market layout.
+
+const MARKET_DISCRIMINATOR: [u8; 8] = [219, 190, 213, 55, 0, 227, 198, 154];
+
+// Discriminator, four pubkeys, commodity, six u32 parameters, nine u64 counters, then
+// bump and is_paused
+const HAS_TRADING_SCHEDULE_OFFSET: usize = 8 + 32 * 4 + 8 + 4 * 6 + 8 * 9 + 2;
+
+// Then market type, expiry, mark and reference price fields, price band, circuit
+// breaker and status
+const VERSION_OFFSET: usize = HAS_TRADING_SCHEDULE_OFFSET + 2 + 8 * 5 + 4 + 2 + 2;
+
+// Then the version 2 notional caps, version 3 auction settings, version 4 accounting,
+// version 5 maker fill fields and version 6 index basket
+const CONTRACT_SPEC_OFFSET: usize = VERSION_OFFSET + 1 + 8 * 3 + 9 + 8 * 8 + 40 + 40 * 4 + 1;
+const CONTRACT_SPEC_LEN: usize = 8 + 8 * 3;
+
+/// Contract specs appear in version 7
+const CONTRACT_SPEC_VERSION: u8 = 7;
+
+#[derive(Debug, Clone, Default)]
+pub struct MarketAccount {
+    /// Whether opens follow the market's TradingSchedule account
+    pub has_trading_schedule: bool,
+    /// Contract specs, for markets new enough to carry them
+    pub contract: Option<MarketContract>,
+}
+
+/// Contract specs as set on the market. Sizes are in base units, 6 decimals of one
+/// `unit`; a zero lot or tick size leaves sizes or limit prices unrestricted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarketContract {
+    pub unit: String,
+    pub multiplier: u64,
+    pub lot_size: u64,
+    pub tick_size: u64,
+}
+
+impl MarketAccount {
+    /// Decode a perps-core Market account
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        if data.get(..8)? != MARKET_DISCRIMINATOR {
+            return None;
+        }
+        let version = *data.get(VERSION_OFFSET)?;
+        let contract = if version >= CONTRACT_SPEC_VERSION {
+            let spec = data.get(CONTRACT_SPEC_OFFSET..CONTRACT_SPEC_OFFSET + CONTRACT_SPEC_LEN)?;
+            let u64_at = |offset: usize| u64::from_le_bytes(spec[offset..offset + 8].try_into().unwrap());
+            Some(MarketContract {
+                unit: String::from_utf8_lossy(&spec[..8]).trim_end_matches('\0').to_string(),
+                multiplier: u64_at(8),
+                lot_size: u64_at(16),
+                tick_size: u64_at(24),
+            })
+        } else {
+            None
+        };
+
+        Some(Self {
+            has_trading_schedule: *data.get(HAS_TRADING_SCHEDULE_OFFSET)? != 0,
+            contract,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountInfoResponse {
+    result: Option<AccountInfoResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountInfoResult {
+    value: Option<AccountInfoValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountInfoValue {
+    data: (String, String),
+}
+
+/// Raw data of an on-chain account
+pub async fn fetch_account_data(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    address: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getAccountInfo",
+        "params": [address, { "encoding": "base64" }],
+    });
+    let response: AccountInfoResponse = client
+        .post(rpc_url)
+        .json(&request)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    let (data, _) = response.result
+        .and_then(|result| result.value)
+        .ok_or("account not found")?
+        .data;
+    Ok(base64::engine::general_purpose::STANDARD.decode(data)?)
+}
+
+/// Parse comma-separated COMMODITY=address pairs, skipping malformed entries
+pub fn parse_commodity_accounts(config: &str) -> HashMap<String, String> {
+    config
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| match entry.split_once('=') {
+            Some((commodity, address)) if !address.trim().is_empty() => {
+                Some((commodity.trim().to_uppercase(), address.trim().to_string()))
+            }
+            _ => {
+                warn!("Ignoring invalid commodity account: {}", entry);
+                None
+            }
+        })
+        .collect()
+}
+
+// =============================================================================
+// Market Directory
+// =============================================================================
+
+pub struct MarketDirectory {
+    client: reqwest::Client,
+    rpc_url: String,
+
+    // Market account per commodity
+    accounts: HashMap<String, String>,
+
+    // Last markets read from chain
+    markets: RwLock<HashMap<String, MarketAccount>>,
+}
+
+impl MarketDirectory {
+    pub fn new(rpc_url: &str) -> Self {
+        let accounts = parse_commodity_accounts(&std::env::var("MARKET_ACCOUNTS").unwrap_or_default());
+        if accounts.is_empty() {
+            warn!("MARKET_ACCOUNTS not set - market settings unavailable");
+        }
+
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap(),
+            rpc_url: rpc_url.to_string(),
+            accounts,
+            markets: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Last market account read for a commodity
+    pub async fn market(&self, commodity: &str) -> Option<MarketAccount> {
+        self.markets.read().await.get(commodity).cloned()
+    }
+
+    /// Re-read every configured market account; a failed read keeps the last market
+    pub async fn fetch_markets(&self) {
+        for (commodity, address) in &self.accounts {
+            let market = fetch_account_data(&self.client, &self.rpc_url, address)
+                .await
+                .and_then(|data| MarketAccount::from_account_data(&data).ok_or("invalid market account data".into()));
+            match market {
+                Ok(market) => {
+                    self.markets.write().await.insert(commodity.clone(), market);
+                }
+                Err(e) => error!("Market fetch failed for {}: {}", commodity, e),
+            }
+        }
+    }
+
+    /// Start background market refresh task
+    pub fn start_background_updates(self: std::sync::Arc<Self>) {
+        tokio::spawn(async move {
+            loop {
+                self.fetch_markets().await;
+                info!("Markets loaded: {}", self.markets.read().await.len());
+
+                // Market settings change rarely; refresh every minute
+                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market_account(has_trading_schedule: bool) -> Vec<u8> {
+        let mut data = MARKET_DISCRIMINATOR.to_vec();
+        data.resize(HAS_TRADING_SCHEDULE_OFFSET, 0);
+        data.push(has_trading_schedule as u8);
+        data.resize(CONTRACT_SPEC_OFFSET + CONTRACT_SPEC_LEN + 64, 0);
+        data
+    }
+
+    /// A version 7 market quoting 1,000 bbl contracts in lots of 0.01 bbl and $0.01 ticks
+    fn oil_market_account() -> Vec<u8> {
+        let mut data = market_account(false);
+        data[VERSION_OFFSET] = CONTRACT_SPEC_VERSION;
+        let spec = &mut data[CONTRACT_SPEC_OFFSET..CONTRACT_SPEC_OFFSET + CONTRACT_SPEC_LEN];
+        spec[..3].copy_from_slice(b"bbl");
+        spec[8..16].copy_from_slice(&1_000_000_000u64.to_le_bytes());
+        spec[16..24].copy_from_slice(&10_000u64.to_le_bytes());
+        spec[24..32].copy_from_slice(&10_000u64.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_contract_spec_offsets() {
+        // Offsets in the perps-core Market layout
+        assert_eq!(HAS_TRADING_SCHEDULE_OFFSET, 242);
+        assert_eq!(VERSION_OFFSET, 292);
+        assert_eq!(CONTRACT_SPEC_OFFSET, 591);
+    }
+
+    #[test]
+    fn test_contract_spec() {
+        let market = MarketAccount::from_account_data(&oil_market_account()).unwrap();
+        assert_eq!(
+            market.contract,
+            Some(MarketContract {
+                unit: "bbl".to_string(),
+                multiplier: 1_000_000_000,
+                lot_size: 10_000,
+                tick_size: 10_000,
+            })
+        );
+
+        // Older markets carry no contract specs
+        let market = MarketAccount::from_account_data(&market_account(false)).unwrap();
+        assert_eq!(market.contract, None);
+    }
+
+    #[test]
+    fn test_trading_schedule_flag() {
+        assert!(MarketAccount::from_account_data(&market_account(true)).unwrap().has_trading_schedule);
+        assert!(!MarketAccount::from_account_data(&market_account(false)).unwrap().has_trading_schedule);
+    }
+
+    #[test]
+    fn test_other_accounts_rejected() {
+        let mut data = market_account(true);
+        data[0] ^= 1;
+        assert!(MarketAccount::from_account_data(&data).is_none());
+        assert!(MarketAccount::from_account_data(&market_account(true)[..HAS_TRADING_SCHEDULE_OFFSET]).is_none());
+    }
+
+    #[test]
+    fn test_parse_commodity_accounts() {
+        let accounts = parse_commodity_accounts("oil=Acct1111, GOLD = Acct2222,bad,SILVER=");
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts["OIL"], "Acct1111");
+        assert_eq!(accounts["GOLD"], "Acct2222");
+    }
+}
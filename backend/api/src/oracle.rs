@@ -4,6 +4,9 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 
+use crate::markets::MarketDirectory;
+use crate::sessions::{SessionCalendar, SessionState};
+
 // =============================================================================
 // Configuration
 // =============================================================================
@@ -79,6 +82,16 @@ pub struct PriceData {
     pub is_valid: bool,          // Whether price passed validation
 }
 
+/// Whether a commodity can be traded right now, and why not
+#[derive(Debug, Clone, Serialize)]
+pub struct TradingStatus {
+    pub commodity: String,
+    pub trading_allowed: bool,
+    pub session: SessionState,
+    pub circuit_breaker_active: bool,
+    pub has_live_price: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OracleStatus {
     pub pyth_healthy: bool,
@@ -137,10 +150,13 @@ pub struct OracleService {
 
     // Oracle health status
     status: Arc<RwLock<OracleStatus>>,
+
+    // On-chain trading schedules
+    sessions: Arc<SessionCalendar>,
 }
 
 impl OracleService {
-    pub fn new() -> Self {
+    pub fn new(sessions: Arc<SessionCalendar>) -> Self {
         let twelve_data_api_key = std::env::var("TWELVE_DATA_API_KEY").ok();
 
         if twelve_data_api_key.is_some() {
@@ -166,6 +182,7 @@ impl OracleService {
                 circuit_breaker_active: false,
                 commodities_available: vec![],
             })),
+            sessions,
         }
     }
 
@@ -560,19 +577,31 @@ impl OracleService {
         status.clone()
     }
 
-    /// Check if trading should be allowed (circuit breaker check)
+    /// Check if trading should be allowed (circuit breaker and session check)
     pub async fn is_trading_allowed(&self, commodity: &str) -> bool {
-        let status = self.status.read().await;
-        if status.circuit_breaker_active {
-            return false;
-        }
+        self.trading_status(commodity).await.trading_allowed
+    }
 
-        let prices = self.prices.read().await;
-        if let Some(price) = prices.get(commodity) {
-            // Only allow trading on real oracle data, not simulated
-            price.source != PriceSource::Simulated
-        } else {
-            false
+    /// Trading gate details: circuit breaker, oracle source and session state
+    pub async fn trading_status(&self, commodity: &str) -> TradingStatus {
+        let circuit_breaker_active = self.status.read().await.circuit_breaker_active;
+
+        // Only allow trading on real oracle data, not simulated
+        let has_live_price = self.prices.read().await
+            .get(commodity)
+            .map(|price| price.source != PriceSource::Simulated)
+            .unwrap_or(false);
+
+        let session = self.sessions.session_state(commodity, chrono::Utc::now().timestamp()).await;
+
+        TradingStatus {
+            commodity: commodity.to_string(),
+            trading_allowed: !circuit_breaker_active
+                && has_live_price
+                && session == SessionState::Open,
+            session,
+            circuit_breaker_active,
+            has_live_price,
         }
     }
 
@@ -593,7 +622,8 @@ impl OracleService {
 
 impl Default for OracleService {
     fn default() -> Self {
-        Self::new()
+        let rpc_url = "http://localhost:8899";
+        Self::new(Arc::new(SessionCalendar::new(rpc_url, Arc::new(MarketDirectory::new(rpc_url)))))
     }
 }
 
@@ -609,7 +639,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_prices() {
-        let service = OracleService::new();
+        let service = OracleService::default();
         let result = service.fetch_prices().await;
         assert!(result.is_ok());
 
@@ -626,7 +656,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_circuit_breaker() {
-        let service = OracleService::new();
+        let service = OracleService::default();
         let _ = service.fetch_prices().await;
 
         let status = service.get_status().await;
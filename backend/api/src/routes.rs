@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::collections::HashMap;
 
+use crate::markets::MarketContract;
 use crate::state::AppState;
 
 // Commodity configuration
@@ -21,6 +22,60 @@ pub struct CommodityConfig {
     pub pyth_feed: String,
 }
 
+// Contract specs read from the market account. Sizes are in base units, 6 decimals of
+// one contract unit, so 1 contract = `multiplier` / 1_000_000 units
+#[derive(Clone, Serialize)]
+pub struct ContractSpec {
+    pub unit: String,       // e.g. "bbl", "oz"
+    pub multiplier: u64,    // Base units per contract
+    pub lot_size: u64,      // Base units every order size is a multiple of, 0 = any
+    pub tick_size: u64,     // Price increment, in 6 decimals, 0 = any
+}
+
+impl From<MarketContract> for ContractSpec {
+    fn from(contract: MarketContract) -> Self {
+        Self {
+            unit: contract.unit,
+            multiplier: contract.multiplier,
+            lot_size: contract.lot_size,
+            tick_size: contract.tick_size,
+        }
+    }
+}
+
+impl ContractSpec {
+    /// Round a size down to whole lots
+    pub fn round_size(&self, size: u64) -> u64 {
+        if self.lot_size == 0 {
+            return size;
+        }
+        size - size % self.lot_size
+    }
+
+    /// Round a price to the nearest tick
+    pub fn round_price(&self, price: u64) -> u64 {
+        if self.tick_size == 0 {
+            return price;
+        }
+        (price + self.tick_size / 2) / self.tick_size * self.tick_size
+    }
+}
+
+// A commodity with the contract specs of its market, once read from chain
+#[derive(Serialize)]
+pub struct CommodityInfo {
+    #[serde(flatten)]
+    pub config: CommodityConfig,
+    pub contract: Option<ContractSpec>,
+}
+
+/// Contract specs of a commodity's market, if read from chain
+pub async fn contract_spec(state: &AppState, commodity: &str) -> Option<ContractSpec> {
+    state.markets.market(commodity).await
+        .and_then(|market| market.contract)
+        .map(ContractSpec::from)
+}
+
 lazy_static::lazy_static! {
     pub static ref COMMODITIES: HashMap<String, CommodityConfig> = {
         let mut m = HashMap::new();
@@ -77,11 +132,12 @@ pub struct MarketInfo {
     pub maintenance_margin_ratio: u32,
     pub initial_margin_ratio: u32,
     pub taker_fee: u32,
-    pub maker_fee: u32,
+    pub maker_fee: i32,            // Negative = rebate
     pub long_open_interest: u64,
     pub short_open_interest: u64,
     pub funding_rate: i64,
     pub is_paused: bool,
+    pub contract: Option<ContractSpec>,
 }
 
 #[derive(Serialize)]
@@ -167,14 +223,22 @@ pub struct PaginationQuery {
 }
 
 // List all available commodities
-pub async fn get_commodities() -> impl IntoResponse {
-    let commodities: Vec<&CommodityConfig> = COMMODITIES.values().collect();
+pub async fn get_commodities(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let mut commodities = Vec::new();
+    for config in COMMODITIES.values() {
+        commodities.push(CommodityInfo {
+            config: config.clone(),
+            contract: contract_spec(&state, &config.id).await,
+        });
+    }
     Json(commodities)
 }
 
 // Get market info for a specific commodity
 pub async fn get_market_by_commodity(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
     Path(commodity): Path<String>,
 ) -> impl IntoResponse {
     let commodity_upper = commodity.to_uppercase();
@@ -182,6 +246,7 @@ pub async fn get_market_by_commodity(
 
     match config {
         Some(cfg) => {
+            let contract = contract_spec(&state, &commodity_upper).await;
             let market = MarketInfo {
                 address: format!("{}_market_address", commodity_upper),
                 commodity: commodity_upper,
@@ -195,6 +260,7 @@ pub async fn get_market_by_commodity(
                 short_open_interest: 0,
                 funding_rate: 0,
                 is_paused: false,
+                contract,
             };
             Json(market).into_response()
         }
@@ -236,6 +302,21 @@ pub async fn get_market_stats_by_commodity(
     }
 }
 
+// Get trading session and circuit breaker state for a specific commodity
+pub async fn get_trading_status(
+    State(state): State<Arc<AppState>>,
+    Path(commodity): Path<String>,
+) -> impl IntoResponse {
+    let commodity_upper = commodity.to_uppercase();
+
+    if !COMMODITIES.contains_key(&commodity_upper) {
+        return (StatusCode::NOT_FOUND, "Commodity not found").into_response();
+    }
+
+    let status = state.oracle_service.trading_status(&commodity_upper).await;
+    Json(status).into_response()
+}
+
 // Get orderbook for a specific commodity
 pub async fn get_orderbook_by_commodity(
     State(state): State<Arc<AppState>>,
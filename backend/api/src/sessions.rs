@@ -0,0 +1,248 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use crate::markets::{fetch_account_data, parse_commodity_accounts, MarketDirectory};
+
+// =============================================================================
+// Trading Session Calendar
+// =============================================================================
+//
+// Reads each market's on-chain TradingSchedule from perps-core, so the API gates
+// trading exactly as the program does: a schedule only applies while the market's
+// `has_trading_schedule` flag is set, weekly windows are minutes after UTC midnight,
+// holidays are UTC midnight timestamps, and outside a session the market only
+// accepts reductions. Schedule accounts are configured per commodity with
+// TRADING_SCHEDULE_ACCOUNTS, e.g. "OIL=<address>,GOLD=<address>", and the markets
+// themselves with MARKET_ACCOUNTS.
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const MAX_HOLIDAYS: usize = 16;
+
+// Anchor discriminator plus the market pubkey precede the schedule fields
+const SCHEDULE_DATA_OFFSET: usize = 8 + 32;
+const SCHEDULE_DATA_LEN: usize = 4 * 2 * 7 + 8 * MAX_HOLIDAYS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SessionState {
+    Open,
+    ReduceOnly,
+}
+
+/// A trading window within one UTC day: [open_minute, close_minute)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionWindow {
+    pub open_minute: u16,
+    pub close_minute: u16,
+}
+
+impl SessionWindow {
+    fn contains(&self, minute: u16) -> bool {
+        minute >= self.open_minute && minute < self.close_minute
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TradingSchedule {
+    /// Two windows per UTC weekday (0 = Sunday)
+    pub weekly_sessions: [[SessionWindow; 2]; 7],
+    /// UTC midnight timestamps of full-day closures (0 = unused slot)
+    pub holidays: [i64; MAX_HOLIDAYS],
+}
+
+impl TradingSchedule {
+    /// Decode a perps-core TradingSchedule account
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        let data = data.get(SCHEDULE_DATA_OFFSET..SCHEDULE_DATA_OFFSET + SCHEDULE_DATA_LEN)?;
+        let u16_at = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+
+        let mut schedule = Self::default();
+        for (day, windows) in schedule.weekly_sessions.iter_mut().enumerate() {
+            for (slot, window) in windows.iter_mut().enumerate() {
+                let offset = (day * 2 + slot) * 4;
+                window.open_minute = u16_at(offset);
+                window.close_minute = u16_at(offset + 2);
+            }
+        }
+        for (i, holiday) in schedule.holidays.iter_mut().enumerate() {
+            let offset = 4 * 2 * 7 + i * 8;
+            *holiday = i64::from_le_bytes(data[offset..offset + 8].try_into().ok()?);
+        }
+        Some(schedule)
+    }
+
+    /// Same rule as the on-chain `TradingSchedule::is_open`
+    pub fn state_at(&self, unix_timestamp: i64) -> SessionState {
+        let day_start = unix_timestamp - unix_timestamp.rem_euclid(SECONDS_PER_DAY);
+        if self.holidays.iter().any(|&holiday| holiday != 0 && holiday == day_start) {
+            return SessionState::ReduceOnly;
+        }
+
+        // 1970-01-01 was a Thursday
+        let weekday = ((unix_timestamp.div_euclid(SECONDS_PER_DAY) + 4).rem_euclid(7)) as usize;
+        let minute = (unix_timestamp.rem_euclid(SECONDS_PER_DAY) / 60) as u16;
+
+        if self.weekly_sessions[weekday].iter().any(|w| w.contains(minute)) {
+            SessionState::Open
+        } else {
+            SessionState::ReduceOnly
+        }
+    }
+}
+
+// =============================================================================
+// Session Calendar
+// =============================================================================
+
+pub struct SessionCalendar {
+    client: reqwest::Client,
+    rpc_url: String,
+
+    // Schedule account per commodity
+    accounts: HashMap<String, String>,
+
+    // Last schedules read from chain
+    schedules: RwLock<HashMap<String, TradingSchedule>>,
+
+    // Markets whose flag decides whether their schedule applies
+    markets: Arc<MarketDirectory>,
+}
+
+impl SessionCalendar {
+    pub fn new(rpc_url: &str, markets: Arc<MarketDirectory>) -> Self {
+        let accounts = parse_commodity_accounts(&std::env::var("TRADING_SCHEDULE_ACCOUNTS").unwrap_or_default());
+        if accounts.is_empty() {
+            warn!("TRADING_SCHEDULE_ACCOUNTS not set - all markets trade continuously");
+        }
+
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap(),
+            rpc_url: rpc_url.to_string(),
+            accounts,
+            schedules: RwLock::new(HashMap::new()),
+            markets,
+        }
+    }
+
+    /// Session state for a commodity. Only a market with `has_trading_schedule` set
+    /// follows its schedule on-chain; any other, or one not read yet, trades continuously
+    pub async fn session_state(&self, commodity: &str, unix_timestamp: i64) -> SessionState {
+        let has_trading_schedule = self.markets.market(commodity).await
+            .is_some_and(|market| market.has_trading_schedule);
+        if !has_trading_schedule {
+            return SessionState::Open;
+        }
+
+        self.schedules.read().await
+            .get(commodity)
+            .map(|schedule| schedule.state_at(unix_timestamp))
+            .unwrap_or(SessionState::Open)
+    }
+
+    /// Re-read every configured schedule account; a failed read keeps the last schedule
+    pub async fn fetch_schedules(&self) {
+        for (commodity, address) in &self.accounts {
+            match self.fetch_schedule(address).await {
+                Ok(schedule) => {
+                    self.schedules.write().await.insert(commodity.clone(), schedule);
+                }
+                Err(e) => error!("Trading schedule fetch failed for {}: {}", commodity, e),
+            }
+        }
+    }
+
+    async fn fetch_schedule(&self, address: &str) -> Result<TradingSchedule, Box<dyn std::error::Error + Send + Sync>> {
+        let data = fetch_account_data(&self.client, &self.rpc_url, address).await?;
+        Ok(TradingSchedule::from_account_data(&data).ok_or("invalid schedule account data")?)
+    }
+
+    /// Start background schedule refresh task
+    pub fn start_background_updates(self: std::sync::Arc<Self>) {
+        tokio::spawn(async move {
+            loop {
+                self.fetch_schedules().await;
+                info!("Trading schedules loaded: {}", self.schedules.read().await.len());
+
+                // Schedules change rarely; refresh every minute
+                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-10-18 is a Sunday
+    const SUNDAY_MIDNIGHT: i64 = 1792281600;
+    const HOUR: i64 = 3600;
+    const DAY: i64 = 24 * HOUR;
+
+    /// Account bytes for 22:00-24:00 UTC Sunday, 00:00-21:00 and 22:00-24:00 UTC Monday
+    /// to Thursday, 00:00-21:00 UTC Friday, with the given holidays
+    fn schedule_account(holidays: &[i64]) -> Vec<u8> {
+        let weekday = [(0, 21 * 60), (22 * 60, 24 * 60)];
+        let week = [
+            [(0, 0), (22 * 60, 24 * 60)],
+            weekday,
+            weekday,
+            weekday,
+            weekday,
+            [(0, 21 * 60), (0, 0)],
+            [(0, 0), (0, 0)],
+        ];
+
+        let mut data = vec![0u8; SCHEDULE_DATA_OFFSET];
+        for (open, close) in week.iter().flatten() {
+            data.extend_from_slice(&u16::to_le_bytes(*open));
+            data.extend_from_slice(&u16::to_le_bytes(*close));
+        }
+        for i in 0..MAX_HOLIDAYS {
+            data.extend_from_slice(&holidays.get(i).copied().unwrap_or(0).to_le_bytes());
+        }
+        data.extend_from_slice(&[0u8; 33]); // bump, version, padding
+        data
+    }
+
+    #[test]
+    fn test_schedule_week() {
+        let schedule = TradingSchedule::from_account_data(&schedule_account(&[])).unwrap();
+
+        assert_eq!(schedule.state_at(SUNDAY_MIDNIGHT + 21 * HOUR), SessionState::ReduceOnly);
+        assert_eq!(schedule.state_at(SUNDAY_MIDNIGHT + 22 * HOUR), SessionState::Open);
+
+        // Monday daily break 21:00-22:00 UTC
+        let monday = SUNDAY_MIDNIGHT + DAY;
+        assert_eq!(schedule.state_at(monday + 12 * HOUR), SessionState::Open);
+        assert_eq!(schedule.state_at(monday + 21 * HOUR + 30 * 60), SessionState::ReduceOnly);
+        assert_eq!(schedule.state_at(monday + 22 * HOUR), SessionState::Open);
+
+        // Friday closes at 21:00 UTC, Saturday stays closed
+        let friday = SUNDAY_MIDNIGHT + 5 * DAY;
+        assert_eq!(schedule.state_at(friday + 20 * HOUR), SessionState::Open);
+        assert_eq!(schedule.state_at(friday + 21 * HOUR), SessionState::ReduceOnly);
+        assert_eq!(schedule.state_at(friday + 36 * HOUR), SessionState::ReduceOnly);
+    }
+
+    #[test]
+    fn test_holiday_closure() {
+        // Christmas 2026 falls on a Friday; closed for the whole UTC day
+        let christmas = 1798156800;
+        let schedule = TradingSchedule::from_account_data(&schedule_account(&[christmas])).unwrap();
+
+        assert_eq!(schedule.state_at(christmas + 12 * HOUR), SessionState::ReduceOnly);
+        assert_eq!(schedule.state_at(christmas - 12 * HOUR), SessionState::Open);
+    }
+
+    #[test]
+    fn test_truncated_account_rejected() {
+        let data = schedule_account(&[]);
+        assert!(TradingSchedule::from_account_data(&data[..SCHEDULE_DATA_OFFSET + 10]).is_none());
+    }
+}
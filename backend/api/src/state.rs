@@ -1,6 +1,8 @@
 use std::sync::{Arc, RwLock};
 use tokio::sync::RwLock as TokioRwLock;
+use crate::markets::MarketDirectory;
 use crate::oracle::OracleService;
+use crate::sessions::SessionCalendar;
 
 /// Simulated trade from demo bot
 #[derive(Clone, Debug, serde::Serialize)]
@@ -18,6 +20,8 @@ pub struct AppState {
     pub rpc_url: String,
     pub cached_orderbook: RwLock<Option<OrderBookSnapshot>>,
     pub oracle_service: Arc<OracleService>,
+    pub markets: Arc<MarketDirectory>,
+    pub session_calendar: Arc<SessionCalendar>,
     pub recent_trades: Arc<TokioRwLock<Vec<SimulatedTrade>>>,
 }
 
@@ -30,10 +34,14 @@ pub struct OrderBookSnapshot {
 
 impl AppState {
     pub fn new(rpc_url: &str) -> Self {
+        let markets = Arc::new(MarketDirectory::new(rpc_url));
+        let session_calendar = Arc::new(SessionCalendar::new(rpc_url, markets.clone()));
         Self {
             rpc_url: rpc_url.to_string(),
             cached_orderbook: RwLock::new(None),
-            oracle_service: Arc::new(OracleService::new()),
+            oracle_service: Arc::new(OracleService::new(session_calendar.clone())),
+            markets,
+            session_calendar,
             recent_trades: Arc::new(TokioRwLock::new(Vec::new())),
         }
     }
@@ -0,0 +1,363 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::pubkey::Pubkey;
+
+// =============================================================================
+// Versioned perps-core account decoders
+// =============================================================================
+//
+// Every perps-core account carries a version byte after its bump. Version 0 is
+// anything written before versioning; its padding reads the version as zero.
+// Decoders branch on the version instead of assuming the latest layout.
+
+pub const MARKET_DISCRIMINATOR: [u8; 8] = [219, 190, 213, 55, 0, 227, 198, 154];
+pub const POSITION_DISCRIMINATOR: [u8; 8] = [170, 188, 143, 228, 122, 64, 247, 208];
+pub const TRIGGER_ORDER_DISCRIMINATOR: [u8; 8] = [236, 61, 42, 190, 152, 12, 106, 116];
+
+/// Position status offset, identical in every layout version:
+/// 8 + 32 + 32 + 1 + 8 + 8 + 8 + 4 + 8 + 8 + 8 + 8 = 133
+pub const POSITION_STATUS_OFFSET: usize = 133;
+
+/// Version byte offset in the version 1 and 2 position layouts (padding in version 0)
+const POSITION_VERSION_OFFSET: usize = 161;
+
+pub const MARKET_VERSION: u8 = 8;
+pub const POSITION_VERSION: u8 = 2;
+
+type DecodeResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// Market layout; fields after is_paused were appended into padding, so
+// version 0 markets decode with those fields zeroed. Fields appended after
+// `version` exist from version 2 on and are read separately per version;
+// the version 4 accounting and version 5 fee fields are skipped to reach the
+// version 6 index basket, and the version 7 contract specs to reach the version 8
+// risk tiers.
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct MarketData {
+    pub authority: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub vault: Pubkey,
+    pub pyth_price_feed: Pubkey,
+    pub commodity: [u8; 8],
+    pub max_leverage: u32,
+    pub maintenance_margin_ratio: u32,
+    pub initial_margin_ratio: u32,
+    pub taker_fee: u32,
+    pub maker_fee: i32,
+    pub liquidation_fee: u32,
+    pub long_open_interest: u64,
+    pub short_open_interest: u64,
+    pub max_open_interest: u64,
+    pub funding_rate: i64,
+    pub last_funding_time: i64,
+    pub funding_interval: i64,
+    pub insurance_fund: u64,
+    pub total_positions: u64,
+    pub total_trades: u64,
+    pub bump: u8,
+    pub is_paused: bool,
+    pub has_trading_schedule: bool,
+    pub market_type: u8, // 0 = Perpetual, 1 = DatedFuture
+    pub expiry_timestamp: i64,
+    pub mark_price: u64,
+    pub mark_price_updated_at: i64,
+    pub mark_price_half_life: u32,
+    pub reference_price: u64,
+    pub reference_price_updated_at: i64,
+    pub price_band_bps: u16,
+    pub circuit_breaker_tripped: bool,
+    pub status: u8, // 0 = Active, 1 = Settling, 2 = Settled
+    pub version: u8,
+    // Read from the version 2 and 3 suffixes in `decode`
+    #[borsh_skip]
+    pub max_user_open_interest_notional: u64,
+    #[borsh_skip]
+    pub liquidation_mode: u8, // 0 = Fixed, 1 = DutchAuction
+    #[borsh_skip]
+    pub index_components: Vec<IndexComponent>, // Empty for single-feed markets
+    #[borsh_skip]
+    pub risk_tiers: Vec<RiskTier>, // Empty for flat margin ratios
+}
+
+/// Leverage and maintenance margin for positions up to `max_notional` of entry notional
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy)]
+pub struct RiskTier {
+    pub max_notional: u64,
+    pub max_leverage: u32,
+    pub maintenance_margin_ratio: u32,
+}
+
+const MAX_RISK_TIERS: usize = 5;
+
+/// One feed of an index market's basket
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy)]
+pub struct IndexComponent {
+    pub price_feed: Pubkey,
+    pub weight: u64, // Units of this commodity per index unit (6 decimals)
+}
+
+const MAX_INDEX_COMPONENTS: usize = 4;
+
+// Version 2 fields following `version`
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+struct MarketV2Suffix {
+    _max_open_interest_notional: u64,
+    max_user_open_interest_notional: u64,
+    _max_open_interest_imbalance_notional: u64,
+}
+
+// Version 3 fields following the version 2 suffix
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+struct MarketV3Suffix {
+    liquidation_mode: u8,
+    _auction_floor_discount_bps: u16,
+    _auction_cap_discount_bps: u16,
+    _auction_duration: u32,
+}
+
+// Version 4 and 5 fields, skipped
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+struct MarketV4V5Suffix {
+    _accounting: [u64; 8],
+    _fill_authority: Pubkey,
+    _rebate_budget: u64,
+}
+
+// Version 6 fields following the version 5 suffix
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+struct MarketV6Suffix {
+    index_components: [IndexComponent; MAX_INDEX_COMPONENTS],
+    index_component_count: u8,
+}
+
+// Version 7 contract specs, skipped
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+struct MarketV7Suffix {
+    _contract_unit: [u8; 8],
+    _contract_multiplier: u64,
+    _lot_size: u64,
+    _tick_size: u64,
+}
+
+// Version 8 fields following the version 7 suffix
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+struct MarketV8Suffix {
+    risk_tiers: [RiskTier; MAX_RISK_TIERS],
+    risk_tier_count: u8,
+}
+
+impl MarketData {
+    pub fn decode(data: &[u8]) -> DecodeResult<Self> {
+        if data.len() < 8 || data[..8] != MARKET_DISCRIMINATOR {
+            return Err("not a market account".into());
+        }
+
+        let mut rest = &data[8..];
+        let mut market = MarketData::deserialize(&mut rest)?;
+        match market.version {
+            0 | 1 => {}
+            2..=MARKET_VERSION => {
+                let suffix = MarketV2Suffix::deserialize(&mut rest)?;
+                market.max_user_open_interest_notional = suffix.max_user_open_interest_notional;
+                if market.version >= 3 {
+                    let suffix = MarketV3Suffix::deserialize(&mut rest)?;
+                    market.liquidation_mode = suffix.liquidation_mode;
+                }
+                if market.version >= 6 {
+                    MarketV4V5Suffix::deserialize(&mut rest)?;
+                    let suffix = MarketV6Suffix::deserialize(&mut rest)?;
+                    let count = (suffix.index_component_count as usize).min(MAX_INDEX_COMPONENTS);
+                    market.index_components = suffix.index_components[..count].to_vec();
+                }
+                if market.version >= 8 {
+                    MarketV7Suffix::deserialize(&mut rest)?;
+                    let suffix = MarketV8Suffix::deserialize(&mut rest)?;
+                    let count = (suffix.risk_tier_count as usize).min(MAX_RISK_TIERS);
+                    market.risk_tiers = suffix.risk_tiers[..count].to_vec();
+                }
+            }
+            v => return Err(format!("unsupported market version {}", v).into()),
+        }
+        Ok(market)
+    }
+
+    /// Feeds the program expects after `pyth_price_feed` in remaining accounts:
+    /// every basket component but the first, in basket order
+    pub fn index_feeds(&self) -> Vec<Pubkey> {
+        self.index_components.iter().skip(1).map(|c| c.price_feed).collect()
+    }
+
+    /// Maintenance margin the program applies to a position of `entry_notional`: its
+    /// risk tier's, the last tier's above the table, or the flat ratio without tiers
+    pub fn maintenance_margin_for(&self, entry_notional: u64) -> u32 {
+        self.risk_tiers
+            .iter()
+            .find(|tier| entry_notional <= tier.max_notional)
+            .or(self.risk_tiers.last())
+            .map_or(self.maintenance_margin_ratio, |tier| tier.maintenance_margin_ratio)
+    }
+
+    /// Price the program judges liquidations on: the EMA mark once one exists
+    pub fn liquidation_price(&self, oracle_price: u64) -> u64 {
+        if self.version >= 1 && self.mark_price > 0 {
+            self.mark_price
+        } else {
+            oracle_price
+        }
+    }
+
+    /// Liquidations run as Dutch auctions that liquidators fill themselves
+    pub fn uses_liquidation_auctions(&self) -> bool {
+        self.liquidation_mode == 1
+    }
+
+    pub fn is_perpetual(&self) -> bool {
+        self.market_type == 0
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == 0
+    }
+}
+
+// Fields shared by every position layout version
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+struct PositionPrefix {
+    owner: Pubkey,
+    market: Pubkey,
+    side: u8,
+    size: u64,
+    collateral: u64,
+    entry_price: u64,
+    leverage: u32,
+    realized_pnl: i64,
+    last_funding_payment: i64,
+    opened_at: i64,
+    last_updated_at: i64,
+    status: u8,
+    execution_source: u8,
+}
+
+// Version 1 fields following the prefix; version 2 has the same layout
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+struct PositionV1Suffix {
+    position_id: u64,
+    margin_credit: u64,
+    is_wrapped: bool,
+    settlement_claim: u64,
+    bump: u8,
+    version: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct PositionData {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub side: u8, // 0 = Long, 1 = Short
+    pub size: u64,
+    pub collateral: u64,
+    pub entry_price: u64,
+    pub leverage: u32,
+    pub realized_pnl: i64,
+    pub last_funding_payment: i64,
+    pub opened_at: i64,
+    pub last_updated_at: i64,
+    pub status: u8, // 0 = Open, 1 = Closed, 2 = Liquidated
+    pub margin_credit: u64,
+    pub is_wrapped: bool,
+    pub version: u8,
+}
+
+impl PositionData {
+    pub fn decode(data: &[u8]) -> DecodeResult<Self> {
+        if data.len() <= POSITION_VERSION_OFFSET || data[..8] != POSITION_DISCRIMINATOR {
+            return Err("not a position account".into());
+        }
+
+        let mut rest = &data[8..];
+        let prefix = PositionPrefix::deserialize(&mut rest)?;
+
+        let (margin_credit, is_wrapped) = match data[POSITION_VERSION_OFFSET] {
+            // Version 0 ends with the bump; nothing else to read
+            0 => (0, false),
+            1..=POSITION_VERSION => {
+                let suffix = PositionV1Suffix::deserialize(&mut rest)?;
+                (suffix.margin_credit, suffix.is_wrapped)
+            }
+            v => return Err(format!("unsupported position version {}", v).into()),
+        };
+
+        Ok(PositionData {
+            owner: prefix.owner,
+            market: prefix.market,
+            side: prefix.side,
+            size: prefix.size,
+            collateral: prefix.collateral,
+            entry_price: prefix.entry_price,
+            leverage: prefix.leverage,
+            realized_pnl: prefix.realized_pnl,
+            last_funding_payment: prefix.last_funding_payment,
+            opened_at: prefix.opened_at,
+            last_updated_at: prefix.last_updated_at,
+            status: prefix.status,
+            margin_credit,
+            is_wrapped,
+            version: data[POSITION_VERSION_OFFSET],
+        })
+    }
+
+    /// Size times entry price, rounded up like the program's `notional_value`
+    pub fn entry_notional(&self) -> u64 {
+        (self.size as u128 * self.entry_price as u128).div_ceil(1_000_000) as u64
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct TriggerOrderData {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub kind: u8, // 0 = TakeProfit, 1 = StopLoss
+    pub trigger_price: u64,
+    pub acceptable_price: u64,
+    pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl TriggerOrderData {
+    pub fn decode(data: &[u8]) -> DecodeResult<Self> {
+        if data.len() < 8 || data[..8] != TRIGGER_ORDER_DISCRIMINATOR {
+            return Err("not a trigger order account".into());
+        }
+        Ok(TriggerOrderData::deserialize(&mut &data[8..])?)
+    }
+
+    /// Whether `price` fires the order for a position on `side`, like the program's
+    /// `is_triggered`
+    pub fn is_triggered(&self, side: u8, price: u64) -> bool {
+        match (self.kind, side) {
+            (0, 0) | (1, 1) => price >= self.trigger_price,
+            _ => price <= self.trigger_price,
+        }
+    }
+}
+
+// Collateral registry layout: market, authority, then fixed asset slots of
+// mint, pyth_price_feed, haircut_bps, decimals and is_active, then num_assets
+const COLLATERAL_ASSET_LEN: usize = 32 + 32 + 2 + 1 + 1;
+const MAX_COLLATERAL_ASSETS: usize = 8;
+const COLLATERAL_ASSETS_OFFSET: usize = 8 + 32 + 32;
+
+/// Price feeds of every registered collateral asset, which the program reads to value
+/// pledged balances
+pub fn collateral_registry_feeds(data: &[u8]) -> DecodeResult<Vec<Pubkey>> {
+    let num_assets_offset = COLLATERAL_ASSETS_OFFSET + COLLATERAL_ASSET_LEN * MAX_COLLATERAL_ASSETS;
+    let num_assets = *data.get(num_assets_offset).ok_or("collateral registry too short")? as usize;
+
+    (0..num_assets.min(MAX_COLLATERAL_ASSETS))
+        .map(|i| {
+            let offset = COLLATERAL_ASSETS_OFFSET + i * COLLATERAL_ASSET_LEN + 32;
+            Ok(Pubkey::try_from(&data[offset..offset + 32])?)
+        })
+        .collect()
+}
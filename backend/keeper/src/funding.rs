@@ -1,6 +1,5 @@
-use crate::{KeeperConfig, get_market_pda};
-use crate::liquidator::MarketData;
-use borsh::BorshDeserialize;
+use crate::{KeeperConfig, get_market_pda, get_trading_schedule_pda, get_crank_rewards_pda, get_user_account_pda};
+use crate::accounts::MarketData;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
@@ -54,10 +53,10 @@ async fn check_and_update_funding(
 
     // Fetch market data
     let market_account = client.get_account(&market_pda)?;
-    let market_data: MarketData = BorshDeserialize::deserialize(&mut &market_account.data[8..])?;
+    let market_data = MarketData::decode(&market_account.data)?;
 
-    if market_data.is_paused {
-        debug!("Market is paused, skipping funding update");
+    if market_data.is_paused || !market_data.is_active() || !market_data.is_perpetual() {
+        debug!("Market is paused, settling or not a perpetual, skipping funding update");
         return Ok(false);
     }
 
@@ -105,13 +104,31 @@ async fn execute_funding_update(
     // Instruction discriminator for "update_funding" in Anchor
     let discriminator: [u8; 8] = [156, 148, 159, 169, 219, 157, 46, 227];
 
+    // Markets with trading hours need their schedule; otherwise pass the
+    // program id to mark the optional account as absent
+    let (schedule_pda, _) = get_trading_schedule_pda(&config.perps_program_id, market_pda);
+    let trading_schedule = if client.get_account(&schedule_pda).is_ok() {
+        schedule_pda
+    } else {
+        config.perps_program_id
+    };
+
+    let (crank_rewards, keeper_user_account) = crank_reward_accounts(client, config, market_pda)?;
+
+    let mut accounts = vec![
+        AccountMeta::new(config.keypair.pubkey(), true),           // keeper (signer)
+        AccountMeta::new(*market_pda, false),                      // market
+        AccountMeta::new_readonly(market_data.pyth_price_feed, false), // pyth_price_feed
+        AccountMeta::new_readonly(trading_schedule, false),        // trading_schedule (optional)
+        crank_rewards,                                             // crank_rewards (optional)
+        keeper_user_account,                                       // keeper_user_account (optional)
+    ];
+    // An index market's other feeds follow as remaining accounts
+    accounts.extend(market_data.index_feeds().into_iter().map(|feed| AccountMeta::new_readonly(feed, false)));
+
     let instruction = Instruction {
         program_id: config.perps_program_id,
-        accounts: vec![
-            AccountMeta::new(config.keypair.pubkey(), true),           // keeper (signer)
-            AccountMeta::new(*market_pda, false),                      // market
-            AccountMeta::new_readonly(market_data.pyth_price_feed, false), // pyth_price_feed
-        ],
+        accounts,
         data: discriminator.to_vec(),
     };
 
@@ -133,6 +150,29 @@ async fn execute_funding_update(
     Ok(())
 }
 
+/// The optional `crank_rewards` and `keeper_user_account` metas: claimed when the market
+/// pays a reward and we have a user account to credit, otherwise marked absent
+pub fn crank_reward_accounts(
+    client: &RpcClient,
+    config: &KeeperConfig,
+    market_pda: &Pubkey,
+) -> Result<(AccountMeta, AccountMeta), Box<dyn std::error::Error + Send + Sync>> {
+    let (crank_rewards_pda, _) = get_crank_rewards_pda(&config.perps_program_id, market_pda);
+    let (keeper_user_pda, _) = get_user_account_pda(&config.perps_program_id, &config.keypair.pubkey());
+    let reward_accounts = client.get_multiple_accounts(&[crank_rewards_pda, keeper_user_pda])?;
+    if reward_accounts.iter().all(|a| a.is_some()) {
+        Ok((
+            AccountMeta::new(crank_rewards_pda, false),
+            AccountMeta::new(keeper_user_pda, false),
+        ))
+    } else {
+        Ok((
+            AccountMeta::new_readonly(config.perps_program_id, false),
+            AccountMeta::new_readonly(config.perps_program_id, false),
+        ))
+    }
+}
+
 // Calculate what the next funding rate will be (for logging)
 pub fn estimate_funding_rate(long_oi: u64, short_oi: u64) -> i64 {
     let long = long_oi as i128;
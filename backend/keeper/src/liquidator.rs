@@ -1,5 +1,5 @@
-use crate::{KeeperConfig, get_market_pda, get_vault_pda, get_vault_token_pda, get_user_account_pda};
-use borsh::{BorshDeserialize, BorshSerialize};
+use crate::{KeeperConfig, get_market_pda, get_vault_pda, get_vault_token_pda, get_user_account_pda, get_user_market_pda, get_margin_account_pda, get_liquidation_auction_pda, get_collateral_registry_pda};
+use crate::accounts::{collateral_registry_feeds, MarketData, PositionData, POSITION_DISCRIMINATOR, POSITION_STATUS_OFFSET};
 use solana_client::rpc_client::RpcClient;
 use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
 use solana_client::rpc_filter::{Memcmp, RpcFilterType};
@@ -15,50 +15,9 @@ use tokio::time::interval;
 use tracing::{info, error, warn, debug};
 
 const LIQUIDATION_CHECK_INTERVAL_SECS: u64 = 10;
-const POSITION_DISCRIMINATOR: [u8; 8] = [170, 188, 143, 228, 122, 64, 247, 208]; // From Anchor
-
-// Simplified position struct for deserialization
-#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
-pub struct PositionData {
-    pub owner: Pubkey,
-    pub market: Pubkey,
-    pub side: u8, // 0 = Long, 1 = Short
-    pub size: u64,
-    pub collateral: u64,
-    pub entry_price: u64,
-    pub leverage: u32,
-    pub realized_pnl: i64,
-    pub last_funding_payment: i64,
-    pub opened_at: i64,
-    pub last_updated_at: i64,
-    pub status: u8, // 0 = Open, 1 = Closed, 2 = Liquidated
-}
 
-// Market struct for deserialization
-#[derive(BorshDeserialize, BorshSerialize, Debug)]
-pub struct MarketData {
-    pub authority: Pubkey,
-    pub collateral_mint: Pubkey,
-    pub pyth_price_feed: Pubkey,
-    pub commodity: [u8; 8],
-    pub max_leverage: u32,
-    pub initial_margin_ratio: u32,
-    pub maintenance_margin_ratio: u32,
-    pub taker_fee: u32,
-    pub maker_fee: u32,
-    pub liquidation_fee: u32,
-    pub long_open_interest: u64,
-    pub short_open_interest: u64,
-    pub max_open_interest: u64,
-    pub funding_rate: i64,
-    pub funding_interval: i64,
-    pub last_funding_time: i64,
-    pub insurance_fund: u64,
-    pub total_positions: u64,
-    pub total_trades: u64,
-    pub is_paused: bool,
-    pub bump: u8,
-}
+/// Positions per `liquidate_many` transaction, kept well under the transaction size limit
+const LIQUIDATION_BATCH_SIZE: usize = 8;
 
 pub async fn run_liquidation_keeper(client: Arc<RpcClient>, config: Arc<KeeperConfig>) {
     info!("Liquidation keeper started");
@@ -100,43 +59,61 @@ async fn check_and_liquidate(
 
     // Fetch market data
     let market_account = client.get_account(&market_pda)?;
-    let market_data: MarketData = BorshDeserialize::deserialize(&mut &market_account.data[8..])?;
+    let market_data = MarketData::decode(&market_account.data)?;
 
-    if market_data.is_paused {
-        debug!("Market is paused, skipping liquidation check");
+    if market_data.is_paused || !market_data.is_active() {
+        debug!("Market is paused or settling, skipping liquidation check");
         return Ok(0);
     }
 
-    // Fetch oracle price
-    let oracle_price = fetch_oracle_price(client, &market_data.pyth_price_feed)?;
-    debug!("Current oracle price: ${:.2}", oracle_price as f64 / 1_000_000.0);
+    // Fetch oracle price; eligibility is judged on the mark price like on-chain
+    let oracle_price = fetch_index_price(client, &market_data)?;
+    let liquidation_price = market_data.liquidation_price(oracle_price);
+    debug!(
+        "Current oracle price: ${:.2}, mark price: ${:.2}",
+        oracle_price as f64 / 1_000_000.0,
+        liquidation_price as f64 / 1_000_000.0
+    );
 
     // Find all open positions
     let open_positions = fetch_open_positions(client, &config.perps_program_id)?;
     debug!("Found {} open positions", open_positions.len());
 
-    let mut liquidated_count = 0;
+    let mut liquidatable = Vec::new();
 
     for (position_address, position) in open_positions {
         // Check if position is liquidatable
-        let margin_ratio = calculate_margin_ratio(&position, oracle_price);
+        let margin_ratio = calculate_margin_ratio(&position, liquidation_price);
+        let required = market_data.maintenance_margin_for(position.entry_notional());
 
-        if margin_ratio < market_data.maintenance_margin_ratio {
+        if margin_ratio < required {
             info!(
                 "Found liquidatable position: {} (margin: {}%, required: {}%)",
                 position_address,
                 margin_ratio as f64 / 100.0,
-                market_data.maintenance_margin_ratio as f64 / 100.0
+                required as f64 / 100.0
             );
+            liquidatable.push((position_address, position));
+        }
+    }
 
-            match execute_liquidation(client, config, &market_pda, &market_data, &position_address, &position).await {
-                Ok(_) => {
-                    liquidated_count += 1;
-                    info!("Liquidation successful for position {}", position_address);
-                }
-                Err(e) => {
-                    error!("Failed to liquidate position {}: {}", position_address, e);
-                }
+    // In auction mode liquidators fill auctions themselves; the keeper only starts them
+    if market_data.uses_liquidation_auctions() {
+        return start_liquidation_auctions(client, config, &market_pda, &market_data, &liquidatable).await;
+    }
+
+    // The program skips positions that recovered or were taken by another keeper,
+    // so a batch only fails as a whole on market-level problems
+    let mut liquidated_count = 0;
+
+    for batch in liquidatable.chunks(LIQUIDATION_BATCH_SIZE) {
+        match execute_batch_liquidation(client, config, &market_pda, &market_data, batch).await {
+            Ok(_) => {
+                liquidated_count += batch.len() as u32;
+                info!("Batch liquidation submitted for {} positions", batch.len());
+            }
+            Err(e) => {
+                error!("Failed to liquidate batch of {} positions: {}", batch.len(), e);
             }
         }
     }
@@ -144,6 +121,23 @@ async fn check_and_liquidate(
     Ok(liquidated_count)
 }
 
+/// Oracle price as the program reads it: the single feed, or the weighted basket sum
+pub fn fetch_index_price(
+    client: &RpcClient,
+    market_data: &MarketData,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    if market_data.index_components.is_empty() {
+        return fetch_oracle_price(client, &market_data.pyth_price_feed);
+    }
+
+    let mut index_price = 0u64;
+    for component in &market_data.index_components {
+        let price = fetch_oracle_price(client, &component.price_feed)?;
+        index_price += (price as u128 * component.weight as u128 / 1_000_000) as u64;
+    }
+    Ok(index_price)
+}
+
 fn fetch_oracle_price(
     client: &RpcClient,
     pyth_feed: &Pubkey,
@@ -175,8 +169,7 @@ fn fetch_open_positions(
     // Filter for position accounts with status = Open (0)
     let filters = vec![
         RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, POSITION_DISCRIMINATOR.to_vec())),
-        // Status at offset 8 + 32 + 32 + 1 + 8 + 8 + 8 + 4 + 8 + 8 + 8 + 8 = 133
-        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(133, vec![0])), // Open status
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(POSITION_STATUS_OFFSET, vec![0])), // Open status
     ];
 
     let config = RpcProgramAccountsConfig {
@@ -192,11 +185,10 @@ fn fetch_open_positions(
 
     let mut positions = Vec::new();
     for (pubkey, account) in accounts {
-        if let Ok(position) = PositionData::deserialize(&mut &account.data[8..]) {
-            if position.status == 0 {
-                // Open status
-                positions.push((pubkey, position));
-            }
+        match PositionData::decode(&account.data) {
+            Ok(position) if position.status == 0 => positions.push((pubkey, position)),
+            Ok(_) => {}
+            Err(e) => warn!("Skipping position {}: {}", pubkey, e),
         }
     }
 
@@ -231,17 +223,94 @@ fn calculate_margin_ratio(position: &PositionData, current_price: u64) -> u32 {
     ((equity * 10000) / notional) as u32
 }
 
-async fn execute_liquidation(
+async fn start_liquidation_auctions(
+    client: &RpcClient,
+    config: &KeeperConfig,
+    market_pda: &Pubkey,
+    market_data: &MarketData,
+    liquidatable: &[(Pubkey, PositionData)],
+) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+    // Skip positions whose auction is already running
+    let auctions: Vec<Pubkey> = liquidatable
+        .iter()
+        .map(|(position_address, _)| get_liquidation_auction_pda(&config.perps_program_id, position_address).0)
+        .collect();
+    let existing = client.get_multiple_accounts(&auctions)?;
+
+    // Positions with margin credit are judged with their pledged collateral, valued
+    // through the registry's asset feeds
+    let (registry_pda, _) = get_collateral_registry_pda(&config.perps_program_id, market_pda);
+    let asset_feeds = if liquidatable.iter().any(|(_, position)| position.margin_credit > 0) {
+        collateral_registry_feeds(&client.get_account(&registry_pda)?.data)?
+    } else {
+        Vec::new()
+    };
+
+    // Instruction discriminator for "start_liquidation_auction" in Anchor
+    let discriminator: [u8; 8] = [32, 210, 115, 53, 58, 3, 225, 120];
+
+    let mut started = 0;
+    for (((position_address, position), auction), existing) in liquidatable.iter().zip(auctions).zip(existing) {
+        if existing.is_some() {
+            continue;
+        }
+
+        let mut accounts = vec![
+            AccountMeta::new(config.keypair.pubkey(), true),     // starter (signer)
+            AccountMeta::new(*market_pda, false),                // market
+            AccountMeta::new_readonly(*position_address, false), // position
+            AccountMeta::new(auction, false),                    // auction
+            AccountMeta::new_readonly(market_data.pyth_price_feed, false), // pyth_price_feed
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // system_program
+        ];
+        let (margin_account_pda, _) = get_margin_account_pda(&config.perps_program_id, market_pda, &position.owner);
+        if position.margin_credit > 0 {
+            accounts.push(AccountMeta::new_readonly(registry_pda, false));        // collateral_registry
+            accounts.push(AccountMeta::new_readonly(margin_account_pda, false));  // margin_account
+        } else {
+            accounts.push(AccountMeta::new_readonly(config.perps_program_id, false));
+            accounts.push(AccountMeta::new_readonly(config.perps_program_id, false));
+        }
+        accounts.extend(market_data.index_feeds().into_iter().map(|feed| AccountMeta::new_readonly(feed, false)));
+        if position.margin_credit > 0 {
+            accounts.extend(asset_feeds.iter().map(|feed| AccountMeta::new_readonly(*feed, false)));
+        }
+
+        let instruction = Instruction {
+            program_id: config.perps_program_id,
+            accounts,
+            data: discriminator.to_vec(),
+        };
+
+        let recent_blockhash = client.get_latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&config.keypair.pubkey()),
+            &[&config.keypair],
+            recent_blockhash,
+        );
+
+        match client.send_and_confirm_transaction(&transaction) {
+            Ok(signature) => {
+                started += 1;
+                info!("Liquidation auction started for {}: {}", position_address, signature);
+            }
+            Err(e) => error!("Failed to start auction for {}: {}", position_address, e),
+        }
+    }
+
+    Ok(started)
+}
+
+async fn execute_batch_liquidation(
     client: &RpcClient,
     config: &KeeperConfig,
     market_pda: &Pubkey,
     market_data: &MarketData,
-    position_address: &Pubkey,
-    position: &PositionData,
+    batch: &[(Pubkey, PositionData)],
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let (vault_pda, _) = get_vault_pda(&config.perps_program_id, market_pda);
     let (vault_token_pda, _) = get_vault_token_pda(&config.perps_program_id, market_pda);
-    let (user_account_pda, _) = get_user_account_pda(&config.perps_program_id, &position.owner);
 
     // Get liquidator's token account
     let liquidator_token_account = get_associated_token_address(
@@ -249,24 +318,59 @@ async fn execute_liquidation(
         &market_data.collateral_mint,
     );
 
-    // Build liquidate instruction
-    // Instruction discriminator for "liquidate" in Anchor
-    let discriminator: [u8; 8] = [223, 179, 226, 125, 48, 46, 39, 74];
+    // Instruction discriminator for "liquidate_many" in Anchor
+    let discriminator: [u8; 8] = [201, 148, 149, 219, 234, 229, 167, 108];
+
+    let mut accounts = vec![
+        AccountMeta::new(config.keypair.pubkey(), true),     // liquidator (signer)
+        AccountMeta::new(*market_pda, false),                // market
+        AccountMeta::new(vault_pda, false),                  // vault
+        AccountMeta::new(vault_token_pda, false),            // vault_token_account
+        AccountMeta::new(liquidator_token_account, false),   // liquidator_token_account
+        AccountMeta::new_readonly(market_data.pyth_price_feed, false), // pyth_price_feed
+        AccountMeta::new_readonly(spl_token::id(), false),   // token_program
+        match config.competition {                           // competition, program id if none
+            Some(competition) => AccountMeta::new(competition, false),
+            None => AccountMeta::new_readonly(config.perps_program_id, false),
+        },
+    ];
+
+    // Positions with margin credit are judged with their pledged collateral, valued
+    // through the registry's asset feeds
+    let (registry_pda, _) = get_collateral_registry_pda(&config.perps_program_id, market_pda);
+    let asset_feeds = if batch.iter().any(|(_, position)| position.margin_credit > 0) {
+        accounts.push(AccountMeta::new_readonly(registry_pda, false)); // collateral_registry
+        collateral_registry_feeds(&client.get_account(&registry_pda)?.data)?
+    } else {
+        accounts.push(AccountMeta::new_readonly(config.perps_program_id, false));
+        Vec::new()
+    };
+
+    // An index market's other feeds lead the remaining accounts, then the asset feeds
+    for feed in market_data.index_feeds() {
+        accounts.push(AccountMeta::new_readonly(feed, false));
+    }
+    accounts.extend(asset_feeds.iter().map(|feed| AccountMeta::new_readonly(*feed, false)));
+
+    for (position_address, position) in batch {
+        let (user_account_pda, _) = get_user_account_pda(&config.perps_program_id, &position.owner);
+        accounts.push(AccountMeta::new(*position_address, false));
+        accounts.push(AccountMeta::new(user_account_pda, false));
+        // Positions that drew margin credit settle it against the owner's margin account
+        if position.margin_credit > 0 {
+            let (margin_account_pda, _) = get_margin_account_pda(&config.perps_program_id, market_pda, &position.owner);
+            accounts.push(AccountMeta::new(margin_account_pda, false));
+        } else {
+            accounts.push(AccountMeta::new_readonly(config.perps_program_id, false));
+        }
+        // The owner's user market PDA follows, initialized or not
+        let (user_market_pda, _) = get_user_market_pda(&config.perps_program_id, market_pda, &position.owner);
+        accounts.push(AccountMeta::new(user_market_pda, false));
+    }
 
     let instruction = Instruction {
         program_id: config.perps_program_id,
-        accounts: vec![
-            AccountMeta::new(config.keypair.pubkey(), true),     // liquidator (signer)
-            AccountMeta::new_readonly(position.owner, false),    // position_owner
-            AccountMeta::new(user_account_pda, false),           // user_account
-            AccountMeta::new(*market_pda, false),                // market
-            AccountMeta::new(*position_address, false),          // position
-            AccountMeta::new(vault_pda, false),                  // vault
-            AccountMeta::new(vault_token_pda, false),            // vault_token_account
-            AccountMeta::new(liquidator_token_account, false),   // liquidator_token_account
-            AccountMeta::new_readonly(market_data.pyth_price_feed, false), // pyth_price_feed
-            AccountMeta::new_readonly(spl_token::id(), false),   // token_program
-        ],
+        accounts,
         data: discriminator.to_vec(),
     };
 
@@ -283,7 +387,7 @@ async fn execute_liquidation(
 
     // Send transaction
     let signature = client.send_and_confirm_transaction(&transaction)?;
-    info!("Liquidation tx: {}", signature);
+    info!("Batch liquidation tx: {}", signature);
 
     Ok(())
 }
@@ -7,8 +7,10 @@ use solana_sdk::{
 use std::{str::FromStr, sync::Arc};
 use tracing::{info, error};
 
+mod accounts;
 mod funding;
 mod liquidator;
+mod triggers;
 
 // Program configuration
 pub struct KeeperConfig {
@@ -16,6 +18,7 @@ pub struct KeeperConfig {
     pub perps_program_id: Pubkey,
     pub usdc_mint: Pubkey,
     pub keypair: Keypair,
+    pub competition: Option<Pubkey>,  // Competition batch liquidations are recorded in
 }
 
 #[tokio::main]
@@ -43,6 +46,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let usdc_mint = std::env::var("USDC_MINT")
         .unwrap_or_else(|_| "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU".to_string());
 
+    let competition = std::env::var("COMPETITION").ok();
+
     info!("========================================");
     info!("   Oil Perps Keeper Bot Starting");
     info!("========================================");
@@ -70,6 +75,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         perps_program_id: Pubkey::from_str(&perps_program_id)?,
         usdc_mint: Pubkey::from_str(&usdc_mint)?,
         keypair,
+        competition: competition.as_deref().map(Pubkey::from_str).transpose()?,
     };
 
     let config = Arc::new(config);
@@ -79,6 +85,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let funding_config = config.clone();
     let liquidator_client = client.clone();
     let liquidator_config = config.clone();
+    let trigger_client = client.clone();
+    let trigger_config = config.clone();
 
     info!("Starting keeper services...");
 
@@ -90,7 +98,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         liquidator::run_liquidation_keeper(liquidator_client, liquidator_config).await;
     });
 
-    // Wait for both tasks
+    let trigger_handle = tokio::spawn(async move {
+        triggers::run_trigger_keeper(trigger_client, trigger_config).await;
+    });
+
+    // Wait for the tasks
     tokio::select! {
         result = funding_handle => {
             error!("Funding keeper exited: {:?}", result);
@@ -98,6 +110,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         result = liquidator_handle => {
             error!("Liquidation keeper exited: {:?}", result);
         }
+        result = trigger_handle => {
+            error!("Trigger order keeper exited: {:?}", result);
+        }
     }
 
     Ok(())
@@ -131,9 +146,51 @@ pub fn get_vault_token_pda(program_id: &Pubkey, market: &Pubkey) -> (Pubkey, u8)
     )
 }
 
+pub fn get_trading_schedule_pda(program_id: &Pubkey, market: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"trading_schedule", market.as_ref()],
+        program_id,
+    )
+}
+
+pub fn get_crank_rewards_pda(program_id: &Pubkey, market: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"crank_rewards", market.as_ref()],
+        program_id,
+    )
+}
+
 pub fn get_user_account_pda(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[b"user", owner.as_ref()],
         program_id,
     )
 }
+
+pub fn get_liquidation_auction_pda(program_id: &Pubkey, position: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"liquidation_auction", position.as_ref()],
+        program_id,
+    )
+}
+
+pub fn get_user_market_pda(program_id: &Pubkey, market: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"user_market", market.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+pub fn get_collateral_registry_pda(program_id: &Pubkey, market: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"collateral_registry", market.as_ref()],
+        program_id,
+    )
+}
+
+pub fn get_margin_account_pda(program_id: &Pubkey, market: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"margin_account", market.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
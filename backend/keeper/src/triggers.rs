@@ -0,0 +1,260 @@
+use crate::{KeeperConfig, get_market_pda, get_user_account_pda, get_user_market_pda, get_margin_account_pda};
+use crate::accounts::{MarketData, PositionData, TriggerOrderData, TRIGGER_ORDER_DISCRIMINATOR};
+use crate::funding::crank_reward_accounts;
+use crate::liquidator::fetch_index_price;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::time::interval;
+use tracing::{info, error, warn, debug};
+
+const TRIGGER_CHECK_INTERVAL_SECS: u64 = 10;
+
+/// Trigger order market offset: after the discriminator and owner
+const TRIGGER_ORDER_MARKET_OFFSET: usize = 8 + 32;
+
+pub async fn run_trigger_keeper(client: Arc<RpcClient>, config: Arc<KeeperConfig>) {
+    info!("Trigger order keeper started");
+    info!("Checking trigger orders every {} seconds", TRIGGER_CHECK_INTERVAL_SECS);
+
+    let mut check_interval = interval(Duration::from_secs(TRIGGER_CHECK_INTERVAL_SECS));
+    let mut consecutive_errors = 0;
+
+    loop {
+        check_interval.tick().await;
+
+        match check_trigger_orders(&client, &config).await {
+            Ok(cranked) => {
+                consecutive_errors = 0;
+                if cranked > 0 {
+                    info!("Executed or reaped {} trigger orders", cranked);
+                }
+            }
+            Err(e) => {
+                consecutive_errors += 1;
+                error!("Trigger order check error (attempt {}): {}", consecutive_errors, e);
+
+                if consecutive_errors >= 5 {
+                    warn!("Too many consecutive errors, backing off...");
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    consecutive_errors = 0;
+                }
+            }
+        }
+    }
+}
+
+async fn check_trigger_orders(
+    client: &RpcClient,
+    config: &KeeperConfig,
+) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+    let (market_pda, _) = get_market_pda(&config.perps_program_id, &config.usdc_mint);
+
+    let market_account = client.get_account(&market_pda)?;
+    let market_data = MarketData::decode(&market_account.data)?;
+
+    let orders = fetch_trigger_orders(client, &config.perps_program_id, &market_pda)?;
+    if orders.is_empty() {
+        return Ok(0);
+    }
+    debug!("Found {} trigger orders", orders.len());
+
+    let position_addresses: Vec<Pubkey> = orders.iter().map(|(_, order)| order.position).collect();
+    let positions = client.get_multiple_accounts(&position_addresses)?;
+
+    // Orders only execute on an active market; stale ones can be reaped regardless
+    let oracle_price = if market_data.is_paused || !market_data.is_active() {
+        None
+    } else {
+        Some(fetch_index_price(client, &market_data)?)
+    };
+
+    let mut cranked = 0;
+    for ((order_address, order), position) in orders.iter().zip(positions) {
+        let position = match position.map(|account| PositionData::decode(&account.data)) {
+            Some(Ok(position)) => position,
+            Some(Err(e)) => {
+                warn!("Skipping trigger order {}: {}", order_address, e);
+                continue;
+            }
+            None => continue,
+        };
+
+        // The position was closed or changed hands without the order
+        let result = if position.status != 0 || position.owner != order.owner {
+            reap_trigger_order(client, config, &market_pda, order_address, order, position.status == 2).await
+        } else if oracle_price.is_some_and(|price| !position.is_wrapped && order.is_triggered(position.side, price)) {
+            execute_trigger_order(client, config, &market_pda, &market_data, order_address, order, &position).await
+        } else {
+            continue;
+        };
+
+        match result {
+            Ok(_) => cranked += 1,
+            Err(e) => error!("Failed to crank trigger order {}: {}", order_address, e),
+        }
+    }
+
+    Ok(cranked)
+}
+
+fn fetch_trigger_orders(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    market_pda: &Pubkey,
+) -> Result<Vec<(Pubkey, TriggerOrderData)>, Box<dyn std::error::Error + Send + Sync>> {
+    let filters = vec![
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, TRIGGER_ORDER_DISCRIMINATOR.to_vec())),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(TRIGGER_ORDER_MARKET_OFFSET, market_pda.to_bytes().to_vec())),
+    ];
+
+    let rpc_config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let accounts = client.get_program_accounts_with_config(program_id, rpc_config)?;
+
+    let mut orders = Vec::new();
+    for (pubkey, account) in accounts {
+        match TriggerOrderData::decode(&account.data) {
+            Ok(order) => orders.push((pubkey, order)),
+            Err(e) => warn!("Skipping trigger order {}: {}", pubkey, e),
+        }
+    }
+
+    Ok(orders)
+}
+
+async fn execute_trigger_order(
+    client: &RpcClient,
+    config: &KeeperConfig,
+    market_pda: &Pubkey,
+    market_data: &MarketData,
+    order_address: &Pubkey,
+    order: &TriggerOrderData,
+    position: &PositionData,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Instruction discriminator for "execute_trigger_order" in Anchor
+    let discriminator: [u8; 8] = [105, 10, 104, 136, 215, 134, 84, 171];
+
+    let absent = AccountMeta::new_readonly(config.perps_program_id, false);
+    let (user_account_pda, _) = get_user_account_pda(&config.perps_program_id, &order.owner);
+    let (user_market_pda, _) = get_user_market_pda(&config.perps_program_id, market_pda, &order.owner);
+
+    // Positions that drew margin credit settle it against the owner's margin account
+    let margin_account = if position.margin_credit > 0 {
+        let (margin_account_pda, _) = get_margin_account_pda(&config.perps_program_id, market_pda, &order.owner);
+        AccountMeta::new(margin_account_pda, false)
+    } else {
+        absent.clone()
+    };
+
+    // Owners running their own orders earn no reward
+    let (crank_rewards, keeper_user_account) = if order.owner == config.keypair.pubkey() {
+        (absent.clone(), absent.clone())
+    } else {
+        crank_reward_accounts(client, config, market_pda)?
+    };
+
+    let mut accounts = vec![
+        AccountMeta::new(config.keypair.pubkey(), true),           // keeper (signer)
+        AccountMeta::new(order.owner, false),                      // owner
+        AccountMeta::new(user_account_pda, false),                 // user_account
+        AccountMeta::new(*market_pda, false),                      // market
+        AccountMeta::new(order.position, false),                   // position
+        AccountMeta::new(*order_address, false),                   // trigger_order
+        AccountMeta::new_readonly(market_data.pyth_price_feed, false), // pyth_price_feed
+        absent.clone(),                                            // user_referral (optional)
+        absent.clone(),                                            // referral_code (optional)
+        margin_account,                                            // margin_account (optional)
+        AccountMeta::new(user_market_pda, false),                  // user_market
+        match config.competition {                                 // competition, program id if none
+            Some(competition) => AccountMeta::new(competition, false),
+            None => absent.clone(),
+        },
+        crank_rewards,                                             // crank_rewards (optional)
+        keeper_user_account,                                       // keeper_user_account (optional)
+    ];
+    // An index market's other feeds follow as remaining accounts
+    accounts.extend(market_data.index_feeds().into_iter().map(|feed| AccountMeta::new_readonly(feed, false)));
+
+    send_instruction(client, config, accounts, discriminator)?;
+    info!("Trigger order executed for position {}", order.position);
+
+    Ok(())
+}
+
+async fn reap_trigger_order(
+    client: &RpcClient,
+    config: &KeeperConfig,
+    market_pda: &Pubkey,
+    order_address: &Pubkey,
+    order: &TriggerOrderData,
+    liquidated: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Instruction discriminator for "reap_trigger_order" in Anchor
+    let discriminator: [u8; 8] = [168, 201, 33, 107, 103, 6, 51, 36];
+
+    // Only orders a liquidation left behind earn the reward, and never our own
+    let (crank_rewards, keeper_user_account) = if !liquidated || order.owner == config.keypair.pubkey() {
+        (
+            AccountMeta::new_readonly(config.perps_program_id, false),
+            AccountMeta::new_readonly(config.perps_program_id, false),
+        )
+    } else {
+        crank_reward_accounts(client, config, market_pda)?
+    };
+
+    let accounts = vec![
+        AccountMeta::new(config.keypair.pubkey(), true),           // keeper (signer)
+        AccountMeta::new(*market_pda, false),                      // market
+        AccountMeta::new_readonly(order.position, false),          // position
+        AccountMeta::new(*order_address, false),                   // trigger_order
+        AccountMeta::new(order.owner, false),                      // owner
+        crank_rewards,                                             // crank_rewards (optional)
+        keeper_user_account,                                       // keeper_user_account (optional)
+    ];
+
+    send_instruction(client, config, accounts, discriminator)?;
+    info!("Stale trigger order reaped for position {}", order.position);
+
+    Ok(())
+}
+
+fn send_instruction(
+    client: &RpcClient,
+    config: &KeeperConfig,
+    accounts: Vec<AccountMeta>,
+    discriminator: [u8; 8],
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let instruction = Instruction {
+        program_id: config.perps_program_id,
+        accounts,
+        data: discriminator.to_vec(),
+    };
+
+    let recent_blockhash = client.get_latest_blockhash()?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&config.keypair.pubkey()),
+        &[&config.keypair],
+        recent_blockhash,
+    );
+
+    let signature = client.send_and_confirm_transaction(&transaction)?;
+    debug!("Trigger order tx: {}", signature);
+
+    Ok(())
+}
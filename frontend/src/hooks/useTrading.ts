@@ -10,6 +10,7 @@ import {
   getVaultPDA,
   getVaultTokenAccountPDA,
   getPositionPDA,
+  getUserMarketPDA,
 } from '../utils/pda';
 import { parseAnchorError, getExplorerUrl } from '../utils/transaction';
 import { useMarketStore } from '../stores/marketStore';
@@ -20,6 +21,25 @@ import {
   LEVERAGE_DECIMALS,
 } from '../config/program';
 
+// Orders not filled within this window are rejected on-chain
+const ORDER_DEADLINE_SECS = 60;
+const U64_MAX = new BN('18446744073709551615');
+
+function orderDeadline(): BN {
+  return new BN(Math.floor(Date.now() / 1000) + ORDER_DEADLINE_SECS);
+}
+
+/**
+ * Convert an optional acceptable price into the on-chain bound.
+ * Buys are capped from above, sells from below; no price means no bound.
+ */
+function acceptablePriceBound(isBuy: boolean, acceptablePrice?: number): BN {
+  if (acceptablePrice === undefined) {
+    return isBuy ? U64_MAX : new BN(0);
+  }
+  return new BN(Math.round(acceptablePrice * Math.pow(10, PRICE_DECIMALS)));
+}
+
 export interface TradingState {
   isLoading: boolean;
   error: string | null;
@@ -217,7 +237,8 @@ export function useTrading() {
     async (
       side: 'long' | 'short',
       size: number,
-      leverage: number
+      leverage: number,
+      acceptablePrice?: number
     ): Promise<string> => {
       if (!program || !publicKey) {
         throw new Error('Wallet not connected');
@@ -233,9 +254,9 @@ export function useTrading() {
         const [userAccountPda] = getUserAccountPDA(publicKey);
         const [marketPda] = getMarketPDA(USDC_MINT);
 
-        // Fetch market to get total_positions for position PDA
-        const marketAccount = await program.account.market.fetch(marketPda);
-        const positionIndex = (marketAccount.totalPositions as BN).toNumber();
+        // Fetch user account to get next_position_id for position PDA
+        const userAccount = await program.account.userAccount.fetch(userAccountPda);
+        const positionIndex = (userAccount.nextPositionId as BN).toNumber();
 
         const [positionPda] = getPositionPDA(publicKey, marketPda, positionIndex);
 
@@ -262,14 +283,18 @@ export function useTrading() {
             side: sideValue,
             size: sizeInUnits,
             leverage: leverageWithDecimals,
+            acceptablePrice: acceptablePriceBound(side === 'long', acceptablePrice),
+            deadline: orderDeadline(),
           })
           .accounts({
             owner: publicKey,
+            payer: publicKey,
             userAccount: userAccountPda,
             market: marketPda,
             position: positionPda,
             pythPriceFeed,
             systemProgram: SystemProgram.programId,
+            userMarket: getUserMarketPDA(marketPda, publicKey)[0],
           })
           .rpc();
 
@@ -301,7 +326,7 @@ export function useTrading() {
    * Close an existing position
    */
   const closePosition = useCallback(
-    async (positionAddress: string): Promise<string> => {
+    async (positionAddress: string, acceptablePrice?: number): Promise<string> => {
       if (!program || !publicKey) {
         throw new Error('Wallet not connected');
       }
@@ -331,9 +356,17 @@ export function useTrading() {
         console.log('Closing position:', positionAddress);
 
         const signature = await program.methods
-          .closePosition()
+          .closePosition({
+            // Closing a short buys, closing a long sells
+            acceptablePrice: acceptablePriceBound(
+              (positionAccount.side as { long?: object }).long === undefined,
+              acceptablePrice
+            ),
+            deadline: orderDeadline(),
+          })
           .accounts({
             owner: publicKey,
+            payer: publicKey,
             userAccount: userAccountPda,
             market: marketPda,
             position: positionPubkey,
@@ -342,6 +375,8 @@ export function useTrading() {
             userTokenAccount,
             pythPriceFeed: marketAccount.pythPriceFeed as PublicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            userMarket: getUserMarketPDA(marketPda, publicKey)[0],
           })
           .rpc();
 
@@ -45,9 +45,21 @@ export function getVaultTokenAccountPDA(market: PublicKey): [PublicKey, number]
   );
 }
 
+/**
+ * Derive User Market PDA
+ * Seeds: ["user_market", market_pubkey, owner_pubkey]
+ */
+export function getUserMarketPDA(market: PublicKey, owner: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('user_market'), market.toBuffer(), owner.toBuffer()],
+    PERPS_CORE_PROGRAM_ID
+  );
+}
+
 /**
  * Derive Position PDA
  * Seeds: ["position", owner_pubkey, market_pubkey, position_index_as_le_bytes]
+ * The index is the owner's UserAccount.next_position_id at open time
  */
 export function getPositionPDA(
   owner: PublicKey,
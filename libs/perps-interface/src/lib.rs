@@ -0,0 +1,55 @@
+//! CPI interface for programs that trade perps-core positions.
+//!
+//! A calling program owns positions through one of its own PDAs: it signs as `owner`
+//! with the PDA's seeds and pays rent from any signer passed as `payer`, so the owner
+//! may hold data of its own. Every position instruction returns a [`PositionFill`]
+//! through return data, which the wrappers here decode.
+//!
+//! Index markets price off a basket of feeds: pass the basket's feeds after the first
+//! as the context's remaining accounts, in basket order.
+
+pub mod pda;
+
+use anchor_lang::prelude::*;
+use perps_core::cpi::accounts::{ClosePosition, IncreasePosition, OpenPosition, ReducePosition};
+
+pub use perps_core::cpi::accounts;
+pub use perps_core::instructions::{
+    ClosePositionParams, IncreasePositionParams, OpenPositionParams, ReducePositionParams,
+};
+pub use perps_core::program::PerpsCore;
+pub use perps_core::state::{PositionFill, Side};
+pub use perps_core::ID;
+
+/// `OpenPositionParams::side` for a long
+pub const SIDE_LONG: u8 = 0;
+/// `OpenPositionParams::side` for a short
+pub const SIDE_SHORT: u8 = 1;
+
+pub fn open_position<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, OpenPosition<'info>>,
+    params: OpenPositionParams,
+) -> Result<PositionFill> {
+    Ok(perps_core::cpi::open_position(ctx, params)?.get())
+}
+
+pub fn increase_position<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, IncreasePosition<'info>>,
+    params: IncreasePositionParams,
+) -> Result<PositionFill> {
+    Ok(perps_core::cpi::increase_position(ctx, params)?.get())
+}
+
+pub fn reduce_position<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, ReducePosition<'info>>,
+    params: ReducePositionParams,
+) -> Result<PositionFill> {
+    Ok(perps_core::cpi::reduce_position(ctx, params)?.get())
+}
+
+pub fn close_position<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, ClosePosition<'info>>,
+    params: ClosePositionParams,
+) -> Result<PositionFill> {
+    Ok(perps_core::cpi::close_position(ctx, params)?.get())
+}
@@ -0,0 +1,33 @@
+//! Addresses of the perps-core accounts a calling program passes in
+
+use anchor_lang::prelude::*;
+
+/// Market for `collateral_mint`; dated futures also take their expiry
+pub fn market(collateral_mint: &Pubkey, expiry_timestamp: Option<i64>) -> (Pubkey, u8) {
+    let series = expiry_timestamp.map(|expiry| expiry.to_le_bytes().to_vec()).unwrap_or_default();
+    Pubkey::find_program_address(&[b"market", collateral_mint.as_ref(), &series], &crate::ID)
+}
+
+pub fn user_account(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"user", owner.as_ref()], &crate::ID)
+}
+
+pub fn user_market(market: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"user_market", market.as_ref(), owner.as_ref()], &crate::ID)
+}
+
+/// Position opened with the owner's `UserAccount::next_position_id` at the time
+pub fn position(owner: &Pubkey, market: &Pubkey, position_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"position", owner.as_ref(), market.as_ref(), &position_id.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+pub fn vault(market: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", market.as_ref()], &crate::ID)
+}
+
+pub fn vault_token_account(market: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_token", market.as_ref()], &crate::ID)
+}
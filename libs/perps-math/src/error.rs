@@ -0,0 +1,22 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    Overflow,           // Result does not fit the target type
+    DivisionByZero,
+    Negative,           // Signed value where only a non-negative one is meaningful
+}
+
+pub type MathResult<T> = Result<T, MathError>;
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathError::Overflow => write!(f, "math overflow"),
+            MathError::DivisionByZero => write!(f, "division by zero"),
+            MathError::Negative => write!(f, "unexpected negative value"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
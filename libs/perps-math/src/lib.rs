@@ -0,0 +1,186 @@
+//! Checked fixed-point math shared by the on-chain programs.
+//!
+//! Prices are 6-decimal fixed point, sizes are base units and rates are basis points.
+//! Every operation that divides takes an explicit [`Rounding`], and callers pick the
+//! direction that favours the protocol: fees, margin requirements and debts round up,
+//! payouts, rewards and PnL round down. Nothing truncates silently; results that do
+//! not fit come back as a [`MathError`].
+
+mod error;
+mod types;
+
+pub use error::{MathError, MathResult};
+pub use types::{Bps, Price, Size};
+
+pub const PRICE_DECIMALS: u32 = 6;
+pub const PRICE_SCALE: u64 = 1_000_000;
+pub const BPS_SCALE: u64 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,   // Toward negative infinity
+    Up,     // Toward positive infinity
+}
+
+/// `a * b / denominator` with a 128-bit intermediate
+pub fn mul_div(a: u64, b: u64, denominator: u64, rounding: Rounding) -> MathResult<u64> {
+    to_u64(mul_div_wide(a as u128, b as u128, denominator as u128, rounding)?)
+}
+
+/// `a * b / denominator` kept in 128 bits, for comparisons against caps
+pub fn mul_div_wide(a: u128, b: u128, denominator: u128, rounding: Rounding) -> MathResult<u128> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    let product = a.checked_mul(b).ok_or(MathError::Overflow)?;
+    let quotient = product / denominator;
+    let remainder = product % denominator;
+    match rounding {
+        Rounding::Up if remainder > 0 => Ok(quotient + 1),
+        _ => Ok(quotient),
+    }
+}
+
+/// Signed `a * b / denominator`; `Rounding::Down` floors, so a loss is never understated
+pub fn mul_div_signed(a: i64, b: u64, denominator: u64, rounding: Rounding) -> MathResult<i64> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    let product = (a as i128)
+        .checked_mul(b as i128)
+        .ok_or(MathError::Overflow)?;
+    let denominator = denominator as i128;
+    let quotient = match rounding {
+        Rounding::Down => product.div_euclid(denominator),
+        Rounding::Up => -(-product).div_euclid(denominator),
+    };
+    i64::try_from(quotient).map_err(|_| MathError::Overflow)
+}
+
+/// An oracle's `price * 10^expo` as a 6-decimal price
+pub fn scale_price(price: i64, expo: i32, rounding: Rounding) -> MathResult<u64> {
+    let price = to_unsigned(price)?;
+    let shift = expo.checked_add(PRICE_DECIMALS as i32).ok_or(MathError::Overflow)?;
+    let factor = 10u64.checked_pow(shift.unsigned_abs()).ok_or(MathError::Overflow)?;
+    if shift >= 0 {
+        price.checked_mul(factor).ok_or(MathError::Overflow)
+    } else {
+        mul_div(price, 1, factor, rounding)
+    }
+}
+
+/// Equity left in `collateral` after `pnl`. A loss beyond the collateral leaves zero,
+/// as the shortfall is borne elsewhere; a gain too large for a u64 is an error.
+pub fn equity(collateral: u64, pnl: i64) -> MathResult<u64> {
+    let equity = collateral as i128 + pnl as i128;
+    u64::try_from(equity.max(0)).map_err(|_| MathError::Overflow)
+}
+
+pub fn to_u64(value: u128) -> MathResult<u64> {
+    u64::try_from(value).map_err(|_| MathError::Overflow)
+}
+
+/// A signed amount that must not be negative, such as an equity or balance
+pub fn to_unsigned(value: i64) -> MathResult<u64> {
+    u64::try_from(value).map_err(|_| MathError::Negative)
+}
+
+pub fn to_signed(value: u64) -> MathResult<i64> {
+    i64::try_from(value).map_err(|_| MathError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_div_rounding() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down), Ok(3));
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up), Ok(4));
+
+        // Exact results are the same either way
+        assert_eq!(mul_div(9, 1, 3, Rounding::Down), Ok(3));
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up), Ok(3));
+        assert_eq!(mul_div(0, 7, 3, Rounding::Up), Ok(0));
+    }
+
+    #[test]
+    fn test_mul_div_wide_intermediate() {
+        // The product overflows u64 but the result fits
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX, Rounding::Down), Ok(u64::MAX));
+        assert_eq!(
+            mul_div_wide(u64::MAX as u128, 1_000_000, 1, Rounding::Down),
+            Ok(u64::MAX as u128 * 1_000_000)
+        );
+    }
+
+    #[test]
+    fn test_mul_div_errors() {
+        assert_eq!(mul_div(1, 1, 0, Rounding::Down), Err(MathError::DivisionByZero));
+        assert_eq!(mul_div(u64::MAX, 2, 1, Rounding::Down), Err(MathError::Overflow));
+        assert_eq!(mul_div_wide(u128::MAX, 2, 1, Rounding::Down), Err(MathError::Overflow));
+        assert_eq!(mul_div_signed(1, 1, 0, Rounding::Down), Err(MathError::DivisionByZero));
+        assert_eq!(mul_div_signed(i64::MAX, 2, 1, Rounding::Down), Err(MathError::Overflow));
+        assert_eq!(mul_div_signed(i64::MIN, 2, 1, Rounding::Down), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn test_mul_div_signed_rounding() {
+        // Down floors toward negative infinity, Up ceils toward positive infinity
+        assert_eq!(mul_div_signed(10, 1, 3, Rounding::Down), Ok(3));
+        assert_eq!(mul_div_signed(10, 1, 3, Rounding::Up), Ok(4));
+        assert_eq!(mul_div_signed(-10, 1, 3, Rounding::Down), Ok(-4));
+        assert_eq!(mul_div_signed(-10, 1, 3, Rounding::Up), Ok(-3));
+        assert_eq!(mul_div_signed(-9, 1, 3, Rounding::Down), Ok(-3));
+        assert_eq!(mul_div_signed(-9, 1, 3, Rounding::Up), Ok(-3));
+    }
+
+    #[test]
+    fn test_mul_div_signed_boundaries() {
+        assert_eq!(mul_div_signed(i64::MAX, 1, 1, Rounding::Down), Ok(i64::MAX));
+        assert_eq!(mul_div_signed(i64::MIN, 1, 1, Rounding::Up), Ok(i64::MIN));
+        assert_eq!(mul_div_signed(i64::MIN, u64::MAX, u64::MAX, Rounding::Down), Ok(i64::MIN));
+    }
+
+    #[test]
+    fn test_scale_price() {
+        // $75.123456 at the exponents Pyth publishes
+        assert_eq!(scale_price(7_512_345_678, -8, Rounding::Down), Ok(75_123_456));
+        assert_eq!(scale_price(7_512_345_678, -8, Rounding::Up), Ok(75_123_457));
+        assert_eq!(scale_price(75_123_456, -6, Rounding::Down), Ok(75_123_456));
+        assert_eq!(scale_price(7_512_345, -5, Rounding::Down), Ok(75_123_450));
+
+        // A large -8 price no longer overflows the intermediate
+        assert_eq!(scale_price(i64::MAX, -8, Rounding::Down), Ok(i64::MAX as u64 / 100));
+    }
+
+    #[test]
+    fn test_scale_price_errors() {
+        assert_eq!(scale_price(-1, -8, Rounding::Down), Err(MathError::Negative));
+        assert_eq!(scale_price(i64::MAX, -5, Rounding::Down), Err(MathError::Overflow));
+        assert_eq!(scale_price(1, 20, Rounding::Down), Err(MathError::Overflow));
+        assert_eq!(scale_price(1, i32::MAX, Rounding::Down), Err(MathError::Overflow));
+        assert_eq!(scale_price(1, -40, Rounding::Down), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(to_u64(u64::MAX as u128), Ok(u64::MAX));
+        assert_eq!(to_u64(u64::MAX as u128 + 1), Err(MathError::Overflow));
+        assert_eq!(to_unsigned(0), Ok(0));
+        assert_eq!(to_unsigned(-1), Err(MathError::Negative));
+        assert_eq!(to_signed(i64::MAX as u64), Ok(i64::MAX));
+        assert_eq!(to_signed(i64::MAX as u64 + 1), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn test_equity() {
+        assert_eq!(equity(1_000, 250), Ok(1_250));
+        assert_eq!(equity(1_000, -250), Ok(750));
+        // Losses beyond the collateral floor at zero
+        assert_eq!(equity(1_000, -1_001), Ok(0));
+        assert_eq!(equity(0, i64::MIN), Ok(0));
+        assert_eq!(equity(u64::MAX, 0), Ok(u64::MAX));
+        assert_eq!(equity(u64::MAX, 1), Err(MathError::Overflow));
+    }
+}
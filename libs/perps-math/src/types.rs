@@ -0,0 +1,213 @@
+use crate::{
+    mul_div, mul_div_signed, mul_div_wide, to_signed, MathError, MathResult, Rounding, BPS_SCALE,
+    PRICE_SCALE,
+};
+
+/// Price in 6-decimal fixed point, quote units per base unit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Price(pub u64);
+
+/// Position or order size in base units
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Size(pub u64);
+
+/// Rate in basis points, 10_000 = 100%
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Bps(pub u64);
+
+impl Price {
+    /// Quote value of `size` at this price
+    pub fn notional(self, size: Size, rounding: Rounding) -> MathResult<u64> {
+        mul_div(size.0, self.0, PRICE_SCALE, rounding)
+    }
+
+    /// Quote value of `size` at this price, widened so it can never overflow
+    pub fn notional_wide(self, size: Size, rounding: Rounding) -> u128 {
+        let product = size.0 as u128 * self.0 as u128;
+        let quotient = product / PRICE_SCALE as u128;
+        let remainder = product % PRICE_SCALE as u128;
+        match rounding {
+            Rounding::Up if remainder > 0 => quotient + 1,
+            _ => quotient,
+        }
+    }
+
+    /// This price moved up by `bps`, as an ask is quoted off a reference
+    pub fn add_bps(self, bps: Bps, rounding: Rounding) -> MathResult<Price> {
+        let factor = BPS_SCALE.checked_add(bps.0).ok_or(MathError::Overflow)?;
+        mul_div(self.0, factor, BPS_SCALE, rounding).map(Price)
+    }
+
+    /// This price moved down by `bps`, as a bid is quoted off a reference
+    pub fn sub_bps(self, bps: Bps, rounding: Rounding) -> MathResult<Price> {
+        let factor = BPS_SCALE.checked_sub(bps.0).ok_or(MathError::Negative)?;
+        mul_div(self.0, factor, BPS_SCALE, rounding).map(Price)
+    }
+
+    /// Distance from `reference` as a share of it
+    pub fn deviation_from(self, reference: Price, rounding: Rounding) -> MathResult<Bps> {
+        Bps::ratio(self.0.abs_diff(reference.0), reference.0, rounding)
+    }
+
+    /// Size-weighted average of two fills, as when a position is increased
+    pub fn weighted_average(
+        self,
+        size: Size,
+        other: Price,
+        other_size: Size,
+        rounding: Rounding,
+    ) -> MathResult<Price> {
+        let total = (size.0 as u128)
+            .checked_add(other_size.0 as u128)
+            .ok_or(MathError::Overflow)?;
+        let value = (size.0 as u128 * self.0 as u128)
+            .checked_add(other_size.0 as u128 * other.0 as u128)
+            .ok_or(MathError::Overflow)?;
+        crate::to_u64(mul_div_wide(value, 1, total, rounding)?).map(Price)
+    }
+}
+
+impl Size {
+    /// `amount` scaled by the share of `total` this size represents, as when part
+    /// of a position's collateral is released
+    pub fn pro_rata(self, amount: u64, total: Size, rounding: Rounding) -> MathResult<u64> {
+        mul_div(amount, self.0, total.0, rounding)
+    }
+
+    /// PnL of this size moving from `entry` to `exit`, positive when a long gains
+    /// on a rise or a short on a fall
+    pub fn pnl(self, entry: Price, exit: Price, is_long: bool, rounding: Rounding) -> MathResult<i64> {
+        let diff = exit.0 as i128 - entry.0 as i128;
+        let diff = if is_long { diff } else { -diff };
+        let diff = i64::try_from(diff).map_err(|_| MathError::Overflow)?;
+        mul_div_signed(diff, self.0, PRICE_SCALE, rounding)
+    }
+
+    pub fn signed(self) -> MathResult<i64> {
+        to_signed(self.0)
+    }
+}
+
+impl Bps {
+    pub const ZERO: Bps = Bps(0);
+    pub const ONE_HUNDRED_PERCENT: Bps = Bps(BPS_SCALE);
+
+    /// This share of `amount`
+    pub fn of(self, amount: u64, rounding: Rounding) -> MathResult<u64> {
+        mul_div(amount, self.0, BPS_SCALE, rounding)
+    }
+
+    /// `part` as a share of `whole`
+    pub fn ratio(part: u64, whole: u64, rounding: Rounding) -> MathResult<Bps> {
+        mul_div(part, BPS_SCALE, whole, rounding).map(Bps)
+    }
+
+    /// What is left of 100% after this share
+    pub fn complement(self) -> MathResult<Bps> {
+        BPS_SCALE.checked_sub(self.0).map(Bps).ok_or(MathError::Negative)
+    }
+
+    pub fn to_u32(self) -> MathResult<u32> {
+        u32::try_from(self.0).map_err(|_| MathError::Overflow)
+    }
+}
+
+impl From<u16> for Bps {
+    fn from(bps: u16) -> Self {
+        Bps(bps as u64)
+    }
+}
+
+impl From<u32> for Bps {
+    fn from(bps: u32) -> Self {
+        Bps(bps as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bps_of() {
+        assert_eq!(Bps(10).of(1_000_000, Rounding::Down), Ok(1_000));
+        assert_eq!(Bps(1).of(5_001, Rounding::Down), Ok(0));
+        assert_eq!(Bps(1).of(5_001, Rounding::Up), Ok(1));
+        assert_eq!(Bps::ONE_HUNDRED_PERCENT.of(u64::MAX, Rounding::Down), Ok(u64::MAX));
+        assert_eq!(Bps(BPS_SCALE + 1).of(u64::MAX, Rounding::Down), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn test_bps_ratio_and_complement() {
+        assert_eq!(Bps::ratio(1, 3, Rounding::Down), Ok(Bps(3_333)));
+        assert_eq!(Bps::ratio(1, 3, Rounding::Up), Ok(Bps(3_334)));
+        assert_eq!(Bps::ratio(1, 0, Rounding::Down), Err(MathError::DivisionByZero));
+        assert_eq!(Bps(1_000).complement(), Ok(Bps(9_000)));
+        assert_eq!(Bps::ONE_HUNDRED_PERCENT.complement(), Ok(Bps::ZERO));
+        assert_eq!(Bps(BPS_SCALE + 1).complement(), Err(MathError::Negative));
+    }
+
+    #[test]
+    fn test_price_bps_moves() {
+        let price = Price(1_000_001);
+        assert_eq!(price.add_bps(Bps(1), Rounding::Down), Ok(Price(1_000_101)));
+        assert_eq!(price.add_bps(Bps(1), Rounding::Up), Ok(Price(1_000_102)));
+        assert_eq!(price.sub_bps(Bps(1), Rounding::Down), Ok(Price(999_900)));
+        assert_eq!(price.sub_bps(Bps(1), Rounding::Up), Ok(Price(999_901)));
+        assert_eq!(price.sub_bps(Bps(BPS_SCALE + 1), Rounding::Down), Err(MathError::Negative));
+    }
+
+    #[test]
+    fn test_pnl_sign_and_rounding() {
+        let size = Size(3);
+        let entry = Price(1_000_000);
+        let up = Price(1_333_333);
+
+        // 3 units up 0.333333 is 0.999999 of a unit of quote, below one
+        assert_eq!(size.pnl(entry, up, true, Rounding::Down), Ok(0));
+        assert_eq!(size.pnl(entry, up, true, Rounding::Up), Ok(1));
+
+        // A short loses the same move, and rounding down never understates the loss
+        assert_eq!(size.pnl(entry, up, false, Rounding::Down), Ok(-1));
+        assert_eq!(size.pnl(entry, up, false, Rounding::Up), Ok(0));
+        assert_eq!(size.pnl(entry, entry, true, Rounding::Down), Ok(0));
+    }
+
+    #[test]
+    fn test_pnl_boundaries() {
+        let max = Price(u64::MAX);
+        let zero = Price(0);
+
+        // Moves wider than i64 are rejected rather than wrapped
+        assert_eq!(Size(1).pnl(zero, max, true, Rounding::Down), Err(MathError::Overflow));
+        assert_eq!(Size(1).pnl(zero, max, false, Rounding::Down), Err(MathError::Overflow));
+
+        let top = Price(i64::MAX as u64);
+        assert_eq!(
+            Size(PRICE_SCALE).pnl(zero, top, true, Rounding::Down),
+            Ok(i64::MAX)
+        );
+        assert_eq!(
+            Size(PRICE_SCALE).pnl(zero, top, false, Rounding::Down),
+            Ok(-i64::MAX)
+        );
+        assert_eq!(
+            Size(2 * PRICE_SCALE).pnl(zero, top, true, Rounding::Down),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_notional_and_pro_rata() {
+        assert_eq!(Price(1_500_000).notional(Size(3), Rounding::Down), Ok(4));
+        assert_eq!(Price(1_500_000).notional(Size(3), Rounding::Up), Ok(5));
+        let product = u64::MAX as u128 * u64::MAX as u128;
+        assert_eq!(
+            Price(u64::MAX).notional_wide(Size(u64::MAX), Rounding::Up),
+            product / PRICE_SCALE as u128 + 1
+        );
+        assert_eq!(Size(1).pro_rata(10, Size(3), Rounding::Down), Ok(3));
+        assert_eq!(Size(1).pro_rata(10, Size(3), Rounding::Up), Ok(4));
+        assert_eq!(Size(1).pro_rata(10, Size(0), Rounding::Down), Err(MathError::DivisionByZero));
+    }
+}
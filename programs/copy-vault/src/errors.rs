@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum CopyVaultError {
+    #[msg("Invalid vault parameters")]
+    InvalidVaultParams,
+
+    #[msg("Only the vault leader can do this")]
+    NotLeader,
+
+    #[msg("Deposit amount too small")]
+    DepositTooSmall,
+
+    #[msg("Vault has no equity left to price shares against")]
+    VaultHasNoEquity,
+
+    #[msg("Insufficient shares")]
+    InsufficientShares,
+
+    #[msg("No withdrawal requested")]
+    NoWithdrawalRequested,
+
+    #[msg("Withdrawal delay has not elapsed")]
+    WithdrawalDelayNotElapsed,
+
+    #[msg("Not enough free collateral to pay the withdrawal; positions must be closed first")]
+    InsufficientVaultLiquidity,
+
+    #[msg("Vault has too many open positions")]
+    TooManyPositions,
+
+    #[msg("Position is not held by this vault")]
+    UnknownPosition,
+
+    #[msg("Open positions must be passed as remaining accounts in vault order")]
+    InvalidPositionAccounts,
+
+    #[msg("Idle collateral already covers the queued withdrawal")]
+    NoWithdrawalShortfall,
+
+    #[msg("Position has no equity to free")]
+    PositionHasNoEquity,
+
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+impl From<perps_math::MathError> for CopyVaultError {
+    fn from(_: perps_math::MathError) -> Self {
+        CopyVaultError::MathOverflow
+    }
+}
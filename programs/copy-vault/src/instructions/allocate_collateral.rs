@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use perps_core::program::PerpsCore;
+use crate::state::CopyVault;
+use crate::errors::CopyVaultError;
+
+/// Move idle vault collateral into the trader's perps-core free balance
+#[derive(Accounts)]
+pub struct AllocateCollateral<'info> {
+    pub leader: Signer<'info>,
+
+    #[account(
+        seeds = [b"copy_vault", copy_vault.leader.as_ref(), &copy_vault.id.to_le_bytes()],
+        bump = copy_vault.bump,
+        constraint = copy_vault.leader == leader.key() @ CopyVaultError::NotLeader
+    )]
+    pub copy_vault: Account<'info, CopyVault>,
+
+    /// CHECK: Signs as the perps-core owner
+    #[account(
+        mut,
+        seeds = [b"trader", copy_vault.key().as_ref()],
+        bump = copy_vault.trader_bump
+    )]
+    pub trader: UncheckedAccount<'info>,
+
+    #[account(mut, address = copy_vault.token_account)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut, address = copy_vault.market)]
+    pub market: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_user_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_vault_token_account: UncheckedAccount<'info>,
+
+    pub perps_core_program: Program<'info, PerpsCore>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<AllocateCollateral>, amount: u64) -> Result<()> {
+    let copy_vault_key = ctx.accounts.copy_vault.key();
+    let trader_seeds = &[
+        b"trader".as_ref(),
+        copy_vault_key.as_ref(),
+        &[ctx.accounts.copy_vault.trader_bump],
+    ];
+    let signer = &[&trader_seeds[..]];
+
+    let cpi_accounts = perps_core::cpi::accounts::DepositCollateral {
+        owner: ctx.accounts.trader.to_account_info(),
+        user_account: ctx.accounts.perps_user_account.to_account_info(),
+        market: ctx.accounts.market.to_account_info(),
+        vault: ctx.accounts.perps_vault.to_account_info(),
+        vault_token_account: ctx.accounts.perps_vault_token_account.to_account_info(),
+        user_token_account: ctx.accounts.vault_token_account.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        collateral_registry: None,
+        margin_account: None,
+        asset_vault_token_account: None,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.perps_core_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    perps_core::cpi::deposit_collateral(cpi_ctx, amount)?;
+
+    msg!("Copy vault {} allocated {} to trading", ctx.accounts.copy_vault.id, amount);
+    Ok(())
+}
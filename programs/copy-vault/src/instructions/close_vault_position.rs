@@ -0,0 +1,223 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use perps_math::Rounding;
+use perps_core::state::{Market, Position, UserAccount};
+use perps_interface::{ClosePositionParams, PerpsCore, ReducePositionParams};
+use crate::state::{read_vault_price, CopyVault, FollowerStake, SHARE_PRICE_SCALE};
+use crate::errors::CopyVaultError;
+
+/// Close one of the vault's positions, paying the settlement into the vault token
+/// account. The leader may always close. So exits never depend on the leader, a
+/// follower may once their withdrawal delay has elapsed, but only by as much as their
+/// queued redemption is short of idle and free collateral: the position is reduced
+/// pro rata to that shortfall and closed outright only when it can't cover it.
+///
+/// Remaining accounts hold an index market's other feeds, then, for a follower, the
+/// vault's open positions in vault order to price their redemption.
+#[derive(Accounts)]
+pub struct CloseVaultPosition<'info> {
+    #[account(mut)]
+    pub closer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"copy_vault", copy_vault.leader.as_ref(), &copy_vault.id.to_le_bytes()],
+        bump = copy_vault.bump
+    )]
+    pub copy_vault: Account<'info, CopyVault>,
+
+    // Required when the closer is not the leader
+    #[account(
+        seeds = [b"follower", copy_vault.key().as_ref(), closer.key().as_ref()],
+        bump = follower_stake.bump
+    )]
+    pub follower_stake: Option<Account<'info, FollowerStake>>,
+
+    /// CHECK: Signs as the perps-core owner
+    #[account(
+        mut,
+        seeds = [b"trader", copy_vault.key().as_ref()],
+        bump = copy_vault.trader_bump
+    )]
+    pub trader: UncheckedAccount<'info>,
+
+    #[account(mut, address = copy_vault.token_account)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut, address = copy_vault.market)]
+    pub market: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_user_account: UncheckedAccount<'info>,
+
+    /// CHECK: Must be one of the vault's positions; validated by perps-core
+    #[account(mut)]
+    pub position: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_vault_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    pub pyth_price_feed: UncheckedAccount<'info>,
+
+    pub perps_core_program: Program<'info, PerpsCore>,
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The trader's user market PDA; perps-core creates it if the vault has none yet
+    #[account(mut)]
+    pub user_market: UncheckedAccount<'info>,
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseVaultPosition<'info>>,
+    params: ClosePositionParams,
+) -> Result<()> {
+    let copy_vault = &ctx.accounts.copy_vault;
+    let position_key = ctx.accounts.position.key();
+    require!(copy_vault.holds_position(&position_key), CopyVaultError::UnknownPosition);
+
+    let market = Account::<Market>::try_from(&ctx.accounts.market)?;
+    let (index_feeds, _) = market.split_index_feeds(ctx.remaining_accounts)?;
+
+    // Size a follower may reduce by, None to close the whole position
+    let reduce_size = if ctx.accounts.closer.key() == copy_vault.leader {
+        None
+    } else {
+        let stake = ctx.accounts.follower_stake
+            .as_ref()
+            .ok_or(CopyVaultError::NotLeader)?;
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(stake.withdrawal_shares > 0, CopyVaultError::NoWithdrawalRequested);
+        require!(
+            current_time >= stake.withdrawal_requested_at + copy_vault.withdrawal_delay,
+            CopyVaultError::WithdrawalDelayNotElapsed
+        );
+
+        require_keys_eq!(ctx.accounts.pyth_price_feed.key(), market.pyth_price_feed);
+        let user_account = Account::<UserAccount>::try_from(&ctx.accounts.perps_user_account)?;
+        require_keys_eq!(user_account.owner, copy_vault.trader);
+        let position = Account::<Position>::try_from(&ctx.accounts.position)?;
+
+        let (price, positions) = read_vault_price(
+            &market,
+            &ctx.accounts.pyth_price_feed,
+            ctx.remaining_accounts,
+            current_time,
+        )?;
+        let idle_balance = ctx.accounts.vault_token_account.amount;
+        let equity = copy_vault.equity(idle_balance, &user_account, price, positions)?;
+        let redemption = perps_math::mul_div(
+            stake.withdrawal_shares,
+            copy_vault.share_price(equity)?,
+            SHARE_PRICE_SCALE,
+            Rounding::Down,
+        )
+        .map_err(CopyVaultError::from)?;
+
+        let available = idle_balance.saturating_add(user_account.collateral_balance);
+        require!(redemption > available, CopyVaultError::NoWithdrawalShortfall);
+
+        let position_equity = perps_math::equity(position.collateral, position.unrealized_pnl(price)?)
+            .map_err(CopyVaultError::from)?;
+        let exit_size = CopyVault::exit_size(
+            redemption - available,
+            position.size,
+            position_equity,
+            market.lot_size,
+        )?;
+        (exit_size < position.size).then_some(exit_size)
+    };
+
+    let copy_vault_key = copy_vault.key();
+    let trader_seeds = &[
+        b"trader".as_ref(),
+        copy_vault_key.as_ref(),
+        &[copy_vault.trader_bump],
+    ];
+    let signer = &[&trader_seeds[..]];
+
+    // The closer pays the user market account's rent should perps-core create it
+    let fill = match reduce_size {
+        Some(size) => {
+            let cpi_accounts = perps_interface::accounts::ReducePosition {
+                owner: ctx.accounts.trader.to_account_info(),
+                payer: ctx.accounts.closer.to_account_info(),
+                user_account: ctx.accounts.perps_user_account.to_account_info(),
+                market: ctx.accounts.market.to_account_info(),
+                position: ctx.accounts.position.to_account_info(),
+                vault: ctx.accounts.perps_vault.to_account_info(),
+                vault_token_account: ctx.accounts.perps_vault_token_account.to_account_info(),
+                user_token_account: ctx.accounts.vault_token_account.to_account_info(),
+                pyth_price_feed: ctx.accounts.pyth_price_feed.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                user_referral: None,
+                referral_code: None,
+                margin_account: None,
+                user_market: ctx.accounts.user_market.to_account_info(),
+                fill_authority: None,
+                competition: None,
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.perps_core_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            )
+            .with_remaining_accounts(index_feeds.to_vec());
+            let reduce_params = ReducePositionParams {
+                size,
+                acceptable_price: params.acceptable_price,
+                deadline: params.deadline,
+            };
+            perps_interface::reduce_position(cpi_ctx, reduce_params)?
+        }
+        None => {
+            let cpi_accounts = perps_interface::accounts::ClosePosition {
+                owner: ctx.accounts.trader.to_account_info(),
+                payer: ctx.accounts.closer.to_account_info(),
+                user_account: ctx.accounts.perps_user_account.to_account_info(),
+                market: ctx.accounts.market.to_account_info(),
+                position: ctx.accounts.position.to_account_info(),
+                vault: ctx.accounts.perps_vault.to_account_info(),
+                vault_token_account: ctx.accounts.perps_vault_token_account.to_account_info(),
+                user_token_account: ctx.accounts.vault_token_account.to_account_info(),
+                pyth_price_feed: ctx.accounts.pyth_price_feed.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                user_referral: None,
+                referral_code: None,
+                margin_account: None,
+                user_market: ctx.accounts.user_market.to_account_info(),
+                competition: None,
+                fill_authority: None,
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.perps_core_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            )
+            .with_remaining_accounts(index_feeds.to_vec());
+            let fill = perps_interface::close_position(cpi_ctx, params)?;
+            ctx.accounts.copy_vault.remove_position(&position_key)?;
+            fill
+        }
+    };
+
+    msg!(
+        "Copy vault {} closed {} of position {}: realized PnL {}",
+        ctx.accounts.copy_vault.id,
+        fill.fill_size,
+        position_key,
+        fill.realized_pnl
+    );
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use perps_core::program::PerpsCore;
+use perps_core::state::{Market, UserAccount};
+use crate::state::CopyVault;
+use crate::errors::CopyVaultError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateCopyVaultParams {
+    pub id: u64,                    // Distinguishes the leader's vaults
+    pub performance_fee_bps: u16,   // Charged on follower profit above their high-water mark
+    pub withdrawal_delay: i64,      // Seconds followers wait between requesting and withdrawing
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateCopyVaultParams)]
+pub struct CreateCopyVault<'info> {
+    #[account(mut)]
+    pub leader: Signer<'info>,
+
+    #[account(
+        init,
+        payer = leader,
+        space = CopyVault::LEN,
+        seeds = [b"copy_vault", leader.key().as_ref(), &params.id.to_le_bytes()],
+        bump
+    )]
+    pub copy_vault: Account<'info, CopyVault>,
+
+    /// CHECK: Data-less PDA that owns the vault's perps-core account; signed for by this program
+    #[account(
+        mut,
+        seeds = [b"trader", copy_vault.key().as_ref()],
+        bump
+    )]
+    pub trader: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = leader,
+        token::mint = collateral_mint,
+        token::authority = trader,
+        seeds = [b"vault_token", copy_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(constraint = collateral_mint.key() == market.collateral_mint)]
+    pub collateral_mint: Account<'info, Mint>,
+
+    pub market: Account<'info, Market>,
+
+    /// CHECK: The trader's perps-core user account, created and validated by perps-core
+    #[account(mut)]
+    pub perps_user_account: UncheckedAccount<'info>,
+
+    pub perps_core_program: Program<'info, PerpsCore>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<CreateCopyVault>, params: CreateCopyVaultParams) -> Result<()> {
+    require!(
+        params.performance_fee_bps <= CopyVault::MAX_PERFORMANCE_FEE_BPS && params.withdrawal_delay >= 0,
+        CopyVaultError::InvalidVaultParams
+    );
+
+    let copy_vault_key = ctx.accounts.copy_vault.key();
+    let trader_bump = *ctx.bumps.get("trader").unwrap();
+
+    let copy_vault = &mut ctx.accounts.copy_vault;
+    copy_vault.leader = ctx.accounts.leader.key();
+    copy_vault.market = ctx.accounts.market.key();
+    copy_vault.collateral_mint = ctx.accounts.collateral_mint.key();
+    copy_vault.token_account = ctx.accounts.vault_token_account.key();
+    copy_vault.trader = ctx.accounts.trader.key();
+    copy_vault.id = params.id;
+    copy_vault.total_shares = 0;
+    copy_vault.performance_fee_bps = params.performance_fee_bps;
+    copy_vault.withdrawal_delay = params.withdrawal_delay;
+    copy_vault.num_positions = 0;
+    copy_vault.bump = *ctx.bumps.get("copy_vault").unwrap();
+    copy_vault.trader_bump = trader_bump;
+    copy_vault.version = CopyVault::VERSION;
+
+    // perps-core charges the owner for the user account, so the leader funds the trader
+    let rent = Rent::get()?.minimum_balance(UserAccount::LEN);
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.leader.to_account_info(),
+                to: ctx.accounts.trader.to_account_info(),
+            },
+        ),
+        rent,
+    )?;
+
+    let trader_seeds = &[
+        b"trader".as_ref(),
+        copy_vault_key.as_ref(),
+        &[trader_bump],
+    ];
+    let signer = &[&trader_seeds[..]];
+
+    let cpi_accounts = perps_core::cpi::accounts::InitializeUser {
+        owner: ctx.accounts.trader.to_account_info(),
+        user_account: ctx.accounts.perps_user_account.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.perps_core_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    perps_core::cpi::initialize_user(cpi_ctx)?;
+
+    msg!("Copy vault {} created by {} on market {}", params.id, copy_vault.leader, copy_vault.market);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_core::state::{Market, UserAccount};
+use crate::state::{read_vault_price, CopyVault, FollowerStake};
+use crate::errors::CopyVaultError;
+
+/// Deposit collateral for shares at the current share price, with positions valued
+/// at a fresh oracle read. Remaining accounts hold an index market's other feeds,
+/// then the vault's open positions in vault order.
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub follower: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"copy_vault", copy_vault.leader.as_ref(), &copy_vault.id.to_le_bytes()],
+        bump = copy_vault.bump
+    )]
+    pub copy_vault: Account<'info, CopyVault>,
+
+    #[account(
+        init_if_needed,
+        payer = follower,
+        space = FollowerStake::LEN,
+        seeds = [b"follower", copy_vault.key().as_ref(), follower.key().as_ref()],
+        bump
+    )]
+    pub follower_stake: Account<'info, FollowerStake>,
+
+    #[account(mut, address = copy_vault.token_account)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = follower_token_account.owner == follower.key(),
+        constraint = follower_token_account.mint == copy_vault.collateral_mint
+    )]
+    pub follower_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = copy_vault.market)]
+    pub market: Account<'info, Market>,
+
+    #[account(constraint = perps_user_account.owner == copy_vault.trader)]
+    pub perps_user_account: Account<'info, UserAccount>,
+
+    /// CHECK: The market's oracle, read by perps-core's pricing
+    #[account(address = market.pyth_price_feed)]
+    pub pyth_price_feed: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    let copy_vault = &mut ctx.accounts.copy_vault;
+
+    // Shares are priced before the deposit lands
+    let (price, positions) = read_vault_price(
+        &ctx.accounts.market,
+        &ctx.accounts.pyth_price_feed,
+        ctx.remaining_accounts,
+        Clock::get()?.unix_timestamp,
+    )?;
+    let equity = copy_vault.equity(
+        ctx.accounts.vault_token_account.amount,
+        &ctx.accounts.perps_user_account,
+        price,
+        positions,
+    )?;
+    let (shares, minted) = copy_vault.shares_for_deposit(amount, equity)?;
+    require!(shares > 0, CopyVaultError::DepositTooSmall);
+    let share_price = copy_vault.share_price(equity)?;
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.follower_token_account.to_account_info(),
+        to: ctx.accounts.vault_token_account.to_account_info(),
+        authority: ctx.accounts.follower.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    let stake = &mut ctx.accounts.follower_stake;
+    if stake.version == 0 {
+        stake.copy_vault = copy_vault.key();
+        stake.follower = ctx.accounts.follower.key();
+        stake.bump = *ctx.bumps.get("follower_stake").unwrap();
+        stake.version = FollowerStake::VERSION;
+    }
+    stake.add_shares(shares, share_price)?;
+
+    copy_vault.total_shares = copy_vault.total_shares
+        .checked_add(minted)
+        .ok_or(CopyVaultError::MathOverflow)?;
+
+    msg!("Deposited {} for {} shares @ {}", amount, shares, share_price);
+    Ok(())
+}
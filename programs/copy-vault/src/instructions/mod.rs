@@ -0,0 +1,17 @@
+pub mod create_copy_vault;
+pub mod allocate_collateral;
+pub mod open_vault_position;
+pub mod close_vault_position;
+pub mod prune_positions;
+pub mod deposit;
+pub mod request_withdrawal;
+pub mod withdraw;
+
+pub use create_copy_vault::*;
+pub use allocate_collateral::*;
+pub use open_vault_position::*;
+pub use close_vault_position::*;
+pub use prune_positions::*;
+pub use deposit::*;
+pub use request_withdrawal::*;
+pub use withdraw::*;
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use perps_interface::{OpenPositionParams, PerpsCore};
+use crate::state::CopyVault;
+use crate::errors::CopyVaultError;
+
+/// Open a perps-core position for the vault from the trader's free balance
+#[derive(Accounts)]
+pub struct OpenVaultPosition<'info> {
+    #[account(mut)]
+    pub leader: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"copy_vault", copy_vault.leader.as_ref(), &copy_vault.id.to_le_bytes()],
+        bump = copy_vault.bump,
+        constraint = copy_vault.leader == leader.key() @ CopyVaultError::NotLeader
+    )]
+    pub copy_vault: Account<'info, CopyVault>,
+
+    /// CHECK: Signs as the perps-core owner
+    #[account(
+        seeds = [b"trader", copy_vault.key().as_ref()],
+        bump = copy_vault.trader_bump
+    )]
+    pub trader: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut, address = copy_vault.market)]
+    pub market: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_user_account: UncheckedAccount<'info>,
+
+    /// CHECK: Created by perps-core
+    #[account(mut)]
+    pub position: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    pub pyth_price_feed: UncheckedAccount<'info>,
+
+    pub perps_core_program: Program<'info, PerpsCore>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Passed through; perps-core requires it when the market follows a trading schedule
+    pub trading_schedule: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The trader's user market PDA, created by perps-core on the first trade
+    #[account(mut)]
+    pub user_market: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<OpenVaultPosition>, params: OpenPositionParams) -> Result<()> {
+    let copy_vault_key = ctx.accounts.copy_vault.key();
+    let trader_seeds = &[
+        b"trader".as_ref(),
+        copy_vault_key.as_ref(),
+        &[ctx.accounts.copy_vault.trader_bump],
+    ];
+    let signer = &[&trader_seeds[..]];
+
+    // The leader pays the position and user market accounts' rent
+    let cpi_accounts = perps_interface::accounts::OpenPosition {
+        owner: ctx.accounts.trader.to_account_info(),
+        payer: ctx.accounts.leader.to_account_info(),
+        user_account: ctx.accounts.perps_user_account.to_account_info(),
+        market: ctx.accounts.market.to_account_info(),
+        position: ctx.accounts.position.to_account_info(),
+        pyth_price_feed: ctx.accounts.pyth_price_feed.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        collateral_registry: None,
+        margin_account: None,
+        trading_schedule: ctx.accounts.trading_schedule.as_ref().map(|a| a.to_account_info()),
+        user_market: ctx.accounts.user_market.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.perps_core_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    let fill = perps_interface::open_position(cpi_ctx, params)?;
+
+    let position = ctx.accounts.position.key();
+    ctx.accounts.copy_vault.add_position(position)?;
+
+    msg!(
+        "Copy vault {} opened position {}: {} @ {}, liquidation price {}",
+        ctx.accounts.copy_vault.id,
+        position,
+        fill.fill_size,
+        fill.fill_price,
+        fill.liquidation_price
+    );
+    Ok(())
+}
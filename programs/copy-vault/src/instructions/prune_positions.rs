@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use perps_core::state::{Position, PositionStatus};
+use crate::state::CopyVault;
+
+/// Drop positions perps-core closed without the vault, e.g. by liquidation, from the
+/// vault's list. Candidates are passed as remaining accounts; open ones are kept.
+#[derive(Accounts)]
+pub struct PrunePositions<'info> {
+    #[account(
+        mut,
+        seeds = [b"copy_vault", copy_vault.leader.as_ref(), &copy_vault.id.to_le_bytes()],
+        bump = copy_vault.bump
+    )]
+    pub copy_vault: Account<'info, CopyVault>,
+}
+
+pub fn handler(ctx: Context<PrunePositions>) -> Result<()> {
+    let copy_vault = &mut ctx.accounts.copy_vault;
+    let mut pruned = 0;
+
+    for info in ctx.remaining_accounts.iter() {
+        let position = Account::<Position>::try_from(info)?;
+        if position.status != PositionStatus::Open {
+            copy_vault.remove_position(&info.key())?;
+            pruned += 1;
+        }
+    }
+
+    msg!("Copy vault {} pruned {} positions", copy_vault.id, pruned);
+    Ok(())
+}
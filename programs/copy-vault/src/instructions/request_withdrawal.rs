@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::{CopyVault, FollowerStake};
+use crate::errors::CopyVaultError;
+
+/// Queue shares for withdrawal, replacing any earlier request and restarting the delay
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    pub follower: Signer<'info>,
+
+    #[account(
+        seeds = [b"copy_vault", copy_vault.leader.as_ref(), &copy_vault.id.to_le_bytes()],
+        bump = copy_vault.bump
+    )]
+    pub copy_vault: Account<'info, CopyVault>,
+
+    #[account(
+        mut,
+        seeds = [b"follower", copy_vault.key().as_ref(), follower.key().as_ref()],
+        bump = follower_stake.bump
+    )]
+    pub follower_stake: Account<'info, FollowerStake>,
+}
+
+pub fn handler(ctx: Context<RequestWithdrawal>, shares: u64) -> Result<()> {
+    let stake = &mut ctx.accounts.follower_stake;
+    require!(shares > 0 && shares <= stake.shares, CopyVaultError::InsufficientShares);
+
+    let current_time = Clock::get()?.unix_timestamp;
+    stake.withdrawal_shares = shares;
+    stake.withdrawal_requested_at = current_time;
+
+    msg!(
+        "Withdrawal of {} shares requested, available at {}",
+        shares,
+        current_time + ctx.accounts.copy_vault.withdrawal_delay
+    );
+    Ok(())
+}
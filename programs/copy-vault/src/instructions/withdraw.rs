@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::Rounding;
+use perps_core::program::PerpsCore;
+use perps_core::state::{Market, UserAccount};
+use crate::state::{read_vault_price, CopyVault, FollowerStake, SHARE_PRICE_SCALE};
+use crate::errors::CopyVaultError;
+
+/// Redeem queued shares once the withdrawal delay has elapsed. The leader's
+/// performance fee on gains above the follower's high-water mark is paid out of
+/// the redemption. Idle collateral is used first, then the trader's free perps-core
+/// balance; if both fall short, vault positions must be closed first. Positions are
+/// valued at a fresh oracle read; remaining accounts hold an index market's other
+/// feeds, then the vault's open positions in vault order.
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub follower: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"copy_vault", copy_vault.leader.as_ref(), &copy_vault.id.to_le_bytes()],
+        bump = copy_vault.bump
+    )]
+    pub copy_vault: Account<'info, CopyVault>,
+
+    #[account(
+        mut,
+        seeds = [b"follower", copy_vault.key().as_ref(), follower.key().as_ref()],
+        bump = follower_stake.bump
+    )]
+    pub follower_stake: Account<'info, FollowerStake>,
+
+    /// CHECK: Signs for the vault token account and as the perps-core owner
+    #[account(
+        mut,
+        seeds = [b"trader", copy_vault.key().as_ref()],
+        bump = copy_vault.trader_bump
+    )]
+    pub trader: UncheckedAccount<'info>,
+
+    #[account(mut, address = copy_vault.token_account)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = follower_token_account.owner == follower.key(),
+        constraint = follower_token_account.mint == copy_vault.collateral_mint
+    )]
+    pub follower_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = leader_token_account.owner == copy_vault.leader @ CopyVaultError::NotLeader,
+        constraint = leader_token_account.mint == copy_vault.collateral_mint
+    )]
+    pub leader_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = copy_vault.market)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, constraint = perps_user_account.owner == copy_vault.trader)]
+    pub perps_user_account: Account<'info, UserAccount>,
+
+    /// CHECK: The market's oracle, read by perps-core's pricing
+    #[account(address = market.pyth_price_feed)]
+    pub pyth_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by perps-core
+    #[account(mut)]
+    pub perps_vault_token_account: UncheckedAccount<'info>,
+
+    pub perps_core_program: Program<'info, PerpsCore>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<Withdraw>) -> Result<()> {
+    let copy_vault = &ctx.accounts.copy_vault;
+    let stake = &ctx.accounts.follower_stake;
+
+    let shares = stake.withdrawal_shares;
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(shares > 0, CopyVaultError::NoWithdrawalRequested);
+    require!(
+        current_time >= stake.withdrawal_requested_at + copy_vault.withdrawal_delay,
+        CopyVaultError::WithdrawalDelayNotElapsed
+    );
+    require!(shares <= stake.shares, CopyVaultError::InsufficientShares);
+
+    let idle_balance = ctx.accounts.vault_token_account.amount;
+    let (price, positions) = read_vault_price(
+        &ctx.accounts.market,
+        &ctx.accounts.pyth_price_feed,
+        ctx.remaining_accounts,
+        current_time,
+    )?;
+    let equity = copy_vault.equity(
+        idle_balance,
+        &ctx.accounts.perps_user_account,
+        price,
+        positions,
+    )?;
+    let share_price = copy_vault.share_price(equity)?;
+    let gross = perps_math::mul_div(shares, share_price, SHARE_PRICE_SCALE, Rounding::Down)
+        .map_err(CopyVaultError::from)?;
+    let fee = stake.performance_fee(shares, share_price, copy_vault.performance_fee_bps)?;
+    let payout = gross - fee;
+
+    let copy_vault_key = copy_vault.key();
+    let trader_seeds = &[
+        b"trader".as_ref(),
+        copy_vault_key.as_ref(),
+        &[copy_vault.trader_bump],
+    ];
+    let signer = &[&trader_seeds[..]];
+
+    // Top up idle collateral from the trader's free balance
+    if gross > idle_balance {
+        let shortfall = gross - idle_balance;
+        require!(
+            shortfall <= ctx.accounts.perps_user_account.collateral_balance,
+            CopyVaultError::InsufficientVaultLiquidity
+        );
+
+        let cpi_accounts = perps_core::cpi::accounts::WithdrawCollateral {
+            owner: ctx.accounts.trader.to_account_info(),
+            user_account: ctx.accounts.perps_user_account.to_account_info(),
+            market: ctx.accounts.market.to_account_info(),
+            vault: ctx.accounts.perps_vault.to_account_info(),
+            vault_token_account: ctx.accounts.perps_vault_token_account.to_account_info(),
+            user_token_account: ctx.accounts.vault_token_account.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            collateral_registry: None,
+            margin_account: None,
+            asset_vault_token_account: None,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.perps_core_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        perps_core::cpi::withdraw_collateral(cpi_ctx, shortfall)?;
+    }
+
+    for (to, amount) in [
+        (ctx.accounts.follower_token_account.to_account_info(), payout),
+        (ctx.accounts.leader_token_account.to_account_info(), fee),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to,
+            authority: ctx.accounts.trader.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, amount)?;
+    }
+
+    let stake = &mut ctx.accounts.follower_stake;
+    stake.shares -= shares;
+    stake.withdrawal_shares = 0;
+    stake.withdrawal_requested_at = 0;
+
+    let copy_vault = &mut ctx.accounts.copy_vault;
+    copy_vault.total_shares -= shares;
+
+    msg!(
+        "Withdrew {} shares @ {}: paid={}, performance fee={}",
+        shares,
+        share_price,
+        payout,
+        fee
+    );
+    Ok(())
+}
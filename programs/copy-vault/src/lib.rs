@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+pub mod errors;
+pub mod instructions;
+pub mod state;
+
+use instructions::*;
+
+declare_id!("CopyVau1t1111111111111111111111111111111111");
+
+#[program]
+pub mod copy_vault {
+    use super::*;
+
+    // Leader instructions
+    pub fn create_copy_vault(
+        ctx: Context<CreateCopyVault>,
+        params: CreateCopyVaultParams,
+    ) -> Result<()> {
+        instructions::create_copy_vault::handler(ctx, params)
+    }
+
+    pub fn allocate_collateral(ctx: Context<AllocateCollateral>, amount: u64) -> Result<()> {
+        instructions::allocate_collateral::handler(ctx, amount)
+    }
+
+    pub fn open_vault_position(
+        ctx: Context<OpenVaultPosition>,
+        params: perps_interface::OpenPositionParams,
+    ) -> Result<()> {
+        instructions::open_vault_position::handler(ctx, params)
+    }
+
+    /// Also callable by a follower whose withdrawal delay has elapsed, limited to
+    /// what their queued redemption is short of
+    pub fn close_vault_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseVaultPosition<'info>>,
+        params: perps_interface::ClosePositionParams,
+    ) -> Result<()> {
+        instructions::close_vault_position::handler(ctx, params)
+    }
+
+    pub fn prune_positions(ctx: Context<PrunePositions>) -> Result<()> {
+        instructions::prune_positions::handler(ctx)
+    }
+
+    // Follower instructions
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        instructions::deposit::handler(ctx, amount)
+    }
+
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, shares: u64) -> Result<()> {
+        instructions::request_withdrawal::handler(ctx, shares)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        instructions::withdraw::handler(ctx)
+    }
+}
@@ -0,0 +1,322 @@
+use anchor_lang::prelude::*;
+use perps_math::{Bps, Rounding};
+use perps_core::state::{Market, Position, PositionStatus, UserAccount};
+use crate::errors::CopyVaultError;
+
+pub const MAX_VAULT_POSITIONS: usize = 4;
+pub const SHARE_PRICE_SCALE: u64 = perps_math::PRICE_SCALE;
+
+/// Shares the first deposit locks with no owner, so that a donation to a near-empty
+/// vault is almost all captured by them instead of inflating the depositor's shares
+pub const MIN_LOCKED_SHARES: u64 = 1_000;
+
+/// A pool of follower collateral traded on one perps-core market by its leader.
+///
+/// The `trader` PDA owns the vault's perps-core `UserAccount` and positions; it holds
+/// no data, only the lamports perps-core charges its owner for the user account, and
+/// this program signs for it. Idle collateral sits in the vault token account.
+/// PDA seeds: [b"copy_vault", leader, id]
+#[account]
+#[derive(Default)]
+pub struct CopyVault {
+    pub leader: Pubkey,
+    pub market: Pubkey,                 // perps-core market the vault trades
+    pub collateral_mint: Pubkey,
+    pub token_account: Pubkey,          // Idle collateral, owned by `trader`
+    pub trader: Pubkey,                 // PDA [b"trader", copy_vault]
+    pub id: u64,
+
+    pub total_shares: u64,
+    pub performance_fee_bps: u16,       // Share of follower profit above their high-water mark
+    pub withdrawal_delay: i64,          // Seconds between requesting and executing a withdrawal
+
+    /// Open perps-core positions, valued into the share price
+    pub positions: [Pubkey; MAX_VAULT_POSITIONS],
+    pub num_positions: u8,
+
+    pub bump: u8,
+    pub trader_bump: u8,
+    pub version: u8,
+}
+
+impl CopyVault {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // leader
+        32 +  // market
+        32 +  // collateral_mint
+        32 +  // token_account
+        32 +  // trader
+        8 +   // id
+        8 +   // total_shares
+        2 +   // performance_fee_bps
+        8 +   // withdrawal_delay
+        32 * MAX_VAULT_POSITIONS +  // positions
+        1 +   // num_positions
+        1 +   // bump
+        1 +   // trader_bump
+        1 +   // version
+        32;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    pub const MAX_PERFORMANCE_FEE_BPS: u16 = 5000;
+
+    pub fn add_position(&mut self, position: Pubkey) -> Result<()> {
+        let index = self.num_positions as usize;
+        require!(index < MAX_VAULT_POSITIONS, CopyVaultError::TooManyPositions);
+        self.positions[index] = position;
+        self.num_positions += 1;
+        Ok(())
+    }
+
+    pub fn holds_position(&self, position: &Pubkey) -> bool {
+        self.positions[..self.num_positions as usize].contains(position)
+    }
+
+    pub fn remove_position(&mut self, position: &Pubkey) -> Result<()> {
+        let count = self.num_positions as usize;
+        let index = self.positions[..count]
+            .iter()
+            .position(|key| key == position)
+            .ok_or(CopyVaultError::UnknownPosition)?;
+        self.positions[index] = self.positions[count - 1];
+        self.positions[count - 1] = Pubkey::default();
+        self.num_positions -= 1;
+        Ok(())
+    }
+
+    /// Vault value in collateral units: idle tokens, free perps-core balance and the
+    /// equity of every open position at `price`, a fresh read of the market's oracle.
+    /// Funding accrued since a position last settled is not included. `positions` must
+    /// be the vault's open positions in vault order.
+    pub fn equity(
+        &self,
+        idle_balance: u64,
+        user_account: &UserAccount,
+        price: u64,
+        positions: &[AccountInfo],
+    ) -> Result<u64> {
+        let count = self.num_positions as usize;
+        require!(positions.len() == count, CopyVaultError::InvalidPositionAccounts);
+
+        let mut equity = idle_balance
+            .checked_add(user_account.collateral_balance)
+            .ok_or(CopyVaultError::MathOverflow)?;
+        for (info, key) in positions.iter().zip(self.positions[..count].iter()) {
+            require!(info.key() == *key, CopyVaultError::InvalidPositionAccounts);
+            let position = Account::<Position>::try_from(info)?;
+            if position.status != PositionStatus::Open {
+                continue;
+            }
+            let position_equity = perps_math::equity(position.collateral, position.unrealized_pnl(price)?)
+                .map_err(CopyVaultError::from)?;
+            equity = equity
+                .checked_add(position_equity)
+                .ok_or(CopyVaultError::MathOverflow)?;
+        }
+
+        Ok(equity)
+    }
+
+    /// Size of a position to close to free `shortfall`: the same fraction of its size as
+    /// `shortfall` is of its equity, rounded up to whole lots and capped at the position
+    pub fn exit_size(shortfall: u64, size: u64, position_equity: u64, lot_size: u64) -> Result<u64> {
+        require!(position_equity > 0, CopyVaultError::PositionHasNoEquity);
+        if shortfall >= position_equity {
+            return Ok(size);
+        }
+        let exit = perps_math::mul_div(size, shortfall, position_equity, Rounding::Up)
+            .map_err(CopyVaultError::from)?;
+        let exit = match exit.checked_rem(lot_size) {
+            Some(remainder) if remainder > 0 => exit.saturating_add(lot_size - remainder),
+            _ => exit,
+        };
+        Ok(exit.min(size))
+    }
+
+    /// Value of one share, scaled by `SHARE_PRICE_SCALE` and rounded down
+    pub fn share_price(&self, equity: u64) -> Result<u64> {
+        if self.total_shares == 0 {
+            return Ok(SHARE_PRICE_SCALE);
+        }
+        Ok(perps_math::mul_div(equity, SHARE_PRICE_SCALE, self.total_shares, Rounding::Down)
+            .map_err(CopyVaultError::from)?)
+    }
+
+    /// Shares for depositing `amount` into a vault worth `equity`, rounded down: the
+    /// depositor's and the total minted. The first deposit mints one share per unit of
+    /// equity, whether deposited or already in the vault, and locks the shares backing
+    /// what was already there, but at least `MIN_LOCKED_SHARES`, with no owner.
+    pub fn shares_for_deposit(&self, amount: u64, equity: u64) -> Result<(u64, u64)> {
+        if self.total_shares == 0 {
+            let minted = amount.checked_add(equity).ok_or(CopyVaultError::MathOverflow)?;
+            return Ok((minted.saturating_sub(equity.max(MIN_LOCKED_SHARES)), minted));
+        }
+        require!(equity > 0, CopyVaultError::VaultHasNoEquity);
+        let shares = perps_math::mul_div(amount, self.total_shares, equity, Rounding::Down)
+            .map_err(CopyVaultError::from)?;
+        Ok((shares, shares))
+    }
+}
+
+/// Read the market's oracle, rejecting stale prices, and split off the vault's
+/// positions. `remaining_accounts` holds an index market's other feeds first, then
+/// the vault's open positions in vault order.
+pub fn read_vault_price<'a, 'info>(
+    market: &Market,
+    price_feed: &AccountInfo,
+    remaining_accounts: &'a [AccountInfo<'info>],
+    current_time: i64,
+) -> Result<(u64, &'a [AccountInfo<'info>])> {
+    let price = market.index_price(price_feed, remaining_accounts, current_time)?;
+    let (_, positions) = market.split_index_feeds(remaining_accounts)?;
+    Ok((price, positions))
+}
+
+/// A follower's stake in a copy vault
+/// PDA seeds: [b"follower", copy_vault, follower]
+#[account]
+#[derive(Default)]
+pub struct FollowerStake {
+    pub copy_vault: Pubkey,
+    pub follower: Pubkey,
+    pub shares: u64,
+
+    /// Share price above which the performance fee is charged: the share-weighted
+    /// entry price. Fees are taken per redeemed share, so it never needs resetting.
+    pub high_water_mark: u64,
+
+    pub withdrawal_shares: u64,         // Shares queued for withdrawal, 0 = none
+    pub withdrawal_requested_at: i64,
+
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl FollowerStake {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // copy_vault
+        32 +  // follower
+        8 +   // shares
+        8 +   // high_water_mark
+        8 +   // withdrawal_shares
+        8 +   // withdrawal_requested_at
+        1 +   // bump
+        1 +   // version
+        32;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    /// Add shares bought at `share_price`, averaging the high-water mark by shares
+    pub fn add_shares(&mut self, shares: u64, share_price: u64) -> Result<()> {
+        let total = self.shares
+            .checked_add(shares)
+            .ok_or(CopyVaultError::MathOverflow)?;
+        let value = (self.shares as u128 * self.high_water_mark as u128)
+            .checked_add(shares as u128 * share_price as u128)
+            .ok_or(CopyVaultError::MathOverflow)?;
+        self.high_water_mark = perps_math::mul_div_wide(value, 1, total as u128, Rounding::Down)
+            .and_then(perps_math::to_u64)
+            .map_err(CopyVaultError::from)?;
+        self.shares = total;
+        Ok(())
+    }
+
+    /// Performance fee owed on redeeming `shares` at `share_price`, rounded up
+    pub fn performance_fee(&self, shares: u64, share_price: u64, fee_bps: u16) -> Result<u64> {
+        if share_price <= self.high_water_mark {
+            return Ok(0);
+        }
+        let fee = perps_math::mul_div(
+            shares,
+            share_price - self.high_water_mark,
+            SHARE_PRICE_SCALE,
+            Rounding::Up,
+        )
+        .and_then(|profit| Bps::from(fee_bps).of(profit, Rounding::Up))
+        .map_err(CopyVaultError::from)?;
+        Ok(fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_deposit_locks_shares() {
+        let vault = CopyVault::default();
+        assert_eq!(vault.share_price(0).unwrap(), SHARE_PRICE_SCALE);
+        assert_eq!(vault.shares_for_deposit(1_000_000, 0).unwrap(), (999_000, 1_000_000));
+        assert_eq!(vault.shares_for_deposit(MIN_LOCKED_SHARES, 0).unwrap(), (0, MIN_LOCKED_SHARES));
+
+        // Equity already in the vault backs locked shares instead of going to the depositor
+        assert_eq!(vault.shares_for_deposit(1_000_000, 500).unwrap(), (999_500, 1_000_500));
+        assert_eq!(vault.shares_for_deposit(1_000_000, 5_000_000).unwrap(), (1_000_000, 6_000_000));
+    }
+
+    #[test]
+    fn test_donation_does_not_inflate_shares() {
+        // An attacker makes the smallest first deposit, then donates a large amount
+        let (attacker_shares, minted) = CopyVault::default()
+            .shares_for_deposit(MIN_LOCKED_SHARES + 1, 0)
+            .unwrap();
+        assert_eq!(attacker_shares, 1);
+        let vault = CopyVault { total_shares: minted, ..Default::default() };
+        let equity = MIN_LOCKED_SHARES + 1 + 1_000_000_000;
+
+        // The locked shares own almost all of the donation: the attacker's one share
+        // redeems under a thousandth of it, and a later depositor loses at most a share's
+        // worth to rounding, which the attacker pays a thousand times over
+        assert_eq!(vault.shares_for_deposit(1_000_000, equity).unwrap(), (1, 1));
+        let share_price = vault.share_price(equity).unwrap();
+        let attacker_value = perps_math::mul_div(attacker_shares, share_price, SHARE_PRICE_SCALE, Rounding::Down).unwrap();
+        assert_eq!(attacker_value, 999_001);
+    }
+
+    #[test]
+    fn test_share_mint_and_burn_round_against_the_follower() {
+        let mut vault = CopyVault { total_shares: 3_000_000, ..Default::default() };
+        assert_eq!(vault.share_price(4_000_000).unwrap(), 1_333_333);
+
+        // Deposits too small for a whole share mint nothing
+        assert_eq!(vault.shares_for_deposit(1, 4_000_000).unwrap(), (0, 0));
+        let (shares, _) = vault.shares_for_deposit(1_000_000, 4_000_000).unwrap();
+        assert_eq!(shares, 750_000);
+
+        // Redeeming straight away returns no more than was deposited
+        vault.total_shares += shares;
+        let share_price = vault.share_price(5_000_000).unwrap();
+        let gross = perps_math::mul_div(shares, share_price, SHARE_PRICE_SCALE, Rounding::Down).unwrap();
+        assert_eq!(gross, 999_999);
+
+        assert!(vault.shares_for_deposit(1_000_000, 0).is_err());
+    }
+
+    #[test]
+    fn test_exit_size_rounds_up_to_whole_lots() {
+        assert_eq!(CopyVault::exit_size(250, 1_000, 1_000, 100).unwrap(), 300);
+        assert_eq!(CopyVault::exit_size(1, 1_000, 1_000, 100).unwrap(), 100);
+        assert_eq!(CopyVault::exit_size(1, 1_000, 1_000, 0).unwrap(), 1);
+
+        // Never more than the position, which closes whole when it can't cover the shortfall
+        assert_eq!(CopyVault::exit_size(990, 1_050, 1_000, 100).unwrap(), 1_050);
+        assert_eq!(CopyVault::exit_size(2_000, 1_050, 1_000, 100).unwrap(), 1_050);
+        assert!(CopyVault::exit_size(1, 1_000, 0, 100).is_err());
+    }
+
+    #[test]
+    fn test_high_water_mark_and_performance_fee() {
+        let mut stake = FollowerStake::default();
+        stake.add_shares(1_000, 1_000_000).unwrap();
+        stake.add_shares(1_000, 1_500_000).unwrap();
+        assert_eq!(stake.high_water_mark, 1_250_000);
+        stake.add_shares(1, 2_000_000).unwrap();
+        assert_eq!(stake.high_water_mark, 1_250_374);
+
+        let stake = FollowerStake { shares: 1_000_001, high_water_mark: 1_000_000, ..Default::default() };
+        assert_eq!(stake.performance_fee(1_000_001, 1_000_000, 2_000).unwrap(), 0);
+        // 200_000.2 of profit rounds up to 200_001, and 20% of it up to 40_001
+        assert_eq!(stake.performance_fee(1_000_001, 1_200_000, 2_000).unwrap(), 40_001);
+    }
+}
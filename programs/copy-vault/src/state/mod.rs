@@ -0,0 +1,3 @@
+pub mod copy_vault;
+
+pub use copy_vault::*;
@@ -62,3 +62,9 @@ pub enum MmRegistryError {
     #[msg("Has open inventory - close it first")]
     HasOpenInventory,
 }
+
+impl From<perps_math::MathError> for MmRegistryError {
+    fn from(_: perps_math::MathError) -> Self {
+        MmRegistryError::MathOverflow
+    }
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::{Bps, Price, Rounding, Size};
 use crate::state::{MmRegistry, MarketMaker, TwoSidedQuote};
 use crate::errors::MmRegistryError;
 
@@ -82,8 +83,12 @@ pub fn handler(ctx: Context<FillQuote>, params: FillQuoteParams) -> Result<FillQ
     require!(params.size <= max_size, MmRegistryError::FillSizeExceedsRemaining);
 
     // Calculate notional and fee
-    let notional = (params.size as u128 * fill_price as u128 / 1_000_000) as u64;
-    let fee = (notional * registry.mm_fee as u64) / 10000;
+    let notional = Price(fill_price)
+        .notional(Size(params.size), Rounding::Up)
+        .map_err(MmRegistryError::from)?;
+    let fee = Bps::from(registry.mm_fee)
+        .of(notional, Rounding::Up)
+        .map_err(MmRegistryError::from)?;
 
     // Process the fill based on direction
     if params.is_buy {
@@ -114,7 +119,7 @@ pub fn handler(ctx: Context<FillQuote>, params: FillQuoteParams) -> Result<FillQ
 
         // Update MM inventory (MM is selling/shorting)
         let market_maker = &mut ctx.accounts.market_maker;
-        market_maker.update_inventory(params.size as i64, fill_price, false);
+        market_maker.update_inventory(params.size as i64, fill_price, false)?;
         market_maker.collateral_deposited += notional; // MM receives payment
     } else {
         // Taker sells to MM (MM buys/goes long)
@@ -153,7 +158,7 @@ pub fn handler(ctx: Context<FillQuote>, params: FillQuoteParams) -> Result<FillQ
 
         // Update MM inventory (MM is buying/going long)
         let market_maker = &mut ctx.accounts.market_maker;
-        market_maker.update_inventory(params.size as i64, fill_price, true);
+        market_maker.update_inventory(params.size as i64, fill_price, true)?;
         market_maker.collateral_deposited = market_maker.collateral_deposited.saturating_sub(notional);
     }
 
@@ -166,11 +171,12 @@ pub fn handler(ctx: Context<FillQuote>, params: FillQuoteParams) -> Result<FillQ
 
     // Recalculate locked collateral based on remaining quote sizes
     let quote = &ctx.accounts.quote;
-    let new_max_notional = std::cmp::max(
-        (quote.bid_remaining as u128 * quote.bid_price as u128 / 1_000_000) as u64,
-        (quote.ask_remaining as u128 * quote.ask_price as u128 / 1_000_000) as u64
-    );
-    let new_collateral_locked = new_max_notional / 10;
+    let new_collateral_locked = MarketMaker::margin_for_quote(
+        quote.bid_remaining,
+        quote.bid_price,
+        quote.ask_remaining,
+        quote.ask_price,
+    )?;
     let collateral_freed = quote.collateral_locked.saturating_sub(new_collateral_locked);
 
     let market_maker = &mut ctx.accounts.market_maker;
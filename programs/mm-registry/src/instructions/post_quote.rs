@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use perps_math::Bps;
 use crate::state::{MmRegistry, MarketMaker, TwoSidedQuote, MmStatus};
 use crate::errors::MmRegistryError;
 
@@ -63,8 +64,8 @@ pub fn handler(ctx: Context<PostQuote>, params: PostQuoteParams) -> Result<()> {
     );
 
     // Check spread
-    let spread = ((params.ask_price - params.bid_price) as u128 * 10000 / params.bid_price as u128) as u32;
-    require!(spread <= registry.max_spread, MmRegistryError::SpreadTooWide);
+    let spread = TwoSidedQuote::spread_between(params.bid_price, params.ask_price)?;
+    require!(spread <= Bps::from(registry.max_spread), MmRegistryError::SpreadTooWide);
 
     // Check max quotes
     require!(
@@ -73,11 +74,12 @@ pub fn handler(ctx: Context<PostQuote>, params: PostQuoteParams) -> Result<()> {
     );
 
     // Calculate collateral requirement (10% of max potential exposure)
-    let max_notional = std::cmp::max(
-        (params.bid_size as u128 * params.bid_price as u128 / 1_000_000) as u64,
-        (params.ask_size as u128 * params.ask_price as u128 / 1_000_000) as u64
-    );
-    let collateral_required = max_notional / 10;
+    let collateral_required = MarketMaker::margin_for_quote(
+        params.bid_size,
+        params.bid_price,
+        params.ask_size,
+        params.ask_price,
+    )?;
 
     require!(
         market_maker.has_available_collateral(collateral_required),
@@ -129,7 +131,7 @@ pub fn handler(ctx: Context<PostQuote>, params: PostQuoteParams) -> Result<()> {
         params.bid_price,
         params.ask_size,
         params.ask_price,
-        spread
+        spread.0
     );
 
     Ok(())
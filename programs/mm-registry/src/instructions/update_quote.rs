@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use perps_math::Bps;
 use crate::state::{MmRegistry, MarketMaker, TwoSidedQuote, MmStatus};
 use crate::errors::MmRegistryError;
 
@@ -52,8 +53,8 @@ pub fn handler(ctx: Context<UpdateQuote>, params: UpdateQuoteParams) -> Result<(
     require!(new_ask_price > new_bid_price, MmRegistryError::InvalidPrice);
 
     // Check spread
-    let spread = ((new_ask_price - new_bid_price) as u128 * 10000 / new_bid_price as u128) as u32;
-    require!(spread <= registry.max_spread, MmRegistryError::SpreadTooWide);
+    let spread = TwoSidedQuote::spread_between(new_bid_price, new_ask_price)?;
+    require!(spread <= Bps::from(registry.max_spread), MmRegistryError::SpreadTooWide);
 
     // Update sizes if provided (can only increase, not decrease below remaining)
     let new_bid_size = params.bid_size.unwrap_or(quote.bid_size);
@@ -77,11 +78,12 @@ pub fn handler(ctx: Context<UpdateQuote>, params: UpdateQuoteParams) -> Result<(
 
     // Calculate new collateral requirement
     let old_collateral = quote.collateral_locked;
-    let new_max_notional = std::cmp::max(
-        (new_bid_remaining as u128 * new_bid_price as u128 / 1_000_000) as u64,
-        (new_ask_remaining as u128 * new_ask_price as u128 / 1_000_000) as u64
-    );
-    let new_collateral_required = new_max_notional / 10;
+    let new_collateral_required = MarketMaker::margin_for_quote(
+        new_bid_remaining,
+        new_bid_price,
+        new_ask_remaining,
+        new_ask_price,
+    )?;
 
     // Adjust collateral
     let market_maker = &mut ctx.accounts.market_maker;
@@ -119,7 +121,7 @@ pub fn handler(ctx: Context<UpdateQuote>, params: UpdateQuoteParams) -> Result<(
         new_bid_price,
         new_ask_remaining,
         new_ask_price,
-        spread
+        spread.0
     );
 
     Ok(())
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use perps_math::{Bps, Price, Rounding, Size};
+use crate::errors::MmRegistryError;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
 pub enum MmStatus {
@@ -98,11 +100,17 @@ impl MarketMaker {
         self.collateral_available >= amount
     }
 
-    /// Calculate margin requirement for a quote
-    pub fn margin_for_quote(&self, size: u64, price: u64) -> u64 {
-        // 10% margin requirement for quotes
-        let notional = (size as u128 * price as u128 / 1_000_000) as u64;
-        notional / 10
+    /// Margin a quote must lock: 10% of its larger side's notional, rounded up
+    pub fn margin_for_quote(bid_size: u64, bid_price: u64, ask_size: u64, ask_price: u64) -> Result<u64> {
+        let bid_notional = Price(bid_price)
+            .notional(Size(bid_size), Rounding::Up)
+            .map_err(MmRegistryError::from)?;
+        let ask_notional = Price(ask_price)
+            .notional(Size(ask_size), Rounding::Up)
+            .map_err(MmRegistryError::from)?;
+        Ok(Bps(1000)
+            .of(bid_notional.max(ask_notional), Rounding::Up)
+            .map_err(MmRegistryError::from)?)
     }
 
     /// Lock collateral for a quote
@@ -118,7 +126,7 @@ impl MarketMaker {
     }
 
     /// Update inventory after a fill
-    pub fn update_inventory(&mut self, size: i64, price: u64, is_buy: bool) {
+    pub fn update_inventory(&mut self, size: i64, price: u64, is_buy: bool) -> Result<()> {
         let signed_size = if is_buy { size } else { -size };
 
         if self.inventory == 0 {
@@ -126,22 +134,25 @@ impl MarketMaker {
             self.inventory = signed_size;
             self.avg_inventory_price = price;
         } else if (self.inventory > 0 && signed_size > 0) || (self.inventory < 0 && signed_size < 0) {
-            // Adding to position - calculate new average price
-            let old_value = (self.inventory.abs() as u128) * (self.avg_inventory_price as u128);
-            let new_value = (signed_size.abs() as u128) * (price as u128);
-            let total_size = self.inventory.abs() + signed_size.abs();
-            self.avg_inventory_price = ((old_value + new_value) / total_size as u128) as u64;
+            // Adding to position - calculate new average price, rounded against the MM
+            let rounding = if self.inventory > 0 { Rounding::Up } else { Rounding::Down };
+            self.avg_inventory_price = Price(self.avg_inventory_price)
+                .weighted_average(
+                    Size(self.inventory.unsigned_abs()),
+                    Price(price),
+                    Size(signed_size.unsigned_abs()),
+                    rounding,
+                )
+                .map_err(MmRegistryError::from)?
+                .0;
             self.inventory += signed_size;
         } else {
             // Reducing position - realize PnL
-            let close_size = signed_size.abs().min(self.inventory.abs());
-            let pnl = if self.inventory > 0 {
-                // Was long, selling
-                ((price as i128 - self.avg_inventory_price as i128) * close_size as i128 / 1_000_000) as i64
-            } else {
-                // Was short, buying
-                ((self.avg_inventory_price as i128 - price as i128) * close_size as i128 / 1_000_000) as i64
-            };
+            let close_size = signed_size.unsigned_abs().min(self.inventory.unsigned_abs());
+            // Long inventory realizes on a sale, short inventory on a purchase
+            let pnl = Size(close_size)
+                .pnl(Price(self.avg_inventory_price), Price(price), self.inventory > 0, Rounding::Down)
+                .map_err(MmRegistryError::from)?;
 
             self.realized_pnl += pnl;
             self.inventory += signed_size;
@@ -151,22 +162,22 @@ impl MarketMaker {
                 self.avg_inventory_price = price;
             }
         }
+        Ok(())
     }
 
     /// Calculate unrealized PnL at current price
-    pub fn calculate_unrealized_pnl(&self, current_price: u64) -> i64 {
+    pub fn calculate_unrealized_pnl(&self, current_price: u64) -> Result<i64> {
         if self.inventory == 0 {
-            return 0;
+            return Ok(0);
         }
 
-        let pnl = if self.inventory > 0 {
-            // Long position
-            ((current_price as i128 - self.avg_inventory_price as i128) * self.inventory as i128 / 1_000_000) as i64
-        } else {
-            // Short position
-            ((self.avg_inventory_price as i128 - current_price as i128) * (-self.inventory) as i128 / 1_000_000) as i64
-        };
-
-        pnl
+        Ok(Size(self.inventory.unsigned_abs())
+            .pnl(
+                Price(self.avg_inventory_price),
+                Price(current_price),
+                self.inventory > 0,
+                Rounding::Down,
+            )
+            .map_err(MmRegistryError::from)?)
     }
 }
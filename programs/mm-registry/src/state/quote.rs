@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use perps_math::{Bps, Rounding};
+use crate::errors::MmRegistryError;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
 pub enum QuoteSide {
@@ -120,14 +122,11 @@ impl Quote {
     }
 
     /// Calculate spread from oracle price (basis points)
-    pub fn spread_from_oracle(&self, oracle_price: u64) -> u32 {
-        let diff = if self.price > oracle_price {
-            self.price - oracle_price
-        } else {
-            oracle_price - self.price
-        };
-
-        ((diff as u128 * 10000) / oracle_price as u128) as u32
+    pub fn spread_from_oracle(&self, oracle_price: u64) -> Result<u32> {
+        let spread = Bps::ratio(self.price.abs_diff(oracle_price), oracle_price, Rounding::Up)
+            .and_then(Bps::to_u32)
+            .map_err(MmRegistryError::from)?;
+        Ok(spread)
     }
 }
 
@@ -200,11 +199,19 @@ impl TwoSidedQuote {
         32;   // padding
 
     /// Calculate spread (basis points)
-    pub fn spread(&self) -> u32 {
+    pub fn spread(&self) -> Result<u32> {
         if self.bid_price == 0 {
-            return 0;
+            return Ok(0);
         }
-        ((self.ask_price - self.bid_price) as u128 * 10000 / self.bid_price as u128) as u32
+        let spread = Self::spread_between(self.bid_price, self.ask_price)?;
+        Ok(spread.to_u32().map_err(MmRegistryError::from)?)
+    }
+
+    /// Spread of an ask over a bid as a share of the bid, rounded up so the
+    /// registry's maximum binds
+    pub fn spread_between(bid_price: u64, ask_price: u64) -> Result<Bps> {
+        let width = ask_price.checked_sub(bid_price).ok_or(MmRegistryError::InvalidPrice)?;
+        Ok(Bps::ratio(width, bid_price, Rounding::Up).map_err(MmRegistryError::from)?)
     }
 
     /// Get mid price
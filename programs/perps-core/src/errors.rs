@@ -83,4 +83,236 @@ pub enum PerpsError {
 
     #[msg("Invalid referral parameters")]
     InvalidReferralParams,
+
+    // Execution errors
+    #[msg("Execution price is worse than the acceptable price")]
+    SlippageExceeded,
+
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+
+    // Collateral errors
+    #[msg("Collateral asset is not registered")]
+    CollateralAssetNotFound,
+
+    #[msg("Collateral asset is already registered")]
+    CollateralAssetExists,
+
+    #[msg("Collateral asset is not accepting deposits")]
+    CollateralAssetInactive,
+
+    #[msg("Collateral registry is full")]
+    CollateralRegistryFull,
+
+    #[msg("Margin credit must be repaid through the margin account")]
+    MarginCreditOutstanding,
+
+    // Trading session errors
+    #[msg("Market is outside trading hours and reduce-only")]
+    MarketClosed,
+
+    #[msg("Trading schedule account is required for this market")]
+    TradingScheduleRequired,
+
+    #[msg("Invalid trading schedule")]
+    InvalidTradingSchedule,
+
+    // Dated futures errors
+    #[msg("Market has expired")]
+    MarketExpired,
+
+    #[msg("Market has not expired yet")]
+    MarketNotExpired,
+
+    #[msg("Operation is only available on dated futures markets")]
+    NotDatedFuture,
+
+    #[msg("Operation is only available on perpetual markets")]
+    NotPerpetual,
+
+    #[msg("Outside the settlement price window")]
+    OutsideSettlementWindow,
+
+    #[msg("Settlement price has not been finalized")]
+    SettlementNotFinalized,
+
+    #[msg("Settlement price is already finalized")]
+    SettlementAlreadyFinalized,
+
+    #[msg("Markets are not consecutive expiries of the same contract")]
+    InvalidRollTarget,
+
+    // Circuit breaker errors
+    #[msg("Price band breached, market is reduce-only")]
+    PriceBandBreached,
+
+    // Position transfer errors
+    #[msg("Position is wrapped as a token, unwrap it first")]
+    PositionWrapped,
+
+    #[msg("Position is not wrapped")]
+    PositionNotWrapped,
+
+    // Global settlement errors
+    #[msg("Market is in global settlement")]
+    MarketInGlobalSettlement,
+
+    #[msg("Market is not in global settlement")]
+    MarketNotInGlobalSettlement,
+
+    #[msg("Global settlement still has open positions")]
+    GlobalSettlementIncomplete,
+
+    #[msg("Position has no settlement claim")]
+    NoSettlementClaim,
+
+    // Migration errors
+    #[msg("Account is already at the current version")]
+    AccountAlreadyMigrated,
+
+    #[msg("Account version is not supported by this program")]
+    UnsupportedAccountVersion,
+
+    // Open interest cap errors
+    #[msg("Open interest imbalance cap exceeded")]
+    OpenInterestImbalanceExceeded,
+
+    #[msg("Per-user open interest cap exceeded")]
+    UserOpenInterestCapExceeded,
+
+    #[msg("User market account required for this market")]
+    UserMarketAccountRequired,
+
+    // Batch liquidation errors
+    #[msg("Invalid batch liquidation accounts")]
+    InvalidBatchAccounts,
+
+    // Liquidation auction errors
+    #[msg("Market does not use this liquidation mode")]
+    WrongLiquidationMode,
+
+    #[msg("Invalid liquidation auction parameters")]
+    InvalidAuctionParams,
+
+    #[msg("Position is still liquidatable")]
+    PositionStillLiquidatable,
+
+    // Vault accounting errors
+    #[msg("Token account is not the vault's token account")]
+    InvalidVaultTokenAccount,
+
+    // Competition errors
+    #[msg("Invalid competition parameters")]
+    InvalidCompetitionParams,
+
+    #[msg("Competition has ended")]
+    CompetitionEnded,
+
+    #[msg("Competition has not ended yet")]
+    CompetitionNotEnded,
+
+    #[msg("Competition is full")]
+    CompetitionFull,
+
+    #[msg("Already entered in this competition")]
+    AlreadyInCompetition,
+
+    #[msg("Not entered in this competition")]
+    NotInCompetition,
+
+    #[msg("Competition already finalized")]
+    CompetitionFinalized,
+
+    #[msg("Competition not finalized yet")]
+    CompetitionNotFinalized,
+
+    // Spread errors
+    #[msg("Invalid spread market parameters")]
+    InvalidSpreadParams,
+
+    #[msg("Spread market is not active")]
+    SpreadMarketInactive,
+
+    #[msg("Market is not this leg of the spread")]
+    InvalidSpreadLeg,
+
+    // Index market errors
+    #[msg("Invalid index basket")]
+    InvalidIndexBasket,
+
+    #[msg("Index feeds missing or out of order")]
+    IndexFeedMismatch,
+
+    #[msg("Index basket can only change while the market has no open interest")]
+    IndexBasketLocked,
+
+    // Contract spec errors
+    #[msg("Invalid contract specs")]
+    InvalidContractSpecs,
+
+    #[msg("Size must be a whole number of lots")]
+    SizeNotLotMultiple,
+
+    #[msg("Limit price must be a multiple of the tick size")]
+    PriceNotOnTick,
+
+    // Risk tier errors
+    #[msg("Invalid risk tiers")]
+    InvalidRiskTiers,
+
+    #[msg("Position notional exceeds the market's largest risk tier")]
+    RiskTierExceeded,
+
+    // Margin debt errors
+    #[msg("Margin account has no debt to recover")]
+    NoMarginDebt,
+
+    #[msg("Repayment too small to seize any collateral")]
+    NothingToSeize,
+
+    // Spread close-out errors
+    #[msg("Global settlement account required for a settling leg")]
+    GlobalSettlementAccountRequired,
+
+    #[msg("Owner token account required to pay out the spread")]
+    OwnerTokenAccountRequired,
+
+    // Global settlement accounting errors
+    #[msg("Amount exceeds what this market holds outside of settlement claims")]
+    SettlementBalanceReserved,
+
+    // Futures settlement errors
+    #[msg("Too little of the settlement window was sampled to settle without the authority")]
+    InsufficientSettlementSamples,
+
+    // Competition recording errors
+    #[msg("Owner is entered in a running competition; its account is required")]
+    CompetitionAccountRequired,
+
+    #[msg("Already entered in a running competition")]
+    InAnotherCompetition,
+
+    // Margin health errors
+    #[msg("Collateral registry and margin account required to value the position's margin credit")]
+    CollateralRegistryRequired,
+
+    // Trigger order errors
+    #[msg("Invalid trigger order")]
+    InvalidTriggerOrder,
+
+    #[msg("Index price has not reached the trigger price")]
+    TriggerNotReached,
+
+    #[msg("Trigger order's position is still open under its owner")]
+    TriggerOrderLive,
+
+    // Pledged collateral errors
+    #[msg("Collateral registry, margin account and asset vault required for a pledged asset")]
+    CollateralAssetAccountsRequired,
+}
+
+impl From<perps_math::MathError> for PerpsError {
+    fn from(_: perps_math::MathError) -> Self {
+        PerpsError::MathOverflow
+    }
 }
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::{CollateralAsset, CollateralRegistry, Market, Vault, MAX_COLLATERAL_ASSETS};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AddCollateralAssetParams {
+    pub haircut_bps: u16,   // Value discount (basis points, 1000 = 10%)
+}
+
+#[derive(Accounts)]
+pub struct AddCollateralAsset<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump
+    )]
+    pub collateral_registry: Account<'info, CollateralRegistry>,
+
+    #[account(constraint = asset_mint.key() != market.collateral_mint @ PerpsError::InvalidMarketConfig)]
+    pub asset_mint: Account<'info, Mint>,
+
+    /// Token account holding deposits of this asset, owned by the market vault
+    #[account(
+        init,
+        payer = authority,
+        token::mint = asset_mint,
+        token::authority = vault,
+        seeds = [b"collateral_vault", market.key().as_ref(), asset_mint.key().as_ref()],
+        bump
+    )]
+    pub asset_vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth price feed account, validated by Pyth SDK on use
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<AddCollateralAsset>, params: AddCollateralAssetParams) -> Result<()> {
+    require!(
+        params.haircut_bps <= CollateralRegistry::MAX_HAIRCUT_BPS,
+        PerpsError::InvalidMarketConfig
+    );
+
+    let registry = &mut ctx.accounts.collateral_registry;
+    let mint = ctx.accounts.asset_mint.key();
+
+    require!(
+        registry.find_asset(&mint).is_none(),
+        PerpsError::CollateralAssetExists
+    );
+    require!(
+        (registry.num_assets as usize) < MAX_COLLATERAL_ASSETS,
+        PerpsError::CollateralRegistryFull
+    );
+
+    let index = registry.num_assets as usize;
+    registry.assets[index] = CollateralAsset {
+        mint,
+        pyth_price_feed: ctx.accounts.pyth_price_feed.key(),
+        haircut_bps: params.haircut_bps,
+        decimals: ctx.accounts.asset_mint.decimals,
+        is_active: true,
+    };
+    registry.num_assets += 1;
+
+    msg!(
+        "Collateral asset added: {} at slot {} with {}bps haircut",
+        mint,
+        index,
+        params.haircut_bps
+    );
+    Ok(())
+}
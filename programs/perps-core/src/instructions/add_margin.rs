@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::{Market, Position, UserAccount, PositionStatus};
+use crate::state::{Market, Position, UserAccount, PositionStatus, MarketStatus};
 use crate::errors::PerpsError;
 
 #[derive(Accounts)]
@@ -16,8 +16,10 @@ pub struct AddMargin<'info> {
     pub user_account: Account<'info, UserAccount>,
 
     #[account(
-        seeds = [b"market", market.collateral_mint.as_ref()],
-        bump = market.bump
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
     )]
     pub market: Account<'info, Market>,
 
@@ -25,7 +27,8 @@ pub struct AddMargin<'info> {
         mut,
         constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
         constraint = position.market == market.key(),
-        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
     )]
     pub position: Account<'info, Position>,
 }
@@ -40,6 +43,10 @@ pub fn handler(ctx: Context<AddMargin>, amount: u64) -> Result<()> {
     );
 
     // Transfer collateral from user account to position
+    let market = &mut ctx.accounts.market;
+    market.untrack_position(position)?;
+    market.debit_user_collateral(amount);
+
     user_account.collateral_balance = user_account.collateral_balance
         .checked_sub(amount)
         .ok_or(PerpsError::MathOverflow)?;
@@ -49,6 +56,7 @@ pub fn handler(ctx: Context<AddMargin>, amount: u64) -> Result<()> {
         .ok_or(PerpsError::MathOverflow)?;
 
     position.last_updated_at = Clock::get()?.unix_timestamp;
+    market.track_position(position)?;
 
     msg!("Added {} margin to position", amount);
     Ok(())
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::{GlobalSettlement, Market, Vault};
+
+/// Breakdown of what a market's vault holds against what it owes, returned as
+/// instruction return data. Amounts are in collateral units (6 decimals).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct VaultAudit {
+    pub token_balance: u64,                 // Tokens actually held by the vault token account
+    pub recorded_balance: u64,              // Balance the program's own transfers account for
+    pub user_collateral: i64,               // Net free collateral deposited through this market
+    pub position_collateral: u64,           // Collateral of tracked open positions held in the vault
+    pub unrealized_pnl: i64,                // Owed to (positive) or by (negative) tracked positions
+    pub insurance_fund: u64,
+    pub referral_rewards: i64,              // Net referral rewards accrued through this market
+    pub outstanding_settlement_claims: u64, // Global settlement claims not yet paid
+    pub surplus: i64,                       // token_balance minus all liabilities above
+}
+
+/// Read-only solvency check of a market's vault. Funding accrued since positions
+/// last settled is not included, and free balances and referral rewards only
+/// reconcile summed over the markets sharing a collateral mint.
+/// Margin debt shows as a deficit until `seize_margin_collateral` recovers it.
+#[derive(Accounts)]
+pub struct AuditVault<'info> {
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    // Required once the market is in global settlement; positions are valued at its price
+    #[account(
+        seeds = [b"global_settlement", market.key().as_ref()],
+        bump = global_settlement.bump
+    )]
+    pub global_settlement: Option<Account<'info, GlobalSettlement>>,
+}
+
+pub fn handler(ctx: Context<AuditVault>) -> Result<VaultAudit> {
+    let market = &ctx.accounts.market;
+    let vault = &ctx.accounts.vault;
+
+    let (price, outstanding_settlement_claims) = match &ctx.accounts.global_settlement {
+        Some(global_settlement) => (
+            global_settlement.settlement_price,
+            global_settlement.total_claims.saturating_sub(global_settlement.total_paid),
+        ),
+        None => {
+            let current_time = Clock::get()?.unix_timestamp;
+
+            // Get oracle price
+            let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+            (oracle_price, 0)
+        }
+    };
+
+    // Collateral lent against multi-collateral never entered the vault
+    let position_collateral = market.position_collateral
+        .saturating_sub(market.position_margin_credit);
+    let unrealized_pnl = market.unrealized_pnl(price);
+
+    let liabilities = market.user_collateral as i128
+        + position_collateral as i128
+        + unrealized_pnl as i128
+        + market.insurance_fund as i128
+        + market.referral_rewards as i128
+        + outstanding_settlement_claims as i128;
+    let token_balance = ctx.accounts.vault_token_account.amount;
+    let surplus = (token_balance as i128 - liabilities)
+        .clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+
+    msg!(
+        "Vault audit: balance={}, recorded={}, pnl={}, surplus={}",
+        token_balance,
+        vault.total_deposits,
+        unrealized_pnl,
+        surplus
+    );
+
+    Ok(VaultAudit {
+        token_balance,
+        recorded_balance: vault.total_deposits,
+        user_collateral: market.user_collateral,
+        position_collateral,
+        unrealized_pnl,
+        insurance_fund: market.insurance_fund,
+        referral_rewards: market.referral_rewards,
+        outstanding_settlement_claims,
+        surplus,
+    })
+}
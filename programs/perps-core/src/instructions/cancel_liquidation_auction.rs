@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralRegistry, CrankRewards, LiquidationAuction, MarginAccount, Market, Position, PositionStatus, UserAccount, unbacked_credit};
+use crate::errors::PerpsError;
+
+/// Permissionless: ends an auction whose position was closed or recovered,
+/// refunding the rent to whoever started it
+#[derive(Accounts)]
+pub struct CancelLiquidationAuction<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(constraint = position.market == market.key())]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidation_auction", position.key().as_ref()],
+        bump = auction.bump,
+        close = starter
+    )]
+    pub auction: Account<'info, LiquidationAuction>,
+
+    /// CHECK: Rent refund destination, must be the auction's starter
+    #[account(mut, constraint = starter.key() == auction.starter)]
+    pub starter: AccountInfo<'info>,
+
+    /// CHECK: Pyth price feed
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    // Optional crank reward, paid into the keeper's free collateral
+    #[account(
+        mut,
+        seeds = [b"crank_rewards", market.key().as_ref()],
+        bump = crank_rewards.bump,
+    )]
+    pub crank_rewards: Option<Account<'info, CrankRewards>>,
+
+    #[account(
+        mut,
+        seeds = [b"user", keeper.key().as_ref()],
+        bump = keeper_user_account.bump,
+    )]
+    pub keeper_user_account: Option<Account<'info, UserAccount>>,
+
+    // Values the pledged collateral behind margin credit; required with the margin
+    // account when the position drew any, with the asset feeds after the index feeds
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump,
+    )]
+    pub collateral_registry: Option<Account<'info, CollateralRegistry>>,
+
+    #[account(
+        seeds = [b"margin_account", market.key().as_ref(), position.owner.as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+}
+
+pub fn handler(ctx: Context<CancelLiquidationAuction>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let position = &ctx.accounts.position;
+
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // A closed position needs no price check
+    if position.status == PositionStatus::Open {
+
+        let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+        market.update_mark_price(oracle_price, current_time);
+        let (_, asset_feeds) = market.split_index_feeds(ctx.remaining_accounts)?;
+        let unbacked = unbacked_credit(
+            position,
+            ctx.accounts.collateral_registry.as_deref(),
+            ctx.accounts.margin_account.as_deref(),
+            asset_feeds,
+            current_time,
+        )?;
+        require!(
+            !position.is_backed_liquidatable(market.mark_price, market.position_maintenance_margin(position)?, unbacked)?,
+            PerpsError::PositionStillLiquidatable
+        );
+    }
+
+    if let (Some(crank_rewards), Some(keeper_user_account)) =
+        (ctx.accounts.crank_rewards.as_deref_mut(), ctx.accounts.keeper_user_account.as_deref_mut())
+    {
+        crank_rewards.pay(market, keeper_user_account, current_time);
+    }
+
+    msg!("Liquidation auction cancelled: position={}", position.key());
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use crate::state::TriggerOrder;
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct CancelTriggerOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = trigger_order.owner == owner.key() @ PerpsError::Unauthorized,
+        close = owner
+    )]
+    pub trigger_order: Account<'info, TriggerOrder>,
+}
+
+pub fn handler(ctx: Context<CancelTriggerOrder>) -> Result<()> {
+    msg!("Trigger order cancelled: {}", ctx.accounts.trigger_order.key());
+    Ok(())
+}
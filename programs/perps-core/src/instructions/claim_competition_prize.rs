@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::Competition;
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct ClaimCompetitionPrize<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+        constraint = competition.is_finalized @ PerpsError::CompetitionNotFinalized
+    )]
+    pub competition: Box<Account<'info, Competition>>,
+
+    #[account(
+        mut,
+        seeds = [b"competition_escrow", competition.key().as_ref()],
+        bump
+    )]
+    pub prize_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key(),
+        constraint = owner_token_account.mint == competition.prize_mint
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<ClaimCompetitionPrize>) -> Result<()> {
+    let competition = &mut ctx.accounts.competition;
+    let index = competition
+        .find_entry(&ctx.accounts.owner.key())
+        .ok_or(PerpsError::NotInCompetition)?;
+
+    let entry = &mut competition.entries[index];
+    require!(entry.prize > 0 && !entry.claimed, PerpsError::NoRewardsToClaim);
+    entry.claimed = true;
+    let prize = entry.prize;
+
+    let authority = competition.authority;
+    let id_bytes = competition.id.to_le_bytes();
+    let competition_seeds = &[
+        b"competition".as_ref(),
+        authority.as_ref(),
+        id_bytes.as_ref(),
+        &[competition.bump],
+    ];
+    let signer = &[&competition_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.prize_escrow.to_account_info(),
+        to: ctx.accounts.owner_token_account.to_account_info(),
+        authority: competition.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::transfer(cpi_ctx, prize)?;
+
+    msg!("Competition {} prize claimed: {} to {}", competition.id, prize, ctx.accounts.owner.key());
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{GlobalSettlement, Market, MarketStatus, Position, Vault};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct ClaimGlobalSettlement<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Settled @ PerpsError::GlobalSettlementIncomplete
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"global_settlement", market.key().as_ref()],
+        bump = global_settlement.bump
+    )]
+    pub global_settlement: Account<'info, GlobalSettlement>,
+
+    #[account(
+        mut,
+        constraint = position.market == market.key(),
+        constraint = position.settlement_claim > 0 @ PerpsError::NoSettlementClaim,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == position.owner @ PerpsError::Unauthorized,
+        constraint = owner_token_account.mint == market.collateral_mint
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<ClaimGlobalSettlement>) -> Result<()> {
+    let global_settlement = &mut ctx.accounts.global_settlement;
+    let position = &mut ctx.accounts.position;
+
+    // Free collateral, insurance and referral rewards still held are never paid to claims
+    let claim = position.settlement_claim;
+    let available = ctx.accounts.vault_token_account.amount
+        .saturating_sub(ctx.accounts.market.reserved_balance());
+    let payout = global_settlement.payout_for(claim).min(available);
+
+    position.settlement_claim = 0;
+    global_settlement.total_paid = global_settlement.total_paid
+        .checked_add(payout)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    if payout > 0 {
+        let market_key = ctx.accounts.market.key();
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, payout)?;
+        ctx.accounts.vault.record_outflow(payout);
+    }
+
+    msg!("Settlement claimed: claim={}, paid={}", claim, payout);
+
+    Ok(())
+}
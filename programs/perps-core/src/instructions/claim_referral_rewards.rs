@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::state::{Market, ReferralCode, Vault};
+use crate::state::{Market, MarketStatus, ReferralCode, Vault};
 use crate::errors::PerpsError;
 
 #[derive(Accounts)]
@@ -17,7 +17,8 @@ pub struct ClaimReferralRewards<'info> {
     pub referral_code: Account<'info, ReferralCode>,
 
     #[account(
-        seeds = [b"market", market.collateral_mint.as_ref()],
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
         bump = market.bump
     )]
     pub market: Account<'info, Market>,
@@ -63,6 +64,15 @@ pub fn handler(ctx: Context<ClaimReferralRewards>) -> Result<()> {
         PerpsError::InsufficientVaultBalance
     );
 
+    // Once in global settlement the vault only pays out rewards accrued through this
+    // market; the rest is owed to settlement claims
+    if ctx.accounts.market.status != MarketStatus::Active {
+        require!(
+            amount as i64 <= ctx.accounts.market.referral_rewards,
+            PerpsError::SettlementBalanceReserved
+        );
+    }
+
     // Reset pending rewards
     referral_code.pending_rewards = 0;
 
@@ -87,6 +97,11 @@ pub fn handler(ctx: Context<ClaimReferralRewards>) -> Result<()> {
         signer,
     );
     token::transfer(cpi_ctx, amount)?;
+    ctx.accounts.vault.record_outflow(amount);
+
+    // Rewards are claimed from whichever market's vault is passed
+    let market = &mut ctx.accounts.market;
+    market.referral_rewards = market.referral_rewards.saturating_sub(amount as i64);
 
     msg!(
         "Claimed {} referral rewards for code {}",
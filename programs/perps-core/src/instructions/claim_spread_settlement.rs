@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{GlobalSettlement, Market, MarketStatus, SpreadMarket, SpreadPosition, Vault};
+use crate::errors::PerpsError;
+
+/// Pays a spread's claim on leg A's global settlement, pro rata like position claims
+#[derive(Accounts)]
+pub struct ClaimSpreadSettlement<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [b"spread_market", spread_market.leg_a_market.as_ref(), spread_market.leg_b_market.as_ref()],
+        bump = spread_market.bump
+    )]
+    pub spread_market: Box<Account<'info, SpreadMarket>>,
+
+    #[account(
+        address = spread_market.leg_a_market @ PerpsError::InvalidSpreadLeg,
+        constraint = leg_a_market.status == MarketStatus::Settled @ PerpsError::GlobalSettlementIncomplete
+    )]
+    pub leg_a_market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"global_settlement", leg_a_market.key().as_ref()],
+        bump = global_settlement.bump
+    )]
+    pub global_settlement: Box<Account<'info, GlobalSettlement>>,
+
+    #[account(
+        mut,
+        constraint = spread_position.spread_market == spread_market.key(),
+        constraint = spread_position.settlement_claim > 0 @ PerpsError::NoSettlementClaim
+    )]
+    pub spread_position: Box<Account<'info, SpreadPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", leg_a_market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", leg_a_market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == spread_position.owner @ PerpsError::Unauthorized,
+        constraint = owner_token_account.mint == leg_a_market.collateral_mint
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<ClaimSpreadSettlement>) -> Result<()> {
+    let global_settlement = &mut ctx.accounts.global_settlement;
+    let spread_position = &mut ctx.accounts.spread_position;
+
+    // Free collateral, insurance and referral rewards still held are never paid to claims
+    let claim = spread_position.settlement_claim;
+    let available = ctx.accounts.vault_token_account.amount
+        .saturating_sub(ctx.accounts.leg_a_market.reserved_balance());
+    let payout = global_settlement.payout_for(claim).min(available);
+
+    spread_position.settlement_claim = 0;
+    global_settlement.total_paid = global_settlement.total_paid
+        .checked_add(payout)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    if payout > 0 {
+        let market_key = ctx.accounts.leg_a_market.key();
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, payout)?;
+        ctx.accounts.vault.record_outflow(payout);
+    }
+
+    msg!("Spread settlement claimed: claim={}, paid={}", claim, payout);
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::{GlobalSettlement, MarginAccount, Market, MarketStatus, Position, PositionStatus, Side, UserAccount, Competition, record_competition_result, load_initialized_user_market};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct CloseOutPosition<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", position.owner.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Settling @ PerpsError::MarketNotInGlobalSettlement
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"global_settlement", market.key().as_ref()],
+        bump = global_settlement.bump
+    )]
+    pub global_settlement: Account<'info, GlobalSettlement>,
+
+    #[account(
+        mut,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    // Required when the position drew margin credit against multi-collateral
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), position.owner.as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    /// CHECK: The owner's user market PDA, counting their open interest. Only read if
+    /// initialized, since the keeper can't be made to create it for the owner.
+    #[account(
+        mut,
+        seeds = [b"user_market", market.key().as_ref(), position.owner.as_ref()],
+        bump,
+    )]
+    pub user_market: UncheckedAccount<'info>,
+
+    // Required while the owner is entered in a running competition, recording the close-out
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+}
+
+pub fn handler(ctx: Context<CloseOutPosition>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let global_settlement = &mut ctx.accounts.global_settlement;
+    let position = &mut ctx.accounts.position;
+    let user_account = &mut ctx.accounts.user_account;
+    let settlement_price = global_settlement.settlement_price;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // PnL at the settlement price plus funding accrued up to the freeze, no fee
+    let pnl = position.unrealized_pnl(settlement_price)?;
+    let funding_payment = position.funding_payment(position.size, market.funding_rate)?;
+    let total_pnl = pnl + funding_payment;
+    let equity = perps_math::equity(position.collateral, total_pnl).map_err(PerpsError::from)?;
+
+    // Margin lent against multi-collateral is repaid before the claim is recorded;
+    // what the equity can't repay becomes debt against the pledged assets
+    let credit_repaid = if position.margin_credit > 0 {
+        let margin_account = ctx.accounts.margin_account
+            .as_mut()
+            .ok_or(PerpsError::MarginCreditOutstanding)?;
+        margin_account.settle_credit(position.margin_credit, equity)
+    } else {
+        0
+    };
+    let claim = equity - credit_repaid;
+
+    market.untrack_position(position)?;
+
+    match position.side {
+        Side::Long => {
+            market.long_open_interest = market.long_open_interest
+                .saturating_sub(position.size);
+        }
+        Side::Short => {
+            market.short_open_interest = market.short_open_interest
+                .saturating_sub(position.size);
+        }
+    }
+    let mut user_market = load_initialized_user_market(&ctx.accounts.user_market)?;
+    if let Some(user_market) = user_market.as_mut() {
+        user_market.remove_open_interest(position.size);
+    }
+
+    position.status = PositionStatus::Closed;
+    position.margin_credit = 0;
+    position.settlement_claim = claim;
+    position.realized_pnl = total_pnl;
+    position.last_updated_at = current_time;
+
+    user_account.realized_pnl = user_account.realized_pnl
+        .saturating_add(total_pnl);
+    if let Some(user_market) = user_market.as_mut() {
+        user_market.record_funding(funding_payment);
+        user_market.record_pnl(total_pnl);
+        user_market.record_close(total_pnl);
+        user_market.exit(&crate::ID)?;
+    }
+    record_competition_result(
+        ctx.accounts.competition.as_deref_mut(),
+        user_account,
+        &market.key(),
+        total_pnl,
+        position.collateral,
+        current_time,
+    )?;
+
+    global_settlement.total_claims = global_settlement.total_claims
+        .checked_add(claim)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    // The last close-out fixes the pot that claims are paid from
+    global_settlement.complete_if_flat(market, ctx.accounts.vault_token_account.amount);
+
+    msg!(
+        "Position closed out: PnL={}, Funding={}, CreditRepaid={}, Claim={}",
+        pnl,
+        funding_payment,
+        credit_repaid,
+        claim
+    );
+
+    Ok(())
+}
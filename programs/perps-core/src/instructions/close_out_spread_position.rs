@@ -0,0 +1,289 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{
+    GlobalSettlement, Market, MarketStatus, PositionStatus, Side, SpreadMarket, SpreadPosition,
+    UserAccount, Vault, load_initialized_user_market,
+};
+use crate::errors::PerpsError;
+use crate::instructions::close_spread_position::move_leg_b_settlement;
+
+/// Closes out both legs of a spread once either leg's market is in global settlement.
+/// A settling leg closes at its settlement price and an active leg at its index price,
+/// with funding and no fee. The equity becomes a claim on leg A's global settlement
+/// when leg A is settling and is paid out directly otherwise.
+#[derive(Accounts)]
+pub struct CloseOutSpreadPosition<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", spread_position.owner.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"spread_market", spread_market.leg_a_market.as_ref(), spread_market.leg_b_market.as_ref()],
+        bump = spread_market.bump
+    )]
+    pub spread_market: Box<Account<'info, SpreadMarket>>,
+
+    #[account(
+        mut,
+        address = spread_market.leg_a_market @ PerpsError::InvalidSpreadLeg
+    )]
+    pub leg_a_market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        address = spread_market.leg_b_market @ PerpsError::InvalidSpreadLeg
+    )]
+    pub leg_b_market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        constraint = spread_position.spread_market == spread_market.key(),
+        constraint = spread_position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed
+    )]
+    pub spread_position: Box<Account<'info, SpreadPosition>>,
+
+    // Leg A's vault holds the collateral and pays the settlement
+    #[account(
+        mut,
+        seeds = [b"vault", leg_a_market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", leg_a_market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Leg B's vault settles leg B's PnL and funding, and is snapshotted if this
+    // close-out flattens a settling leg B
+    #[account(
+        mut,
+        seeds = [b"vault", leg_b_market.key().as_ref()],
+        bump = leg_b_vault.bump
+    )]
+    pub leg_b_vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", leg_b_market.key().as_ref()],
+        bump
+    )]
+    pub leg_b_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = leg_a_price_feed.key() == leg_a_market.pyth_price_feed)]
+    pub leg_a_price_feed: AccountInfo<'info>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = leg_b_price_feed.key() == leg_b_market.pyth_price_feed)]
+    pub leg_b_price_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    // Required for each leg in global settlement
+    #[account(
+        mut,
+        seeds = [b"global_settlement", leg_a_market.key().as_ref()],
+        bump = leg_a_settlement.bump
+    )]
+    pub leg_a_settlement: Option<Box<Account<'info, GlobalSettlement>>>,
+
+    #[account(
+        mut,
+        seeds = [b"global_settlement", leg_b_market.key().as_ref()],
+        bump = leg_b_settlement.bump
+    )]
+    pub leg_b_settlement: Option<Box<Account<'info, GlobalSettlement>>>,
+
+    // Receives the equity when leg A is still active
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == spread_position.owner @ PerpsError::Unauthorized,
+        constraint = owner_token_account.mint == leg_a_market.collateral_mint
+    )]
+    pub owner_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: The owner's user market PDAs, counting their open interest in each leg. Only
+    /// read if initialized, since the keeper can't be made to create them for the owner.
+    #[account(
+        mut,
+        seeds = [b"user_market", leg_a_market.key().as_ref(), spread_position.owner.as_ref()],
+        bump,
+    )]
+    pub leg_a_user_market: UncheckedAccount<'info>,
+
+    /// CHECK: As leg_a_user_market
+    #[account(
+        mut,
+        seeds = [b"user_market", leg_b_market.key().as_ref(), spread_position.owner.as_ref()],
+        bump,
+    )]
+    pub leg_b_user_market: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<CloseOutSpreadPosition>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let leg_a_settling = ctx.accounts.leg_a_market.status == MarketStatus::Settling;
+    let leg_b_settling = ctx.accounts.leg_b_market.status == MarketStatus::Settling;
+    require!(leg_a_settling || leg_b_settling, PerpsError::MarketNotInGlobalSettlement);
+
+    // Remaining accounts hold leg A's index feeds, then leg B's
+    let (_, leg_b_feeds) = ctx.accounts.leg_a_market.split_index_feeds(ctx.remaining_accounts)?;
+    let price_a = close_out_price(
+        &ctx.accounts.leg_a_market,
+        ctx.accounts.leg_a_settlement.as_deref(),
+        &ctx.accounts.leg_a_price_feed,
+        ctx.remaining_accounts,
+        current_time,
+    )?;
+    let price_b = close_out_price(
+        &ctx.accounts.leg_b_market,
+        ctx.accounts.leg_b_settlement.as_deref(),
+        &ctx.accounts.leg_b_price_feed,
+        leg_b_feeds,
+        current_time,
+    )?;
+
+    let spread_position = &mut ctx.accounts.spread_position;
+    let leg_a = spread_position.leg_a;
+    let leg_b = spread_position.leg_b;
+    let leg_a_market = &mut ctx.accounts.leg_a_market;
+    let leg_b_market = &mut ctx.accounts.leg_b_market;
+
+    // PnL at the close-out prices plus funding accrued up to now, no fee
+    let pnl = spread_position.unrealized_pnl(price_a, price_b)?;
+    let funding_payment = spread_position.funding_payment(leg_a_market.funding_rate, leg_b_market.funding_rate)?;
+    let total_pnl = pnl + funding_payment;
+    let equity = perps_math::equity(spread_position.collateral, total_pnl).map_err(PerpsError::from)?;
+    let leg_b_transfer = spread_position.leg_b_transfer(
+        leg_a.unrealized_pnl(price_a)? + leg_a.funding_payment(leg_a_market.funding_rate)?,
+        leg_b.unrealized_pnl(price_b)? + leg_b.funding_payment(leg_b_market.funding_rate)?,
+    );
+
+    leg_a_market.untrack_spread_leg(&leg_a, spread_position.collateral)?;
+    leg_b_market.untrack_spread_leg(&leg_b, 0)?;
+
+    for (market, leg) in [(&mut *leg_a_market, &leg_a), (&mut *leg_b_market, &leg_b)] {
+        match leg.side {
+            Side::Long => {
+                market.long_open_interest = market.long_open_interest.saturating_sub(leg.size);
+            }
+            Side::Short => {
+                market.short_open_interest = market.short_open_interest.saturating_sub(leg.size);
+            }
+        }
+    }
+    for (user_market, leg) in [
+        (&ctx.accounts.leg_a_user_market, &leg_a),
+        (&ctx.accounts.leg_b_user_market, &leg_b),
+    ] {
+        if let Some(mut user_market) = load_initialized_user_market(user_market)? {
+            user_market.remove_open_interest(leg.size);
+            user_market.exit(&crate::ID)?;
+        }
+    }
+
+    spread_position.status = PositionStatus::Closed;
+    spread_position.realized_pnl = total_pnl;
+    spread_position.last_updated_at = current_time;
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.realized_pnl = user_account.realized_pnl.saturating_add(total_pnl);
+
+    let spread_market = &mut ctx.accounts.spread_market;
+    spread_market.open_spreads = spread_market.open_spreads.saturating_sub(1);
+
+    move_leg_b_settlement(
+        leg_b_transfer,
+        &mut ctx.accounts.vault,
+        &ctx.accounts.vault_token_account,
+        &mut ctx.accounts.leg_b_vault,
+        &ctx.accounts.leg_b_vault_token_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    // A settling leg A owes the equity as a claim paid pro rata with its positions
+    let mut payout = 0;
+    if leg_a_settling {
+        let global_settlement = ctx.accounts.leg_a_settlement
+            .as_deref_mut()
+            .ok_or(PerpsError::GlobalSettlementAccountRequired)?;
+        spread_position.settlement_claim = equity;
+        global_settlement.total_claims = global_settlement.total_claims
+            .checked_add(equity)
+            .ok_or(PerpsError::MathOverflow)?;
+    } else if equity > 0 {
+        let owner_token_account = ctx.accounts.owner_token_account
+            .as_ref()
+            .ok_or(PerpsError::OwnerTokenAccountRequired)?;
+        payout = equity;
+
+        let market_key = leg_a_market.key();
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: owner_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, payout)?;
+        ctx.accounts.vault.record_outflow(payout);
+    }
+
+    // The last close-out of a settling leg fixes the pot its claims are paid from
+    ctx.accounts.vault_token_account.reload()?;
+    ctx.accounts.leg_b_vault_token_account.reload()?;
+    if let Some(global_settlement) = ctx.accounts.leg_a_settlement.as_deref_mut().filter(|_| leg_a_settling) {
+        global_settlement.complete_if_flat(leg_a_market, ctx.accounts.vault_token_account.amount);
+    }
+    if let Some(global_settlement) = ctx.accounts.leg_b_settlement.as_deref_mut().filter(|_| leg_b_settling) {
+        global_settlement.complete_if_flat(leg_b_market, ctx.accounts.leg_b_vault_token_account.amount);
+    }
+
+    msg!(
+        "Spread closed out: PnL={}, Funding={}, Leg B transfer={}, Claim={}, Settlement={}",
+        pnl,
+        funding_payment,
+        leg_b_transfer,
+        spread_position.settlement_claim,
+        payout
+    );
+
+    Ok(())
+}
+
+/// A settling leg closes at its global settlement price, an active one at its index price
+fn close_out_price(
+    market: &Market,
+    global_settlement: Option<&Account<GlobalSettlement>>,
+    price_feed: &AccountInfo,
+    remaining_accounts: &[AccountInfo],
+    current_time: i64,
+) -> Result<u64> {
+    if market.status != MarketStatus::Settling {
+        return market.index_price(price_feed, remaining_accounts, current_time);
+    }
+
+    let global_settlement = global_settlement.ok_or(PerpsError::GlobalSettlementAccountRequired)?;
+    Ok(global_settlement.settlement_price)
+}
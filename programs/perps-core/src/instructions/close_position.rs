@@ -1,14 +1,23 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use pyth_sdk_solana::load_price_feed_from_account_info;
-use crate::state::{Market, Position, Vault, UserAccount, Side, PositionStatus, ReferralCode, UserReferral};
+use crate::state::{Market, Position, PositionFill, Vault, UserAccount, Side, PositionStatus, ReferralCode, UserReferral, MarginAccount, MarketStatus, UserMarketAccount, Competition, record_competition_result, is_within_deadline};
 use crate::errors::PerpsError;
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClosePositionParams {
+    pub acceptable_price: u64,  // Worst fill accepted (6 decimals)
+    pub deadline: i64,          // Unix timestamp after which the close is rejected (0 = none)
+}
+
 #[derive(Accounts)]
 pub struct ClosePosition<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    // Funds the user market account's rent, so a program-derived owner holding data can trade
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     #[account(
         mut,
         seeds = [b"user", owner.key().as_ref()],
@@ -19,8 +28,9 @@ pub struct ClosePosition<'info> {
 
     #[account(
         mut,
-        seeds = [b"market", market.collateral_mint.as_ref()],
-        bump = market.bump
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
     )]
     pub market: Account<'info, Market>,
 
@@ -28,7 +38,8 @@ pub struct ClosePosition<'info> {
         mut,
         constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
         constraint = position.market == market.key(),
-        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
     )]
     pub position: Account<'info, Position>,
 
@@ -58,6 +69,7 @@ pub struct ClosePosition<'info> {
     pub pyth_price_feed: AccountInfo<'info>,
 
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 
     // Optional referral accounts - if user has a referral, include these
     #[account(
@@ -73,80 +85,111 @@ pub struct ClosePosition<'info> {
         bump = referral_code.bump,
     )]
     pub referral_code: Option<Account<'info, ReferralCode>>,
+
+    // Required when the position drew margin credit against multi-collateral
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), owner.key().as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    // Counts the owner's open interest in this market, created if they have none yet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_market: Account<'info, UserMarketAccount>,
+
+    // Required while the owner is entered in a running competition, recording the close
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+
+    // Co-signs when the close filled a resting order, making the owner the maker
+    #[account(constraint = fill_authority.key() == market.fill_authority @ PerpsError::Unauthorized)]
+    pub fill_authority: Option<Signer<'info>>,
 }
 
-pub fn handler(ctx: Context<ClosePosition>) -> Result<()> {
+pub fn handler(ctx: Context<ClosePosition>, params: ClosePositionParams) -> Result<PositionFill> {
     let market = &mut ctx.accounts.market;
     let position = &mut ctx.accounts.position;
     let user_account = &mut ctx.accounts.user_account;
+    ctx.accounts.user_market.initialize_if_new(
+        ctx.accounts.owner.key(),
+        market.key(),
+        *ctx.bumps.get("user_market").unwrap(),
+    );
 
-    // Get oracle price
-    let price_feed = load_price_feed_from_account_info(&ctx.accounts.pyth_price_feed)
-        .map_err(|_| PerpsError::InvalidOraclePrice)?;
+    require!(market.is_on_tick(params.acceptable_price), PerpsError::PriceNotOnTick);
 
     let current_time = Clock::get()?.unix_timestamp;
-    let price_data = price_feed
-        .get_price_no_older_than(current_time, 60)
-        .ok_or(PerpsError::StaleOraclePrice)?;
+    require!(
+        is_within_deadline(params.deadline, current_time),
+        PerpsError::DeadlineExceeded
+    );
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
 
-    require!(price_data.price > 0, PerpsError::InvalidOraclePrice);
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
 
-    let oracle_price = (price_data.price as u64)
-        .checked_mul(10u64.pow((6 + price_data.expo.abs() as u32) as u32))
-        .and_then(|p| p.checked_div(10u64.pow(price_data.expo.abs() as u32)))
-        .ok_or(PerpsError::MathOverflow)? as u64;
+    // Closing trades against the position's side
+    require!(
+        position.side.opposite().is_acceptable_price(oracle_price, params.acceptable_price),
+        PerpsError::SlippageExceeded
+    );
 
     // Calculate PnL
-    let pnl = position.unrealized_pnl(oracle_price);
+    let pnl = position.unrealized_pnl(oracle_price)?;
 
     // Calculate funding payment
-    let funding_diff = market.funding_rate - position.last_funding_payment;
-    let funding_payment = match position.side {
-        Side::Long => -((position.size as i64 * funding_diff) / 1_000_000),
-        Side::Short => (position.size as i64 * funding_diff) / 1_000_000,
-    };
+    let funding_payment = position.funding_payment(position.size, market.funding_rate)?;
 
     // Calculate fees
-    let notional = position.notional_value();
-    let base_fee = (notional as u128 * market.taker_fee as u128 / 10000) as u64;
+    let notional = position.notional_value()?;
+    let is_maker = ctx.accounts.fill_authority.is_some();
+    let (base_fee, rebate) = market.fill_fee(notional, is_maker)?;
 
     // Process referral if user has one
     let (fee, referral_reward) = if let (Some(user_referral), Some(referral_code)) =
         (&mut ctx.accounts.user_referral, &mut ctx.accounts.referral_code)
     {
-        // Validate referral relationship
-        if user_referral.referral_code == referral_code.key() && referral_code.is_active {
-            // Calculate discounted fee for user
-            let discount = (base_fee as u128 * user_referral.discount_bps as u128 / 10000) as u64;
-            let discounted_fee = base_fee.saturating_sub(discount);
-
-            // Calculate reward for referrer (based on discounted fee to prevent gaming)
-            let reward = (discounted_fee as u128 * referral_code.reward_bps as u128 / 10000) as u64;
-
-            // Update user referral stats (FIXES SYBIL ATTACK - tracked on actual trades)
-            user_referral.total_volume = user_referral.total_volume.saturating_add(notional);
-            user_referral.total_fees_paid = user_referral.total_fees_paid.saturating_add(discounted_fee);
-            user_referral.total_referrer_rewards = user_referral.total_referrer_rewards.saturating_add(reward);
-
-            // Update referral code stats
-            referral_code.total_volume = referral_code.total_volume.saturating_add(notional);
-            referral_code.total_fees_generated = referral_code.total_fees_generated.saturating_add(discounted_fee);
-            referral_code.total_rewards_earned = referral_code.total_rewards_earned.saturating_add(reward);
-            referral_code.pending_rewards = referral_code.pending_rewards.saturating_add(reward);
-
-            msg!("Referral processed: discount={}, reward={}", discount, reward);
-
-            (discounted_fee, reward)
-        } else {
-            (base_fee, 0)
-        }
+        let referral_code_key = referral_code.key();
+        user_referral.process_trade_fee(referral_code_key, referral_code, base_fee, notional)?
     } else {
         (base_fee, 0)
     };
 
     // Final settlement amount
-    let total_pnl = pnl + funding_payment - fee as i64;
-    let settlement = (position.collateral as i64 + total_pnl).max(0) as u64;
+    let total_pnl = pnl + funding_payment - fee as i64 + rebate as i64;
+    let settlement = perps_math::equity(position.collateral, total_pnl).map_err(PerpsError::from)?;
+
+    // Margin lent against multi-collateral is repaid before anything is paid out;
+    // what the settlement can't repay becomes debt against the pledged assets
+    let credit_repaid = if position.margin_credit > 0 {
+        let margin_account = ctx.accounts.margin_account
+            .as_mut()
+            .ok_or(PerpsError::MarginCreditOutstanding)?;
+        margin_account.settle_credit(position.margin_credit, settlement)
+    } else {
+        0
+    };
+    let payout = settlement - credit_repaid;
+
+    market.untrack_position(position)?;
+
+    // Band breaches are recorded here too; reductions stay allowed while reduce-only
+    market.update_price_band(oracle_price, current_time);
+
+    // Every fill feeds the mark price at the index price it settled at; a maker's
+    // acceptable price is only a bound, so it never moves the mark
+    market.update_mark_price(oracle_price, current_time);
 
     // Update market OI
     match position.side {
@@ -159,13 +202,21 @@ pub fn handler(ctx: Context<ClosePosition>) -> Result<()> {
                 .saturating_sub(position.size);
         }
     }
+    ctx.accounts.user_market.remove_open_interest(position.size);
 
     // Add fee to insurance fund (minus referral reward which stays for claiming)
     let insurance_fee = fee.saturating_sub(referral_reward);
     market.insurance_fund = market.insurance_fund.saturating_add(insurance_fee);
+    market.referral_rewards = market.referral_rewards.saturating_add(referral_reward as i64);
+    if is_maker {
+        market.pay_maker_rebate(rebate);
+    } else {
+        market.collect_taker_fee(insurance_fee);
+    }
 
     // Mark position as closed
     position.status = PositionStatus::Closed;
+    position.margin_credit = 0;
     position.realized_pnl = total_pnl;
     position.last_updated_at = current_time;
 
@@ -174,9 +225,22 @@ pub fn handler(ctx: Context<ClosePosition>) -> Result<()> {
         .saturating_add(total_pnl);
     user_account.total_trades = user_account.total_trades
         .saturating_add(1);
+    let user_market = &mut ctx.accounts.user_market;
+    user_market.record_trade(notional, fee);
+    user_market.record_funding(funding_payment);
+    user_market.record_pnl(total_pnl);
+    user_market.record_close(total_pnl);
+    record_competition_result(
+        ctx.accounts.competition.as_deref_mut(),
+        user_account,
+        &market.key(),
+        total_pnl,
+        position.collateral,
+        current_time,
+    )?;
 
     // Transfer settlement to user
-    if settlement > 0 {
+    if payout > 0 {
         let market_key = market.key();
         let vault_bump = ctx.accounts.vault.bump;
         let vault_seeds = &[
@@ -196,16 +260,24 @@ pub fn handler(ctx: Context<ClosePosition>) -> Result<()> {
             cpi_accounts,
             signer,
         );
-        token::transfer(cpi_ctx, settlement)?;
+        token::transfer(cpi_ctx, payout)?;
+        ctx.accounts.vault.record_outflow(payout);
     }
 
     msg!(
-        "Position closed: PnL={}, Funding={}, Fee={}, Settlement={}",
+        "Position closed: PnL={}, Funding={}, Fee={}, Rebate={}, CreditRepaid={}, Settlement={}",
         pnl,
         funding_payment,
         fee,
-        settlement
+        rebate,
+        credit_repaid,
+        payout
     );
 
-    Ok(())
+    let maintenance_margin_ratio = market.position_maintenance_margin(position)?;
+    let mut fill = position.fill(position.key(), oracle_price, position.size, maintenance_margin_ratio)?;
+    fill.fee = fee;
+    fill.rebate = rebate;
+    fill.realized_pnl = total_pnl;
+    Ok(fill)
 }
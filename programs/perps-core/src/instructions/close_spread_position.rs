@@ -0,0 +1,306 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{
+    Market, MarketStatus, PositionStatus, Side, SpreadMarket, SpreadPosition,
+    UserAccount, UserMarketAccount, Vault, is_within_deadline,
+};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CloseSpreadPositionParams {
+    pub acceptable_price_a: u64,    // Worst leg A fill accepted (6 decimals)
+    pub acceptable_price_b: u64,    // Worst leg B fill accepted (6 decimals)
+    pub deadline: i64,              // Unix timestamp after which the close is rejected (0 = none)
+}
+
+#[derive(Accounts)]
+pub struct CloseSpreadPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.owner == owner.key()
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"spread_market", spread_market.leg_a_market.as_ref(), spread_market.leg_b_market.as_ref()],
+        bump = spread_market.bump
+    )]
+    pub spread_market: Box<Account<'info, SpreadMarket>>,
+
+    #[account(
+        mut,
+        address = spread_market.leg_a_market @ PerpsError::InvalidSpreadLeg,
+        constraint = leg_a_market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub leg_a_market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        address = spread_market.leg_b_market @ PerpsError::InvalidSpreadLeg,
+        constraint = leg_b_market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub leg_b_market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        constraint = spread_position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = spread_position.spread_market == spread_market.key(),
+        constraint = spread_position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed
+    )]
+    pub spread_position: Box<Account<'info, SpreadPosition>>,
+
+    // Leg A's vault holds the collateral and pays the settlement
+    #[account(
+        mut,
+        seeds = [b"vault", leg_a_market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", leg_a_market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Leg B's vault settles leg B's PnL, funding and fee
+    #[account(
+        mut,
+        seeds = [b"vault", leg_b_market.key().as_ref()],
+        bump = leg_b_vault.bump
+    )]
+    pub leg_b_vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", leg_b_market.key().as_ref()],
+        bump
+    )]
+    pub leg_b_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == owner.key(),
+        constraint = user_token_account.mint == leg_a_market.collateral_mint
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = leg_a_price_feed.key() == leg_a_market.pyth_price_feed)]
+    pub leg_a_price_feed: AccountInfo<'info>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = leg_b_price_feed.key() == leg_b_market.pyth_price_feed)]
+    pub leg_b_price_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    // Count the owner's open interest in each leg, created if they have none yet
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", leg_a_market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub leg_a_user_market: Box<Account<'info, UserMarketAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", leg_b_market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub leg_b_user_market: Box<Account<'info, UserMarketAccount>>,
+}
+
+pub fn handler(ctx: Context<CloseSpreadPosition>, params: CloseSpreadPositionParams) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        is_within_deadline(params.deadline, current_time),
+        PerpsError::DeadlineExceeded
+    );
+    require!(
+        !ctx.accounts.leg_a_market.is_expired(current_time)
+            && !ctx.accounts.leg_b_market.is_expired(current_time),
+        PerpsError::MarketExpired
+    );
+    require!(
+        ctx.accounts.leg_a_market.is_on_tick(params.acceptable_price_a)
+            && ctx.accounts.leg_b_market.is_on_tick(params.acceptable_price_b),
+        PerpsError::PriceNotOnTick
+    );
+
+    // Remaining accounts hold leg A's index feeds, then leg B's
+    let (_, leg_b_feeds) = ctx.accounts.leg_a_market.split_index_feeds(ctx.remaining_accounts)?;
+    let price_a = ctx.accounts.leg_a_market.index_price(&ctx.accounts.leg_a_price_feed, ctx.remaining_accounts, current_time)?;
+    let price_b = ctx.accounts.leg_b_market.index_price(&ctx.accounts.leg_b_price_feed, leg_b_feeds, current_time)?;
+
+    let spread_position = &mut ctx.accounts.spread_position;
+    let leg_a = spread_position.leg_a;
+    let leg_b = spread_position.leg_b;
+
+    // Closing trades each leg against its side
+    require!(
+        leg_a.side.opposite().is_acceptable_price(price_a, params.acceptable_price_a)
+            && leg_b.side.opposite().is_acceptable_price(price_b, params.acceptable_price_b),
+        PerpsError::SlippageExceeded
+    );
+
+    let leg_a_market = &mut ctx.accounts.leg_a_market;
+    let leg_b_market = &mut ctx.accounts.leg_b_market;
+
+    let pnl = spread_position.unrealized_pnl(price_a, price_b)?;
+    let funding_payment = spread_position.funding_payment(leg_a_market.funding_rate, leg_b_market.funding_rate)?;
+
+    // Each leg pays its market's taker fee into that market's insurance fund
+    let (fee_a, _) = leg_a_market.fill_fee(leg_a.notional_value()?, false)?;
+    let (fee_b, _) = leg_b_market.fill_fee(leg_b.notional_value()?, false)?;
+    let fee = fee_a.saturating_add(fee_b);
+
+    let leg_a_result = leg_a.unrealized_pnl(price_a)? + leg_a.funding_payment(leg_a_market.funding_rate)? - fee_a as i64;
+    let leg_b_result = leg_b.unrealized_pnl(price_b)? + leg_b.funding_payment(leg_b_market.funding_rate)? - fee_b as i64;
+    let leg_b_transfer = spread_position.leg_b_transfer(leg_a_result, leg_b_result);
+
+    let total_pnl = pnl + funding_payment - fee as i64;
+    let payout = perps_math::equity(spread_position.collateral, total_pnl).map_err(PerpsError::from)?;
+
+    leg_a_market.untrack_spread_leg(&leg_a, spread_position.collateral)?;
+    leg_b_market.untrack_spread_leg(&leg_b, 0)?;
+
+    for (market, leg, price) in [(&mut *leg_a_market, &leg_a, price_a), (&mut *leg_b_market, &leg_b, price_b)] {
+        // Band breaches are recorded here too; closes stay allowed while reduce-only
+        market.update_price_band(price, current_time);
+        market.update_mark_price(price, current_time);
+        match leg.side {
+            Side::Long => {
+                market.long_open_interest = market.long_open_interest.saturating_sub(leg.size);
+            }
+            Side::Short => {
+                market.short_open_interest = market.short_open_interest.saturating_sub(leg.size);
+            }
+        }
+    }
+    let owner = ctx.accounts.owner.key();
+    for (user_market, market_key, bump, leg, leg_fee) in [
+        (&mut ctx.accounts.leg_a_user_market, leg_a_market.key(), *ctx.bumps.get("leg_a_user_market").unwrap(), &leg_a, fee_a),
+        (&mut ctx.accounts.leg_b_user_market, leg_b_market.key(), *ctx.bumps.get("leg_b_user_market").unwrap(), &leg_b, fee_b),
+    ] {
+        user_market.initialize_if_new(owner, market_key, bump);
+        user_market.remove_open_interest(leg.size);
+        user_market.record_trade(leg.notional_value()?, leg_fee);
+    }
+
+    for (market, leg_fee) in [(&mut *leg_a_market, fee_a), (&mut *leg_b_market, fee_b)] {
+        market.insurance_fund = market.insurance_fund.saturating_add(leg_fee);
+        market.collect_taker_fee(leg_fee);
+    }
+
+    spread_position.status = PositionStatus::Closed;
+    spread_position.realized_pnl = total_pnl;
+    spread_position.last_updated_at = current_time;
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.realized_pnl = user_account.realized_pnl.saturating_add(total_pnl);
+    user_account.total_trades = user_account.total_trades.saturating_add(1);
+
+    let spread_market = &mut ctx.accounts.spread_market;
+    spread_market.open_spreads = spread_market.open_spreads.saturating_sub(1);
+
+    move_leg_b_settlement(
+        leg_b_transfer,
+        &mut ctx.accounts.vault,
+        &ctx.accounts.vault_token_account,
+        &mut ctx.accounts.leg_b_vault,
+        &ctx.accounts.leg_b_vault_token_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    if payout > 0 {
+        let market_key = leg_a_market.key();
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, payout)?;
+        ctx.accounts.vault.record_outflow(payout);
+    }
+
+    msg!(
+        "Spread closed: PnL={}, Funding={}, Fee={}, Leg B transfer={}, Settlement={}",
+        pnl,
+        funding_payment,
+        fee,
+        leg_b_transfer,
+        payout
+    );
+
+    Ok(())
+}
+
+/// Settle leg B in its own vault: move `amount` from leg B's vault to leg A's when
+/// positive, and from leg A's to leg B's when negative
+pub fn move_leg_b_settlement<'info>(
+    amount: i64,
+    leg_a_vault: &mut Account<'info, Vault>,
+    leg_a_vault_token_account: &Account<'info, TokenAccount>,
+    leg_b_vault: &mut Account<'info, Vault>,
+    leg_b_vault_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let (from_vault, from_token_account, to_vault, to_token_account) = if amount > 0 {
+        (leg_b_vault, leg_b_vault_token_account, leg_a_vault, leg_a_vault_token_account)
+    } else {
+        (leg_a_vault, leg_a_vault_token_account, leg_b_vault, leg_b_vault_token_account)
+    };
+    let amount = amount.unsigned_abs();
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let market_key = from_vault.market;
+    let vault_seeds = &[
+        b"vault".as_ref(),
+        market_key.as_ref(),
+        &[from_vault.bump],
+    ];
+    let signer = &[&vault_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: from_token_account.to_account_info(),
+        to: to_token_account.to_account_info(),
+        authority: from_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)?;
+    from_vault.record_outflow(amount);
+    to_vault.record_inflow(amount);
+    Ok(())
+}
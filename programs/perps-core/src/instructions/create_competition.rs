@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::{Competition, CompetitionMetric, MAX_COMPETITION_MARKETS, MAX_PRIZE_RANKS};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CompetitionParams {
+    pub id: u64,                                        // Distinguishes the authority's competitions
+    pub start_time: i64,
+    pub end_time: i64,
+    pub metric: CompetitionMetric,
+    pub markets: [Pubkey; MAX_COMPETITION_MARKETS],     // Default pubkey = unused
+    pub payout_bps: [u16; MAX_PRIZE_RANKS],             // Prize pool share per rank, best first, 0 = unused
+}
+
+impl CompetitionParams {
+    pub fn validate(&self, current_time: i64) -> Result<()> {
+        require!(
+            self.end_time > self.start_time && self.end_time > current_time,
+            PerpsError::InvalidCompetitionParams
+        );
+        require!(
+            self.markets.iter().any(|market| *market != Pubkey::default()),
+            PerpsError::InvalidCompetitionParams
+        );
+        let total_bps: u32 = self.payout_bps.iter().map(|&bps| bps as u32).sum();
+        require!(
+            self.payout_bps[0] > 0 && total_bps <= 10000,
+            PerpsError::InvalidCompetitionParams
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(params: CompetitionParams)]
+pub struct CreateCompetition<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Competition::LEN,
+        seeds = [b"competition", authority.key().as_ref(), &params.id.to_le_bytes()],
+        bump
+    )]
+    pub competition: Box<Account<'info, Competition>>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = prize_mint,
+        token::authority = competition,
+        seeds = [b"competition_escrow", competition.key().as_ref()],
+        bump
+    )]
+    pub prize_escrow: Account<'info, TokenAccount>,
+
+    pub prize_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<CreateCompetition>, params: CompetitionParams) -> Result<()> {
+    params.validate(Clock::get()?.unix_timestamp)?;
+
+    let competition = &mut ctx.accounts.competition;
+    competition.authority = ctx.accounts.authority.key();
+    competition.prize_mint = ctx.accounts.prize_mint.key();
+    competition.id = params.id;
+    competition.start_time = params.start_time;
+    competition.end_time = params.end_time;
+    competition.metric = params.metric;
+
+    let mut num_markets = 0;
+    for market in params.markets.iter().filter(|market| **market != Pubkey::default()) {
+        competition.markets[num_markets] = *market;
+        num_markets += 1;
+    }
+    competition.num_markets = num_markets as u8;
+
+    competition.payout_bps = params.payout_bps;
+    competition.prize_pool = 0;
+    competition.num_entries = 0;
+    competition.is_finalized = false;
+    competition.bump = *ctx.bumps.get("competition").unwrap();
+    competition.version = Competition::VERSION;
+
+    msg!(
+        "Competition {} created: {} to {} on {} markets",
+        params.id,
+        params.start_time,
+        params.end_time,
+        num_markets
+    );
+    Ok(())
+}
@@ -48,6 +48,7 @@ pub fn handler(ctx: Context<CreateReferralCode>, params: CreateReferralCodeParam
     referral_code.created_at = clock.unix_timestamp;
     referral_code.is_active = true;
     referral_code.bump = *ctx.bumps.get("referral_code").unwrap();
+    referral_code.version = ReferralCode::VERSION;
 
     msg!(
         "Referral code created: {} by {}",
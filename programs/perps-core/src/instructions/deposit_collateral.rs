@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::state::{Market, Vault, UserAccount};
+use crate::state::{CollateralRegistry, Market, MarginAccount, Vault, UserAccount};
+use crate::errors::PerpsError;
 
+/// Deposits the market's collateral mint into the user's free balance, or pledges
+/// any other registered mint to their margin account for this market
 #[derive(Accounts)]
 pub struct DepositCollateral<'info> {
     #[account(mut)]
@@ -16,7 +19,8 @@ pub struct DepositCollateral<'info> {
     pub user_account: Account<'info, UserAccount>,
 
     #[account(
-        seeds = [b"market", market.collateral_mint.as_ref()],
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
         bump = market.bump
     )]
     pub market: Account<'info, Market>,
@@ -37,15 +41,41 @@ pub struct DepositCollateral<'info> {
 
     #[account(
         mut,
-        constraint = user_token_account.owner == owner.key(),
-        constraint = user_token_account.mint == market.collateral_mint
+        constraint = user_token_account.owner == owner.key()
     )]
     pub user_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+
+    // Required when depositing a mint other than the market's collateral mint
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump
+    )]
+    pub collateral_registry: Option<Account<'info, CollateralRegistry>>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), owner.key().as_ref()],
+        bump = margin_account.bump,
+        constraint = margin_account.owner == owner.key()
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", market.key().as_ref(), user_token_account.mint.as_ref()],
+        bump
+    )]
+    pub asset_vault_token_account: Option<Account<'info, TokenAccount>>,
 }
 
 pub fn handler(ctx: Context<DepositCollateral>, amount: u64) -> Result<()> {
+    let mint = ctx.accounts.user_token_account.mint;
+    if mint != ctx.accounts.market.collateral_mint {
+        return deposit_asset(ctx, mint, amount);
+    }
+
     // Transfer tokens from user to vault
     let cpi_accounts = Transfer {
         from: ctx.accounts.user_token_account.to_account_info(),
@@ -64,8 +94,54 @@ pub fn handler(ctx: Context<DepositCollateral>, amount: u64) -> Result<()> {
 
     // Update vault
     let vault = &mut ctx.accounts.vault;
-    vault.total_deposits = vault.total_deposits.checked_add(amount).unwrap();
+    vault.record_inflow(amount);
+    ctx.accounts.market.credit_user_collateral(amount);
 
     msg!("Deposited {} collateral for user {}", amount, ctx.accounts.owner.key());
     Ok(())
 }
+
+/// Pledged assets are held per market, where they back margin credit, so they are
+/// kept in the margin account rather than the cross-market free balance
+fn deposit_asset(ctx: Context<DepositCollateral>, mint: Pubkey, amount: u64) -> Result<()> {
+    let registry = ctx.accounts.collateral_registry
+        .as_ref()
+        .ok_or(PerpsError::CollateralAssetAccountsRequired)?;
+    let asset_vault_token_account = ctx.accounts.asset_vault_token_account
+        .as_ref()
+        .ok_or(PerpsError::CollateralAssetAccountsRequired)?;
+
+    let index = registry
+        .find_asset(&mint)
+        .ok_or(PerpsError::CollateralAssetNotFound)?;
+    require!(
+        registry.assets[index].is_active,
+        PerpsError::CollateralAssetInactive
+    );
+
+    // Transfer tokens from user to the asset vault
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.user_token_account.to_account_info(),
+        to: asset_vault_token_account.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    // Update margin account
+    let margin_account = ctx.accounts.margin_account
+        .as_mut()
+        .ok_or(PerpsError::CollateralAssetAccountsRequired)?;
+    margin_account.balances[index] = margin_account.balances[index]
+        .checked_add(amount)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    msg!(
+        "Deposited {} of collateral asset {} for user {}",
+        amount,
+        mint,
+        ctx.accounts.owner.key()
+    );
+    Ok(())
+}
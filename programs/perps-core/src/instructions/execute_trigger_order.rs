@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+use crate::state::{
+    Competition, CrankRewards, MarginAccount, Market, MarketStatus, Position, PositionStatus,
+    ReferralCode, Side, TriggerOrder, UserAccount, UserReferral, load_initialized_user_market,
+    record_competition_result,
+};
+use crate::errors::PerpsError;
+
+/// Permissionless: closes a position whose take-profit or stop-loss the index price has
+/// crossed, as a taker at the index price. The settlement is credited to the owner's
+/// free collateral and the order's rent refunded to them.
+#[derive(Accounts)]
+pub struct ExecuteTriggerOrder<'info> {
+    pub keeper: Signer<'info>,
+
+    /// CHECK: Rent refund destination, must be the order's owner
+    #[account(mut, constraint = owner.key() == trigger_order.owner)]
+    pub owner: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.owner == owner.key()
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"trigger_order", position.key().as_ref(), &[trigger_order.kind as u8]],
+        bump = trigger_order.bump,
+        close = owner
+    )]
+    pub trigger_order: Account<'info, TriggerOrder>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    // Required when the owner has a referral, as on close_position
+    #[account(
+        mut,
+        seeds = [b"user_referral", owner.key().as_ref()],
+        bump = user_referral.bump,
+    )]
+    pub user_referral: Option<Account<'info, UserReferral>>,
+
+    #[account(
+        mut,
+        seeds = [b"referral_code", referral_code.code.as_ref()],
+        bump = referral_code.bump,
+    )]
+    pub referral_code: Option<Account<'info, ReferralCode>>,
+
+    // Required when the position drew margin credit against multi-collateral
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), owner.key().as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    /// CHECK: The owner's user market PDA, counting their open interest. Only read if
+    /// initialized, since the keeper can't be made to create it for the owner.
+    #[account(
+        mut,
+        seeds = [b"user_market", market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_market: UncheckedAccount<'info>,
+
+    // Required while the owner is entered in a running competition, recording the close
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+
+    // Optional crank reward, paid into the keeper's free collateral
+    #[account(
+        mut,
+        seeds = [b"crank_rewards", market.key().as_ref()],
+        bump = crank_rewards.bump,
+    )]
+    pub crank_rewards: Option<Account<'info, CrankRewards>>,
+
+    // Owners running their own orders earn nothing; their user account is already loaded
+    #[account(
+        mut,
+        seeds = [b"user", keeper.key().as_ref()],
+        bump = keeper_user_account.bump,
+        constraint = keeper.key() != owner.key() @ PerpsError::Unauthorized,
+    )]
+    pub keeper_user_account: Option<Account<'info, UserAccount>>,
+}
+
+pub fn handler(ctx: Context<ExecuteTriggerOrder>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let position = &mut ctx.accounts.position;
+    let user_account = &mut ctx.accounts.user_account;
+    let trigger_order = &ctx.accounts.trigger_order;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+    require!(
+        trigger_order.is_triggered(position.side, oracle_price),
+        PerpsError::TriggerNotReached
+    );
+
+    // Closing trades against the position's side
+    require!(
+        position.side.opposite().is_acceptable_price(oracle_price, trigger_order.acceptable_price),
+        PerpsError::SlippageExceeded
+    );
+
+    let pnl = position.unrealized_pnl(oracle_price)?;
+    let funding_payment = position.funding_payment(position.size, market.funding_rate)?;
+
+    let notional = position.notional_value()?;
+    let (base_fee, _) = market.fill_fee(notional, false)?;
+    let (fee, referral_reward) = if let (Some(user_referral), Some(referral_code)) =
+        (&mut ctx.accounts.user_referral, &mut ctx.accounts.referral_code)
+    {
+        let referral_code_key = referral_code.key();
+        user_referral.process_trade_fee(referral_code_key, referral_code, base_fee, notional)?
+    } else {
+        (base_fee, 0)
+    };
+
+    let total_pnl = pnl + funding_payment - fee as i64;
+    let settlement = perps_math::equity(position.collateral, total_pnl).map_err(PerpsError::from)?;
+
+    // Margin lent against multi-collateral is repaid before anything is credited;
+    // what the settlement can't repay becomes debt against the pledged assets
+    let credit_repaid = if position.margin_credit > 0 {
+        let margin_account = ctx.accounts.margin_account
+            .as_mut()
+            .ok_or(PerpsError::MarginCreditOutstanding)?;
+        margin_account.settle_credit(position.margin_credit, settlement)
+    } else {
+        0
+    };
+    let payout = settlement - credit_repaid;
+
+    market.untrack_position(position)?;
+    market.update_price_band(oracle_price, current_time);
+    market.update_mark_price(oracle_price, current_time);
+
+    match position.side {
+        Side::Long => {
+            market.long_open_interest = market.long_open_interest
+                .saturating_sub(position.size);
+        }
+        Side::Short => {
+            market.short_open_interest = market.short_open_interest
+                .saturating_sub(position.size);
+        }
+    }
+
+    let insurance_fee = fee.saturating_sub(referral_reward);
+    market.insurance_fund = market.insurance_fund.saturating_add(insurance_fee);
+    market.referral_rewards = market.referral_rewards.saturating_add(referral_reward as i64);
+    market.collect_taker_fee(insurance_fee);
+
+    position.status = PositionStatus::Closed;
+    position.margin_credit = 0;
+    position.realized_pnl = total_pnl;
+    position.last_updated_at = current_time;
+
+    // The settlement stays in the vault as the owner's free collateral
+    user_account.collateral_balance = user_account.collateral_balance.saturating_add(payout);
+    market.credit_user_collateral(payout);
+
+    user_account.realized_pnl = user_account.realized_pnl
+        .saturating_add(total_pnl);
+    user_account.total_trades = user_account.total_trades
+        .saturating_add(1);
+    if let Some(mut user_market) = load_initialized_user_market(&ctx.accounts.user_market)? {
+        user_market.remove_open_interest(position.size);
+        user_market.record_trade(notional, fee);
+        user_market.record_funding(funding_payment);
+        user_market.record_pnl(total_pnl);
+        user_market.record_close(total_pnl);
+        user_market.exit(&crate::ID)?;
+    }
+    record_competition_result(
+        ctx.accounts.competition.as_deref_mut(),
+        user_account,
+        &market.key(),
+        total_pnl,
+        position.collateral,
+        current_time,
+    )?;
+
+    if let (Some(crank_rewards), Some(keeper_user_account)) =
+        (ctx.accounts.crank_rewards.as_deref_mut(), ctx.accounts.keeper_user_account.as_deref_mut())
+    {
+        crank_rewards.pay(market, keeper_user_account, current_time);
+    }
+
+    msg!(
+        "Trigger order executed: {:?} on {} @ {}, PnL={}, Funding={}, Fee={}, CreditRepaid={}, Settlement={}",
+        trigger_order.kind,
+        position.key(),
+        oracle_price,
+        pnl,
+        funding_payment,
+        fee,
+        credit_repaid,
+        payout
+    );
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::{Bps, Rounding};
+use crate::state::Competition;
+use crate::errors::PerpsError;
+
+/// Rank participants once the competition has ended and assign each rank its share
+/// of the prize pool. Shares without a ranked participant go back to the authority.
+#[derive(Accounts)]
+pub struct FinalizeCompetition<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+        constraint = !competition.is_finalized @ PerpsError::CompetitionFinalized
+    )]
+    pub competition: Box<Account<'info, Competition>>,
+
+    #[account(
+        mut,
+        seeds = [b"competition_escrow", competition.key().as_ref()],
+        bump
+    )]
+    pub prize_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = authority_token_account.owner == competition.authority @ PerpsError::Unauthorized,
+        constraint = authority_token_account.mint == competition.prize_mint
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<FinalizeCompetition>) -> Result<()> {
+    let competition = &mut ctx.accounts.competition;
+    require!(
+        Clock::get()?.unix_timestamp > competition.end_time,
+        PerpsError::CompetitionNotEnded
+    );
+
+    let ranking = competition.ranking();
+    let payout_bps = competition.payout_bps;
+    let mut allocated: u64 = 0;
+    for (&index, &bps) in ranking.iter().zip(payout_bps.iter()) {
+        let prize = Bps::from(bps)
+            .of(competition.prize_pool, Rounding::Down)
+            .map_err(PerpsError::from)?;
+        competition.entries[index].prize = prize;
+        allocated += prize;
+    }
+    competition.is_finalized = true;
+
+    let unallocated = competition.prize_pool - allocated;
+    if unallocated > 0 {
+        let authority = competition.authority;
+        let id_bytes = competition.id.to_le_bytes();
+        let competition_seeds = &[
+            b"competition".as_ref(),
+            authority.as_ref(),
+            id_bytes.as_ref(),
+            &[competition.bump],
+        ];
+        let signer = &[&competition_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.prize_escrow.to_account_info(),
+            to: ctx.accounts.authority_token_account.to_account_info(),
+            authority: competition.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, unallocated)?;
+    }
+
+    msg!(
+        "Competition {} finalized: {} ranked, allocated={}, returned={}",
+        competition.id,
+        ranking.len(),
+        allocated,
+        unallocated
+    );
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use crate::state::{CrankRewards, FuturesSettlement, Market, MarketType, UserAccount};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct FinalizeSettlement<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.market_type == MarketType::DatedFuture @ PerpsError::NotDatedFuture
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"futures_settlement", market.key().as_ref()],
+        bump = futures_settlement.bump,
+        constraint = !futures_settlement.is_finalized @ PerpsError::SettlementAlreadyFinalized
+    )]
+    pub futures_settlement: Account<'info, FuturesSettlement>,
+
+    /// CHECK: Pyth price feed, only read if too little of the window was sampled
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    // Optional crank reward, paid into the keeper's free collateral
+    #[account(
+        mut,
+        seeds = [b"crank_rewards", market.key().as_ref()],
+        bump = crank_rewards.bump,
+    )]
+    pub crank_rewards: Option<Account<'info, CrankRewards>>,
+
+    #[account(
+        mut,
+        seeds = [b"user", keeper.key().as_ref()],
+        bump = keeper_user_account.bump,
+    )]
+    pub keeper_user_account: Option<Account<'info, UserAccount>>,
+}
+
+pub fn handler(ctx: Context<FinalizeSettlement>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let settlement = &mut ctx.accounts.futures_settlement;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(market.is_expired(current_time), PerpsError::MarketNotExpired);
+
+    // Settle at the TWAP of the final window; if too little of it was sampled only
+    // the market authority may settle, at spot
+    let settlement_price = match settlement.twap() {
+        Some(twap) => twap,
+        None => {
+            require!(
+                ctx.accounts.keeper.key() == market.authority,
+                PerpsError::InsufficientSettlementSamples
+            );
+            market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?
+        }
+    };
+
+    settlement.settlement_price = settlement_price;
+    settlement.is_finalized = true;
+
+    if let (Some(crank_rewards), Some(keeper_user_account)) =
+        (ctx.accounts.crank_rewards.as_deref_mut(), ctx.accounts.keeper_user_account.as_deref_mut())
+    {
+        crank_rewards.pay(market, keeper_user_account, current_time);
+    }
+
+    msg!(
+        "Futures settled: market={}, price={}, samples={}",
+        market.key(),
+        settlement_price,
+        settlement.num_samples
+    );
+
+    Ok(())
+}
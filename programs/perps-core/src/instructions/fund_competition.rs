@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::Competition;
+use crate::errors::PerpsError;
+
+/// Add to a competition's prize pool; anyone may sponsor it until it is finalized
+#[derive(Accounts)]
+pub struct FundCompetition<'info> {
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+        constraint = !competition.is_finalized @ PerpsError::CompetitionFinalized
+    )]
+    pub competition: Box<Account<'info, Competition>>,
+
+    #[account(
+        mut,
+        seeds = [b"competition_escrow", competition.key().as_ref()],
+        bump
+    )]
+    pub prize_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = funder_token_account.owner == funder.key(),
+        constraint = funder_token_account.mint == competition.prize_mint
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<FundCompetition>, amount: u64) -> Result<()> {
+    require!(amount > 0, PerpsError::InvalidCompetitionParams);
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.funder_token_account.to_account_info(),
+        to: ctx.accounts.prize_escrow.to_account_info(),
+        authority: ctx.accounts.funder.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    let competition = &mut ctx.accounts.competition;
+    competition.prize_pool = competition.prize_pool
+        .checked_add(amount)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    msg!("Competition {} funded with {}, pool={}", competition.id, amount, competition.prize_pool);
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use perps_math::{Price, Rounding, Size};
+use crate::state::{CollateralRegistry, MarginAccount, TradingSchedule, Market, Position, PositionFill, UserAccount, Side, PositionStatus, MarketStatus, UserMarketAccount, unbacked_credit, is_within_deadline};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct IncreasePositionParams {
+    pub size: u64,              // Additional size in base units
+    pub acceptable_price: u64,  // Worst fill accepted (6 decimals)
+    pub deadline: i64,          // Unix timestamp after which the increase is rejected (0 = none)
+}
+
+#[derive(Accounts)]
+pub struct IncreasePosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Funds the user market account's rent, so a program-derived owner holding data can trade
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.owner == owner.key()
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = !market.is_paused @ PerpsError::MarketPaused
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+
+    // Optional multi-collateral accounts - needed when free balance is short.
+    // Price feeds for the user's collateral assets follow any index feeds in remaining accounts.
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump,
+    )]
+    pub collateral_registry: Option<Account<'info, CollateralRegistry>>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), owner.key().as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    // Required when the market follows a trading schedule
+    #[account(
+        seeds = [b"trading_schedule", market.key().as_ref()],
+        bump = trading_schedule.bump,
+    )]
+    pub trading_schedule: Option<Account<'info, TradingSchedule>>,
+
+    // Counts the owner's open interest in this market, created if they have none yet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_market: Account<'info, UserMarketAccount>,
+}
+
+pub fn handler(ctx: Context<IncreasePosition>, params: IncreasePositionParams) -> Result<PositionFill> {
+    let market = &mut ctx.accounts.market;
+    let user_account = &mut ctx.accounts.user_account;
+    let position = &mut ctx.accounts.position;
+    ctx.accounts.user_market.initialize_if_new(
+        ctx.accounts.owner.key(),
+        market.key(),
+        *ctx.bumps.get("user_market").unwrap(),
+    );
+
+    require!(params.size > 0, PerpsError::PositionTooSmall);
+
+    // Sizes trade in whole lots and limits sit on ticks
+    require!(market.is_lot_multiple(params.size), PerpsError::SizeNotLotMultiple);
+    require!(market.is_on_tick(params.acceptable_price), PerpsError::PriceNotOnTick);
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        is_within_deadline(params.deadline, current_time),
+        PerpsError::DeadlineExceeded
+    );
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+
+    // Outside trading hours the market is reduce-only
+    if market.has_trading_schedule {
+        let schedule = ctx.accounts.trading_schedule
+            .as_ref()
+            .ok_or(PerpsError::TradingScheduleRequired)?;
+        require!(schedule.is_open(current_time), PerpsError::MarketClosed);
+    }
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    // Opens halt once the price leaves the band around the reference price; the
+    // breach is latched by the next crank, reduction or close that succeeds
+    require!(market.within_price_band(oracle_price), PerpsError::PriceBandBreached);
+    market.update_price_band(oracle_price, current_time);
+
+    require!(
+        position.side.is_acceptable_price(oracle_price, params.acceptable_price),
+        PerpsError::SlippageExceeded
+    );
+
+    // Collateral for the added size, at the initial margin of the risk tier the
+    // increased position lands in
+    let existing_notional = position.notional_value()?;
+    let (added_notional, required_collateral) =
+        market.added_initial_margin(params.size, oracle_price, existing_notional)?;
+    require!(
+        market.within_risk_tiers(existing_notional.saturating_add(added_notional)),
+        PerpsError::RiskTierExceeded
+    );
+
+    // Any shortfall in free balance is lent against multi-collateral at haircut value
+    let margin_credit = required_collateral.saturating_sub(user_account.collateral_balance);
+    if margin_credit > 0 {
+        let (Some(registry), Some(margin_account)) =
+            (&ctx.accounts.collateral_registry, &mut ctx.accounts.margin_account)
+        else {
+            return Err(PerpsError::InsufficientCollateral.into());
+        };
+        let (_, asset_feeds) = market.split_index_feeds(ctx.remaining_accounts)?;
+        margin_account.draw_credit(registry, asset_feeds, margin_credit, current_time)?;
+    }
+
+    // Check OI caps
+    match position.side {
+        Side::Long => require!(
+            market.can_increase_long_oi(params.size),
+            PerpsError::OpenInterestCapExceeded
+        ),
+        Side::Short => require!(
+            market.can_increase_short_oi(params.size),
+            PerpsError::OpenInterestCapExceeded
+        ),
+    }
+    market.apply_open_interest_caps(
+        position.side,
+        params.size,
+        oracle_price,
+        &mut ctx.accounts.user_market,
+    )?;
+
+    market.untrack_position(position)?;
+
+    // Settle funding accrued on the existing size before the size changes
+    let funding_payment = position.funding_payment(position.size, market.funding_rate)?;
+    position.collateral = perps_math::equity(position.collateral, funding_payment).map_err(PerpsError::from)?;
+    position.realized_pnl = position.realized_pnl.saturating_add(funding_payment);
+    position.last_funding_payment = market.funding_rate;
+
+    // Deduct collateral from user
+    user_account.collateral_balance = user_account.collateral_balance
+        .checked_sub(required_collateral - margin_credit)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    // New entry price is the size-weighted average of the old entry and the fill
+    let new_size = position.size
+        .checked_add(params.size)
+        .ok_or(PerpsError::MathOverflow)?;
+    // Rounded against the holder: up for longs, down for shorts
+    let rounding = match position.side {
+        Side::Long => Rounding::Up,
+        Side::Short => Rounding::Down,
+    };
+    let entry_price = Price(position.entry_price)
+        .weighted_average(Size(position.size), Price(oracle_price), Size(params.size), rounding)
+        .map_err(PerpsError::from)?
+        .0;
+
+    position.size = new_size;
+    position.entry_price = entry_price;
+    position.collateral = position.collateral
+        .checked_add(required_collateral)
+        .ok_or(PerpsError::MathOverflow)?;
+    position.margin_credit = position.margin_credit
+        .checked_add(margin_credit)
+        .ok_or(PerpsError::MathOverflow)?;
+    position.last_updated_at = current_time;
+
+    // Growing into a higher tier raises the maintenance margin on the whole position,
+    // and lent margin the pledged collateral no longer backs doesn't count towards it
+    let (_, asset_feeds) = market.split_index_feeds(ctx.remaining_accounts)?;
+    let unbacked = unbacked_credit(
+        position,
+        ctx.accounts.collateral_registry.as_deref(),
+        ctx.accounts.margin_account.as_deref(),
+        asset_feeds,
+        current_time,
+    )?;
+    require!(
+        !position.is_backed_liquidatable(oracle_price, market.position_maintenance_margin(position)?, unbacked)?,
+        PerpsError::InsufficientCollateral
+    );
+
+    market.track_position(position)?;
+    market.debit_user_collateral(required_collateral - margin_credit);
+
+    // Every fill feeds the mark price
+    market.update_mark_price(oracle_price, current_time);
+
+    // Update market OI
+    match position.side {
+        Side::Long => {
+            market.long_open_interest = market.long_open_interest
+                .checked_add(params.size)
+                .ok_or(PerpsError::MathOverflow)?;
+        }
+        Side::Short => {
+            market.short_open_interest = market.short_open_interest
+                .checked_add(params.size)
+                .ok_or(PerpsError::MathOverflow)?;
+        }
+    }
+
+    market.total_trades = market.total_trades
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    user_account.total_trades = user_account.total_trades
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+    let user_market = &mut ctx.accounts.user_market;
+    user_market.record_trade(added_notional, 0);
+    user_market.record_funding(funding_payment);
+    user_market.record_pnl(funding_payment);
+
+    msg!(
+        "Position increased: {} +{} @ {}, new size={}, entry={}",
+        if position.side == Side::Long { "LONG" } else { "SHORT" },
+        params.size,
+        oracle_price,
+        new_size,
+        entry_price
+    );
+
+    let maintenance_margin_ratio = market.position_maintenance_margin(position)?;
+    let mut fill = position.fill(position.key(), oracle_price, params.size, maintenance_margin_ratio)?;
+    fill.realized_pnl = funding_payment;
+    Ok(fill)
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralRegistry, Market};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct InitializeCollateralRegistry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = CollateralRegistry::LEN,
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump
+    )]
+    pub collateral_registry: Account<'info, CollateralRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeCollateralRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.collateral_registry;
+    registry.market = ctx.accounts.market.key();
+    registry.authority = ctx.accounts.authority.key();
+    registry.num_assets = 0;
+    registry.bump = *ctx.bumps.get("collateral_registry").unwrap();
+    registry.version = CollateralRegistry::VERSION;
+
+    msg!("Collateral registry initialized for market {}", registry.market);
+    Ok(())
+}
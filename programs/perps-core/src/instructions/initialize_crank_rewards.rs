@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::{CrankRewards, Market};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CrankRewardsParams {
+    pub reward_per_crank: u64,        // Settlement units (6 decimals), 0 = disabled
+    pub max_rewards_per_window: u64,  // Cap per hour across all cranks
+}
+
+#[derive(Accounts)]
+pub struct InitializeCrankRewards<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = CrankRewards::LEN,
+        seeds = [b"crank_rewards", market.key().as_ref()],
+        bump
+    )]
+    pub crank_rewards: Account<'info, CrankRewards>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeCrankRewards>, params: CrankRewardsParams) -> Result<()> {
+    let crank_rewards = &mut ctx.accounts.crank_rewards;
+    crank_rewards.market = ctx.accounts.market.key();
+    crank_rewards.reward_per_crank = params.reward_per_crank;
+    crank_rewards.max_rewards_per_window = params.max_rewards_per_window;
+    crank_rewards.window_start = Clock::get()?.unix_timestamp;
+    crank_rewards.paid_in_window = 0;
+    crank_rewards.total_paid = 0;
+    crank_rewards.bump = *ctx.bumps.get("crank_rewards").unwrap();
+    crank_rewards.version = CrankRewards::VERSION;
+
+    msg!(
+        "Crank rewards initialized: {} per crank, {} per window",
+        params.reward_per_crank,
+        params.max_rewards_per_window
+    );
+    Ok(())
+}
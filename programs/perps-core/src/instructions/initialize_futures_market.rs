@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::{FuturesSettlement, Market, MarketType, Vault};
+use crate::errors::PerpsError;
+use crate::instructions::initialize_market::InitializeMarketParams;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitializeFuturesMarketParams {
+    pub market: InitializeMarketParams,
+    pub expiry_timestamp: i64,          // Unix timestamp at which trading stops
+    pub settlement_window: i64,         // TWAP window before expiry (seconds)
+}
+
+#[derive(Accounts)]
+#[instruction(params: InitializeFuturesMarketParams)]
+pub struct InitializeFuturesMarket<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Market::LEN,
+        seeds = [b"market", collateral_mint.key().as_ref(), params.expiry_timestamp.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = FuturesSettlement::LEN,
+        seeds = [b"futures_settlement", market.key().as_ref()],
+        bump
+    )]
+    pub futures_settlement: Account<'info, FuturesSettlement>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Vault::LEN,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = collateral_mint,
+        token::authority = vault,
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub collateral_mint: Account<'info, Mint>,
+
+    /// CHECK: Pyth price feed account, validated by Pyth SDK
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeFuturesMarket>,
+    params: InitializeFuturesMarketParams,
+) -> Result<()> {
+    params.market.validate()?;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(params.expiry_timestamp > current_time, PerpsError::InvalidMarketConfig);
+    require!(
+        params.settlement_window > 0
+            && params.settlement_window < params.expiry_timestamp - current_time,
+        PerpsError::InvalidMarketConfig
+    );
+
+    let market = &mut ctx.accounts.market;
+    market.authority = ctx.accounts.authority.key();
+    market.collateral_mint = ctx.accounts.collateral_mint.key();
+    market.vault = ctx.accounts.vault.key();
+    market.pyth_price_feed = ctx.accounts.pyth_price_feed.key();
+    params.market.apply(market, current_time);
+    market.market_type = MarketType::DatedFuture;
+    market.expiry_timestamp = params.expiry_timestamp;
+    market.bump = *ctx.bumps.get("market").unwrap();
+    market.version = Market::VERSION;
+
+    let settlement = &mut ctx.accounts.futures_settlement;
+    settlement.market = market.key();
+    settlement.settlement_window = params.settlement_window;
+    settlement.price_sum = 0;
+    settlement.num_samples = 0;
+    settlement.last_sample_time = 0;
+    settlement.settlement_price = 0;
+    settlement.is_finalized = false;
+    settlement.last_price = 0;
+    settlement.sampled_seconds = 0;
+    settlement.bump = *ctx.bumps.get("futures_settlement").unwrap();
+    settlement.version = FuturesSettlement::VERSION;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.market = market.key();
+    vault.collateral_mint = ctx.accounts.collateral_mint.key();
+    vault.token_account = ctx.accounts.vault_token_account.key();
+    vault.total_deposits = 0;
+    vault.bump = *ctx.bumps.get("vault").unwrap();
+    vault.version = Vault::VERSION;
+
+    msg!(
+        "Futures market initialized: {} for commodity: {} expiring at {}",
+        market.key(),
+        params.market.commodity,
+        params.expiry_timestamp
+    );
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, MarginAccount};
+
+#[derive(Accounts)]
+pub struct InitializeMarginAccount<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MarginAccount::LEN,
+        seeds = [b"margin_account", market.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub margin_account: Account<'info, MarginAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeMarginAccount>) -> Result<()> {
+    let margin_account = &mut ctx.accounts.margin_account;
+    margin_account.owner = ctx.accounts.owner.key();
+    margin_account.market = ctx.accounts.market.key();
+    margin_account.credit_used = 0;
+    margin_account.debt = 0;
+    margin_account.bump = *ctx.bumps.get("margin_account").unwrap();
+    margin_account.version = MarginAccount::VERSION;
+
+    msg!("Margin account initialized: {}", margin_account.key());
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{Market, Vault};
+use crate::state::{LiquidationMode, Market, MarketStatus, MarketType, Vault};
 use crate::errors::PerpsError;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -10,12 +10,92 @@ pub struct InitializeMarketParams {
     pub maintenance_margin_ratio: u32,
     pub initial_margin_ratio: u32,
     pub taker_fee: u32,
-    pub maker_fee: u32,
+    pub maker_fee: i32,              // Negative pays makers a rebate out of taker fees
     pub liquidation_fee: u32,
     pub max_open_interest: u64,
     pub funding_interval: i64,
 }
 
+impl InitializeMarketParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(!self.commodity.is_empty() && self.commodity.len() <= 8, PerpsError::InvalidMarketConfig);
+        require!(self.max_leverage > 0 && self.max_leverage <= 100_000, PerpsError::InvalidMarketConfig);
+        require!(self.maintenance_margin_ratio > 0, PerpsError::InvalidMarketConfig);
+        require!(self.initial_margin_ratio > self.maintenance_margin_ratio, PerpsError::InvalidMarketConfig);
+        require!(
+            Market::valid_fees(self.taker_fee, self.maker_fee),
+            PerpsError::InvalidMarketConfig
+        );
+        Ok(())
+    }
+
+    /// Write the commodity, risk and fee parameters and reset the market's counters
+    pub fn apply(&self, market: &mut Market, current_time: i64) {
+        market.commodity = Market::commodity_from_str(&self.commodity);
+
+        market.max_leverage = self.max_leverage;
+        market.maintenance_margin_ratio = self.maintenance_margin_ratio;
+        market.initial_margin_ratio = self.initial_margin_ratio;
+        market.taker_fee = self.taker_fee;
+        market.maker_fee = self.maker_fee;
+        market.liquidation_fee = self.liquidation_fee;
+        market.max_open_interest = self.max_open_interest;
+        market.funding_interval = self.funding_interval;
+
+        market.long_open_interest = 0;
+        market.short_open_interest = 0;
+        market.funding_rate = 0;
+        market.last_funding_time = current_time;
+        market.insurance_fund = 0;
+        market.total_positions = 0;
+        market.total_trades = 0;
+        market.is_paused = false;
+        market.has_trading_schedule = false;
+
+        market.mark_price = 0;
+        market.mark_price_updated_at = current_time;
+        market.mark_price_half_life = Market::DEFAULT_MARK_PRICE_HALF_LIFE;
+
+        market.reference_price = 0;
+        market.reference_price_updated_at = current_time;
+        market.price_band_bps = Market::DEFAULT_PRICE_BAND_BPS;
+        market.circuit_breaker_tripped = false;
+        market.status = MarketStatus::Active;
+
+        market.max_open_interest_notional = 0;
+        market.max_user_open_interest_notional = 0;
+        market.max_open_interest_imbalance_notional = 0;
+
+        market.liquidation_mode = LiquidationMode::Fixed;
+        market.auction_floor_discount_bps = 0;
+        market.auction_cap_discount_bps = 0;
+        market.auction_duration = 0;
+
+        market.user_collateral = 0;
+        market.position_collateral = 0;
+        market.position_margin_credit = 0;
+        market.long_entry_notional = 0;
+        market.short_entry_notional = 0;
+        market.tracked_long_size = 0;
+        market.tracked_short_size = 0;
+        market.referral_rewards = 0;
+
+        market.fill_authority = Pubkey::default();
+        market.rebate_budget = 0;
+
+        market.index_components = Default::default();
+        market.index_component_count = 0;
+
+        market.contract_unit = [0u8; 8];
+        market.contract_multiplier = 0;
+        market.lot_size = 0;
+        market.tick_size = 0;
+
+        market.risk_tiers = Default::default();
+        market.risk_tier_count = 0;
+    }
+}
+
 #[derive(Accounts)]
 pub struct InitializeMarket<'info> {
     #[account(mut)]
@@ -60,36 +140,18 @@ pub struct InitializeMarket<'info> {
 }
 
 pub fn handler(ctx: Context<InitializeMarket>, params: InitializeMarketParams) -> Result<()> {
-    require!(!params.commodity.is_empty() && params.commodity.len() <= 8, PerpsError::InvalidMarketConfig);
-    require!(params.max_leverage > 0 && params.max_leverage <= 100_000, PerpsError::InvalidMarketConfig);
-    require!(params.maintenance_margin_ratio > 0, PerpsError::InvalidMarketConfig);
-    require!(params.initial_margin_ratio > params.maintenance_margin_ratio, PerpsError::InvalidMarketConfig);
+    params.validate()?;
 
     let market = &mut ctx.accounts.market;
     market.authority = ctx.accounts.authority.key();
     market.collateral_mint = ctx.accounts.collateral_mint.key();
     market.vault = ctx.accounts.vault.key();
     market.pyth_price_feed = ctx.accounts.pyth_price_feed.key();
-    market.commodity = Market::commodity_from_str(&params.commodity);
-
-    market.max_leverage = params.max_leverage;
-    market.maintenance_margin_ratio = params.maintenance_margin_ratio;
-    market.initial_margin_ratio = params.initial_margin_ratio;
-    market.taker_fee = params.taker_fee;
-    market.maker_fee = params.maker_fee;
-    market.liquidation_fee = params.liquidation_fee;
-    market.max_open_interest = params.max_open_interest;
-    market.funding_interval = params.funding_interval;
-
-    market.long_open_interest = 0;
-    market.short_open_interest = 0;
-    market.funding_rate = 0;
-    market.last_funding_time = Clock::get()?.unix_timestamp;
-    market.insurance_fund = 0;
-    market.total_positions = 0;
-    market.total_trades = 0;
-    market.is_paused = false;
+    params.apply(market, Clock::get()?.unix_timestamp);
+    market.market_type = MarketType::Perpetual;
+    market.expiry_timestamp = 0;
     market.bump = *ctx.bumps.get("market").unwrap();
+    market.version = Market::VERSION;
 
     let vault = &mut ctx.accounts.vault;
     vault.market = market.key();
@@ -97,6 +159,7 @@ pub fn handler(ctx: Context<InitializeMarket>, params: InitializeMarketParams) -
     vault.token_account = ctx.accounts.vault_token_account.key();
     vault.total_deposits = 0;
     vault.bump = *ctx.bumps.get("vault").unwrap();
+    vault.version = Vault::VERSION;
 
     msg!("Market initialized: {} for commodity: {}", market.key(), params.commodity);
     Ok(())
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+use crate::state::{Market, Position, PositionStatus, Vault};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct InitializePositionMint<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// One-of-one token representing the position; the vault mints and burns it
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = vault,
+        seeds = [b"position_mint", position.key().as_ref()],
+        bump
+    )]
+    pub position_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<InitializePositionMint>) -> Result<()> {
+    msg!(
+        "Position mint initialized: {} for position {}",
+        ctx.accounts.position_mint.key(),
+        ctx.accounts.position.key()
+    );
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, SpreadMarket};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SpreadMarketParams {
+    pub hedge_ratio: u64,               // Leg B base units per leg A unit (6 decimals)
+    pub initial_margin_ratio: u32,      // Basis points of gross notional
+    pub maintenance_margin_ratio: u32,  // Basis points of gross notional
+}
+
+#[derive(Accounts)]
+pub struct InitializeSpreadMarket<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", leg_a_market.collateral_mint.as_ref(), leg_a_market.series_seed().as_ref()],
+        bump = leg_a_market.bump,
+        constraint = leg_a_market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub leg_a_market: Box<Account<'info, Market>>,
+
+    #[account(
+        seeds = [b"market", leg_b_market.collateral_mint.as_ref(), leg_b_market.series_seed().as_ref()],
+        bump = leg_b_market.bump,
+        constraint = leg_b_market.authority == authority.key() @ PerpsError::Unauthorized,
+        constraint = leg_b_market.key() != leg_a_market.key() @ PerpsError::InvalidSpreadParams,
+        constraint = leg_b_market.collateral_mint == leg_a_market.collateral_mint @ PerpsError::InvalidSpreadParams
+    )]
+    pub leg_b_market: Box<Account<'info, Market>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = SpreadMarket::LEN,
+        seeds = [b"spread_market", leg_a_market.key().as_ref(), leg_b_market.key().as_ref()],
+        bump
+    )]
+    pub spread_market: Account<'info, SpreadMarket>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeSpreadMarket>, params: SpreadMarketParams) -> Result<()> {
+    require!(params.hedge_ratio > 0, PerpsError::InvalidSpreadParams);
+    require!(
+        SpreadMarket::valid_margins(
+            params.initial_margin_ratio,
+            params.maintenance_margin_ratio,
+            &ctx.accounts.leg_a_market,
+            &ctx.accounts.leg_b_market,
+        ),
+        PerpsError::InvalidSpreadParams
+    );
+
+    let spread_market = &mut ctx.accounts.spread_market;
+    spread_market.authority = ctx.accounts.authority.key();
+    spread_market.leg_a_market = ctx.accounts.leg_a_market.key();
+    spread_market.leg_b_market = ctx.accounts.leg_b_market.key();
+    spread_market.hedge_ratio = params.hedge_ratio;
+    spread_market.initial_margin_ratio = params.initial_margin_ratio;
+    spread_market.maintenance_margin_ratio = params.maintenance_margin_ratio;
+    spread_market.open_spreads = 0;
+    spread_market.is_active = true;
+    spread_market.bump = *ctx.bumps.get("spread_market").unwrap();
+    spread_market.version = SpreadMarket::VERSION;
+
+    msg!(
+        "Spread market initialized: {} / {} at hedge ratio {}, margin {}/{}bps",
+        ctx.accounts.leg_a_market.commodity_str(),
+        ctx.accounts.leg_b_market.commodity_str(),
+        params.hedge_ratio,
+        params.initial_margin_ratio,
+        params.maintenance_margin_ratio
+    );
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, SessionWindow, TradingSchedule, MAX_HOLIDAYS};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TradingScheduleParams {
+    pub weekly_sessions: [[SessionWindow; 2]; 7],   // UTC windows per weekday, 0 = Sunday
+    pub holidays: [i64; MAX_HOLIDAYS],              // UTC midnight timestamps, 0 = unused
+    pub enabled: bool,                              // Whether the market follows the schedule
+}
+
+impl TradingScheduleParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.weekly_sessions.iter().flatten().all(|window| window.is_valid()),
+            PerpsError::InvalidTradingSchedule
+        );
+        require!(
+            self.holidays.iter().all(|&day| day >= 0 && day % 86_400 == 0),
+            PerpsError::InvalidTradingSchedule
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeTradingSchedule<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = TradingSchedule::LEN,
+        seeds = [b"trading_schedule", market.key().as_ref()],
+        bump
+    )]
+    pub trading_schedule: Account<'info, TradingSchedule>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeTradingSchedule>,
+    params: TradingScheduleParams,
+) -> Result<()> {
+    params.validate()?;
+
+    let schedule = &mut ctx.accounts.trading_schedule;
+    schedule.market = ctx.accounts.market.key();
+    schedule.weekly_sessions = params.weekly_sessions;
+    schedule.holidays = params.holidays;
+    schedule.bump = *ctx.bumps.get("trading_schedule").unwrap();
+    schedule.version = TradingSchedule::VERSION;
+
+    ctx.accounts.market.has_trading_schedule = params.enabled;
+
+    msg!(
+        "Trading schedule initialized for market {} (enabled={})",
+        schedule.market,
+        params.enabled
+    );
+    Ok(())
+}
+
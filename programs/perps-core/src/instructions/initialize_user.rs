@@ -25,7 +25,11 @@ pub fn handler(ctx: Context<InitializeUser>) -> Result<()> {
     user_account.total_positions = 0;
     user_account.total_trades = 0;
     user_account.realized_pnl = 0;
+    user_account.next_position_id = 0;
     user_account.bump = *ctx.bumps.get("user_account").unwrap();
+    user_account.version = UserAccount::VERSION;
+    user_account.competition = Pubkey::default();
+    user_account.competition_end = 0;
 
     msg!("User account initialized: {}", user_account.key());
     Ok(())
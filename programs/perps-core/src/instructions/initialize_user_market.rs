@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, UserMarketAccount};
+
+#[derive(Accounts)]
+pub struct InitializeUserMarket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_market: Account<'info, UserMarketAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeUserMarket>) -> Result<()> {
+    let user_market = &mut ctx.accounts.user_market;
+    user_market.owner = ctx.accounts.owner.key();
+    user_market.market = ctx.accounts.market.key();
+    user_market.open_interest = 0;
+    user_market.bump = *ctx.bumps.get("user_market").unwrap();
+    user_market.version = UserMarketAccount::VERSION;
+    user_market.volume = 0;
+    user_market.fees_paid = 0;
+    user_market.realized_pnl = 0;
+    user_market.funding_paid = 0;
+    user_market.funding_received = 0;
+    user_market.total_trades = 0;
+    user_market.liquidations = 0;
+    user_market.wins = 0;
+    user_market.losses = 0;
+
+    msg!("User market account initialized: {}", user_market.key());
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::{GlobalSettlement, Market, MarketStatus};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct InitiateGlobalSettlement<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = GlobalSettlement::LEN,
+        seeds = [b"global_settlement", market.key().as_ref()],
+        bump
+    )]
+    pub global_settlement: Account<'info, GlobalSettlement>,
+
+    #[account(
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitiateGlobalSettlement>, settlement_price: u64) -> Result<()> {
+    require!(settlement_price > 0, PerpsError::InvalidMarketConfig);
+
+    let market = &mut ctx.accounts.market;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let global_settlement = &mut ctx.accounts.global_settlement;
+    global_settlement.market = market.key();
+    global_settlement.settlement_price = settlement_price;
+    global_settlement.initiated_at = current_time;
+    global_settlement.total_claims = 0;
+    global_settlement.claimable_balance = 0;
+    global_settlement.total_paid = 0;
+    global_settlement.bump = *ctx.bumps.get("global_settlement").unwrap();
+    global_settlement.version = GlobalSettlement::VERSION;
+
+    // With nothing open there is nothing to close out
+    market.status = MarketStatus::Settling;
+    global_settlement.complete_if_flat(market, ctx.accounts.vault_token_account.amount);
+
+    msg!(
+        "Global settlement initiated: market={}, price={}",
+        market.key(),
+        settlement_price
+    );
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use crate::state::{Competition, CompetitionEntry, UserAccount, MAX_COMPETITION_ENTRIES};
+use crate::errors::PerpsError;
+
+/// Enter a competition before it ends; only closes after joining are counted.
+/// Entries are approved by the competition's authority co-signing, so its slots can't
+/// be filled with throwaway wallets. An owner is in one competition at a time, and
+/// until it ends every close, reduction and liquidation of their positions must pass
+/// its account.
+#[derive(Accounts)]
+pub struct JoinCompetition<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(constraint = authority.key() == competition.authority @ PerpsError::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.owner == owner.key()
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump
+    )]
+    pub competition: Box<Account<'info, Competition>>,
+}
+
+pub fn handler(ctx: Context<JoinCompetition>) -> Result<()> {
+    let competition = &mut ctx.accounts.competition;
+    let user_account = &mut ctx.accounts.user_account;
+    let owner = ctx.accounts.owner.key();
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(current_time <= competition.end_time, PerpsError::CompetitionEnded);
+    require!(
+        competition.find_entry(&owner).is_none(),
+        PerpsError::AlreadyInCompetition
+    );
+    require!(
+        (competition.num_entries as usize) < MAX_COMPETITION_ENTRIES,
+        PerpsError::CompetitionFull
+    );
+    require!(
+        user_account.active_competition(current_time).is_none(),
+        PerpsError::InAnotherCompetition
+    );
+
+    let index = competition.num_entries as usize;
+    competition.entries[index] = CompetitionEntry {
+        owner,
+        ..CompetitionEntry::default()
+    };
+    competition.num_entries += 1;
+
+    user_account.competition = competition.key();
+    user_account.competition_end = competition.end_time;
+
+    msg!("{} joined competition {}", owner, competition.id);
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use pyth_sdk_solana::load_price_feed_from_account_info;
-use crate::state::{Market, Position, Vault, UserAccount, Side, PositionStatus};
+use perps_math::{Bps, Rounding};
+use crate::state::{Market, Position, Vault, UserAccount, Side, PositionStatus, MarketStatus, LiquidationMode, Competition, MarginAccount, CollateralRegistry, record_competition_result, unbacked_credit, load_initialized_user_market};
 use crate::errors::PerpsError;
 
 #[derive(Accounts)]
@@ -21,8 +21,10 @@ pub struct Liquidate<'info> {
 
     #[account(
         mut,
-        seeds = [b"market", market.collateral_mint.as_ref()],
-        bump = market.bump
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = market.liquidation_mode == LiquidationMode::Fixed @ PerpsError::WrongLiquidationMode
     )]
     pub market: Account<'info, Market>,
 
@@ -60,6 +62,39 @@ pub struct Liquidate<'info> {
     pub pyth_price_feed: AccountInfo<'info>,
 
     pub token_program: Program<'info, Token>,
+
+    /// CHECK: The owner's user market PDA, counting their open interest. Only read if
+    /// initialized, since the liquidator can't be made to create it for the owner.
+    #[account(
+        mut,
+        seeds = [b"user_market", market.key().as_ref(), position_owner.key().as_ref()],
+        bump,
+    )]
+    pub user_market: UncheckedAccount<'info>,
+
+    // Required while the owner is entered in a running competition, recording the liquidation
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+
+    // Required when the position drew margin credit against multi-collateral
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), position_owner.key().as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    // Values the pledged collateral behind margin credit; required with the margin
+    // account when the position drew any, with the asset feeds after the index feeds
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump,
+    )]
+    pub collateral_registry: Option<Account<'info, CollateralRegistry>>,
 }
 
 pub fn handler(ctx: Context<Liquidate>) -> Result<()> {
@@ -67,38 +102,58 @@ pub fn handler(ctx: Context<Liquidate>) -> Result<()> {
     let position = &mut ctx.accounts.position;
     let user_account = &mut ctx.accounts.user_account;
 
-    // Get oracle price
-    let price_feed = load_price_feed_from_account_info(&ctx.accounts.pyth_price_feed)
-        .map_err(|_| PerpsError::InvalidOraclePrice)?;
-
     let current_time = Clock::get()?.unix_timestamp;
-    let price_data = price_feed
-        .get_price_no_older_than(current_time, 60)
-        .ok_or(PerpsError::StaleOraclePrice)?;
-
-    require!(price_data.price > 0, PerpsError::InvalidOraclePrice);
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
 
-    let oracle_price = (price_data.price as u64)
-        .checked_mul(10u64.pow((6 + price_data.expo.abs() as u32) as u32))
-        .and_then(|p| p.checked_div(10u64.pow(price_data.expo.abs() as u32)))
-        .ok_or(PerpsError::MathOverflow)? as u64;
-
-    // Check if position is liquidatable
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    // Eligibility is judged on the smoothed mark price so a single bad print cannot
+    // trigger liquidations; the amounts below still settle at the oracle price
+    market.update_mark_price(oracle_price, current_time);
+    market.update_price_band(oracle_price, current_time);
+    let (_, asset_feeds) = market.split_index_feeds(ctx.remaining_accounts)?;
+    let unbacked = unbacked_credit(
+        position,
+        ctx.accounts.collateral_registry.as_deref(),
+        ctx.accounts.margin_account.as_deref(),
+        asset_feeds,
+        current_time,
+    )?;
     require!(
-        position.is_liquidatable(oracle_price, market.maintenance_margin_ratio),
+        position.is_backed_liquidatable(market.mark_price, market.position_maintenance_margin(position)?, unbacked)?,
         PerpsError::NotLiquidatable
     );
 
+    // The owner's counted open interest is released along with the position
+    let mut user_market = load_initialized_user_market(&ctx.accounts.user_market)?;
+
     // Calculate liquidation amounts
-    let pnl = position.unrealized_pnl(oracle_price);
-    let remaining_collateral = (position.collateral as i64 + pnl).max(0) as u64;
+    let pnl = position.unrealized_pnl(oracle_price)?;
+    let equity = perps_math::equity(position.collateral, pnl).map_err(PerpsError::from)?;
+
+    // Margin lent against multi-collateral never entered the vault, so it is repaid
+    // before anything is paid out; what the equity can't repay becomes debt
+    let credit_repaid = if position.margin_credit > 0 {
+        let margin_account = ctx.accounts.margin_account
+            .as_mut()
+            .ok_or(PerpsError::MarginCreditOutstanding)?;
+        margin_account.settle_credit(position.margin_credit, equity)
+    } else {
+        0
+    };
+    let remaining_collateral = equity - credit_repaid;
 
     // Liquidation fee goes to liquidator
-    let liquidation_reward = (remaining_collateral as u128 * market.liquidation_fee as u128 / 10000) as u64;
+    let liquidation_reward = Bps::from(market.liquidation_fee)
+        .of(remaining_collateral, Rounding::Down)
+        .map_err(PerpsError::from)?;
 
     // Remainder goes to insurance fund
     let to_insurance = remaining_collateral.saturating_sub(liquidation_reward);
 
+    market.untrack_position(position)?;
+
     // Update market OI
     match position.side {
         Side::Long => {
@@ -110,17 +165,34 @@ pub fn handler(ctx: Context<Liquidate>) -> Result<()> {
                 .saturating_sub(position.size);
         }
     }
+    if let Some(user_market) = user_market.as_deref_mut() {
+        user_market.remove_open_interest(position.size);
+    }
 
     // Add to insurance fund
     market.insurance_fund = market.insurance_fund.saturating_add(to_insurance);
 
     // Mark position as liquidated
     position.status = PositionStatus::Liquidated;
+    position.margin_credit = 0;
     position.realized_pnl = pnl;
     position.last_updated_at = current_time;
 
     // Update user stats
     user_account.realized_pnl = user_account.realized_pnl.saturating_add(pnl);
+    if let Some(user_market) = user_market.as_mut() {
+        user_market.record_pnl(pnl);
+        user_market.record_liquidation();
+        user_market.exit(&crate::ID)?;
+    }
+    record_competition_result(
+        ctx.accounts.competition.as_deref_mut(),
+        user_account,
+        &market.key(),
+        pnl,
+        position.collateral,
+        current_time,
+    )?;
 
     // Pay liquidator
     if liquidation_reward > 0 {
@@ -144,14 +216,16 @@ pub fn handler(ctx: Context<Liquidate>) -> Result<()> {
             signer,
         );
         token::transfer(cpi_ctx, liquidation_reward)?;
+        ctx.accounts.vault.record_outflow(liquidation_reward);
     }
 
     msg!(
-        "Position liquidated: owner={}, size={}, reward={}, insurance={}",
+        "Position liquidated: owner={}, size={}, reward={}, insurance={}, credit repaid={}",
         position.owner,
         position.size,
         liquidation_reward,
-        to_insurance
+        to_insurance,
+        credit_repaid
     );
 
     Ok(())
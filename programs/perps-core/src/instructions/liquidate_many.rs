@@ -0,0 +1,297 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::{Bps, Rounding};
+use crate::state::{Market, Position, Vault, UserAccount, Side, PositionStatus, MarketStatus, UserMarketAccount, LiquidationMode, MarginAccount, CollateralRegistry, Competition, record_competition_result, unbacked_credit};
+use crate::errors::PerpsError;
+
+pub const MAX_BATCH_LIQUIDATIONS: usize = 16;
+
+/// Liquidates every eligible position in `remaining_accounts` against one oracle read.
+///
+/// Remaining accounts start with an index market's feeds, then, when the collateral
+/// registry is passed, the price feed of every registered asset, then come in
+/// (position, user_account, margin_account, user_market) groups. The margin account
+/// settles credit the position drew against multi-collateral; pass the program id for
+/// positions without any. The user market is the owner's PDA, only read if they
+/// initialized it, and releases their counted open interest and records the liquidation.
+/// Positions that are closed, healthy or belong to another market are skipped, as are
+/// those whose owner is entered in a running competition other than the one passed.
+/// Positions with margin credit are judged with their pledged collateral valued as in
+/// `liquidate`, and skipped when the registry is not passed.
+#[derive(Accounts)]
+pub struct LiquidateMany<'info> {
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = market.liquidation_mode == LiquidationMode::Fixed @ PerpsError::WrongLiquidationMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = liquidator_token_account.owner == liquidator.key(),
+        constraint = liquidator_token_account.mint == market.collateral_mint
+    )]
+    pub liquidator_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth price feed
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    // Records each liquidation whose owner is entered in this competition
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+
+    // Values the pledged collateral behind margin credit, with the asset feeds after
+    // the index feeds; positions that drew any are skipped without it
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump,
+    )]
+    pub collateral_registry: Option<Box<Account<'info, CollateralRegistry>>>,
+}
+
+pub fn handler(ctx: Context<LiquidateMany>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+
+    let (_, after_index_feeds) = market.split_index_feeds(ctx.remaining_accounts)?;
+    let registry = ctx.accounts.collateral_registry.as_deref().map(|registry| &**registry);
+    let asset_feed_count = registry.map_or(0, |registry| registry.num_assets as usize);
+    require!(after_index_feeds.len() >= asset_feed_count, PerpsError::InvalidBatchAccounts);
+    let (asset_feeds, batch_accounts) = after_index_feeds.split_at(asset_feed_count);
+    let batch = batch_accounts.chunks_exact(4);
+    let batch_size = batch.len();
+    require!(
+        batch_size > 0 && batch_size <= MAX_BATCH_LIQUIDATIONS && batch.remainder().is_empty(),
+        PerpsError::InvalidBatchAccounts
+    );
+
+    // Get oracle price once for the whole batch
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    // Eligibility is judged on the smoothed mark price, amounts settle at the oracle price
+    market.update_mark_price(oracle_price, current_time);
+    market.update_price_band(oracle_price, current_time);
+
+    let market_key = market.key();
+    let competition_key = ctx.accounts.competition.as_ref().map(|competition| competition.key());
+    let mut total_reward: u64 = 0;
+    let mut liquidated: u32 = 0;
+
+    for accounts in batch {
+        let Some(mut position) = load_batch_account::<Position>(&accounts[0]) else {
+            continue;
+        };
+        if position.market != market_key || position.status != PositionStatus::Open {
+            msg!("Skipping position {}", accounts[0].key());
+            continue;
+        }
+
+        // UserAccount PDAs are unique per owner, so the owner field identifies it
+        let Some(mut user_account) = load_batch_account::<UserAccount>(&accounts[1]) else {
+            continue;
+        };
+        if user_account.owner != position.owner {
+            msg!("Skipping position {}: user account mismatch", accounts[0].key());
+            continue;
+        }
+
+        // Owners in a running competition have every liquidation recorded there
+        if let Some(entered) = user_account.active_competition(current_time) {
+            if competition_key != Some(entered) {
+                msg!("Skipping position {}: competition account required", accounts[0].key());
+                continue;
+            }
+        }
+
+        // Margin account PDAs are unique per market and owner, like user accounts
+        let mut margin_account = None;
+        if position.margin_credit > 0 {
+            match load_batch_account::<MarginAccount>(&accounts[2]) {
+                Some(account) if account.market == market_key && account.owner == position.owner => {
+                    margin_account = Some(account);
+                }
+                _ => {
+                    msg!("Skipping position {}: margin account mismatch", accounts[0].key());
+                    continue;
+                }
+            }
+        }
+
+        // Lent margin the pledged collateral no longer backs counts against the position
+        if position.margin_credit > 0 && registry.is_none() {
+            msg!("Skipping position {}: collateral registry required", accounts[0].key());
+            continue;
+        }
+        let unbacked = unbacked_credit(&position, registry, margin_account.as_ref(), asset_feeds, current_time)?;
+        if !position.is_backed_liquidatable(market.mark_price, market.position_maintenance_margin(&position)?, unbacked)? {
+            msg!("Skipping position {}", accounts[0].key());
+            continue;
+        }
+
+        let Ok(user_market) = load_user_market(&accounts[3], &market_key, &position.owner) else {
+            msg!("Skipping position {}: user market mismatch", accounts[0].key());
+            continue;
+        };
+
+        // Calculate liquidation amounts
+        let pnl = position.unrealized_pnl(oracle_price)?;
+        let equity = perps_math::equity(position.collateral, pnl).map_err(PerpsError::from)?;
+
+        // Lent margin never entered the vault and is repaid before anything is paid out
+        let credit_repaid = match margin_account.as_mut() {
+            Some(margin_account) => margin_account.settle_credit(position.margin_credit, equity),
+            None => 0,
+        };
+        let remaining_collateral = equity - credit_repaid;
+        let liquidation_reward = Bps::from(market.liquidation_fee)
+            .of(remaining_collateral, Rounding::Down)
+            .map_err(PerpsError::from)?;
+        let to_insurance = remaining_collateral.saturating_sub(liquidation_reward);
+
+        market.untrack_position(&position)?;
+
+        // Update market OI
+        match position.side {
+            Side::Long => {
+                market.long_open_interest = market.long_open_interest
+                    .saturating_sub(position.size);
+            }
+            Side::Short => {
+                market.short_open_interest = market.short_open_interest
+                    .saturating_sub(position.size);
+            }
+        }
+        market.insurance_fund = market.insurance_fund.saturating_add(to_insurance);
+
+        position.status = PositionStatus::Liquidated;
+        position.margin_credit = 0;
+        position.realized_pnl = pnl;
+        position.last_updated_at = current_time;
+        user_account.realized_pnl = user_account.realized_pnl.saturating_add(pnl);
+
+        // Written back immediately so a position repeated in the batch reads as liquidated
+        store_batch_account(&accounts[0], &position)?;
+        store_batch_account(&accounts[1], &user_account)?;
+        if let Some(margin_account) = margin_account {
+            store_batch_account(&accounts[2], &margin_account)?;
+        }
+        if let Some(mut user_market) = user_market {
+            user_market.remove_open_interest(position.size);
+            user_market.record_pnl(pnl);
+            user_market.record_liquidation();
+            store_batch_account(&accounts[3], &user_market)?;
+        }
+        record_competition_result(
+            ctx.accounts.competition.as_deref_mut(),
+            &user_account,
+            &market_key,
+            pnl,
+            position.collateral,
+            current_time,
+        )?;
+
+        total_reward = total_reward.saturating_add(liquidation_reward);
+        liquidated += 1;
+
+        msg!(
+            "Position liquidated: owner={}, size={}, reward={}, insurance={}",
+            position.owner,
+            position.size,
+            liquidation_reward,
+            to_insurance
+        );
+    }
+
+    // Pay liquidator once for the whole batch
+    if total_reward > 0 {
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.liquidator_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, total_reward)?;
+        ctx.accounts.vault.record_outflow(total_reward);
+    }
+
+    msg!(
+        "Batch liquidation: {} of {} positions liquidated, reward={}",
+        liquidated,
+        batch_size,
+        total_reward
+    );
+
+    Ok(())
+}
+
+/// The owner's user market account if initialized. Anything but their user market PDA
+/// is an error, so the owner's open interest can't be left counted.
+fn load_user_market(info: &AccountInfo, market: &Pubkey, owner: &Pubkey) -> Result<Option<UserMarketAccount>> {
+    let (address, _) = Pubkey::find_program_address(
+        &[b"user_market", market.as_ref(), owner.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(info.key(), address, PerpsError::UserMarketAccountRequired);
+
+    if info.owner != &crate::ID {
+        return Ok(None);
+    }
+    load_batch_account::<UserMarketAccount>(info)
+        .map(Some)
+        .ok_or(PerpsError::UserMarketAccountRequired.into())
+}
+
+/// Deserialize a writable program account from the batch, or None to skip it
+fn load_batch_account<T: AccountDeserialize + Owner>(info: &AccountInfo) -> Option<T> {
+    if !info.is_writable || info.owner != &T::owner() {
+        msg!("Skipping account {}", info.key());
+        return None;
+    }
+    let data = info.try_borrow_data().ok()?;
+    T::try_deserialize(&mut &data[..]).ok()
+}
+
+fn store_batch_account<T: AccountSerialize>(info: &AccountInfo, account: &T) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    account.try_serialize(&mut &mut data[..])
+}
@@ -0,0 +1,242 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::{Bps, Rounding};
+use crate::state::{
+    Market, MarketStatus, PositionStatus, Side, SpreadMarket, SpreadPosition,
+    UserAccount, Vault, load_initialized_user_market,
+};
+use crate::errors::PerpsError;
+use crate::instructions::close_spread_position::move_leg_b_settlement;
+
+/// Liquidate both legs of a spread once its equity falls below the spread's
+/// maintenance margin. The liquidator earns leg A's liquidation fee.
+#[derive(Accounts)]
+pub struct LiquidateSpreadPosition<'info> {
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    /// CHECK: Position owner, doesn't need to sign
+    pub position_owner: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", position_owner.key().as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"spread_market", spread_market.leg_a_market.as_ref(), spread_market.leg_b_market.as_ref()],
+        bump = spread_market.bump
+    )]
+    pub spread_market: Box<Account<'info, SpreadMarket>>,
+
+    #[account(
+        mut,
+        address = spread_market.leg_a_market @ PerpsError::InvalidSpreadLeg,
+        constraint = leg_a_market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub leg_a_market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        address = spread_market.leg_b_market @ PerpsError::InvalidSpreadLeg,
+        constraint = leg_b_market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub leg_b_market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        constraint = spread_position.owner == position_owner.key(),
+        constraint = spread_position.spread_market == spread_market.key(),
+        constraint = spread_position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed
+    )]
+    pub spread_position: Box<Account<'info, SpreadPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", leg_a_market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", leg_a_market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Leg B's vault settles leg B's PnL
+    #[account(
+        mut,
+        seeds = [b"vault", leg_b_market.key().as_ref()],
+        bump = leg_b_vault.bump
+    )]
+    pub leg_b_vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", leg_b_market.key().as_ref()],
+        bump
+    )]
+    pub leg_b_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = liquidator_token_account.owner == liquidator.key(),
+        constraint = liquidator_token_account.mint == leg_a_market.collateral_mint
+    )]
+    pub liquidator_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Pyth price feed
+    #[account(constraint = leg_a_price_feed.key() == leg_a_market.pyth_price_feed)]
+    pub leg_a_price_feed: AccountInfo<'info>,
+
+    /// CHECK: Pyth price feed
+    #[account(constraint = leg_b_price_feed.key() == leg_b_market.pyth_price_feed)]
+    pub leg_b_price_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: The owner's user market PDAs, counting their open interest in each leg. Only
+    /// read if initialized, since the liquidator can't be made to create them for the owner.
+    #[account(
+        mut,
+        seeds = [b"user_market", leg_a_market.key().as_ref(), position_owner.key().as_ref()],
+        bump,
+    )]
+    pub leg_a_user_market: UncheckedAccount<'info>,
+
+    /// CHECK: As leg_a_user_market
+    #[account(
+        mut,
+        seeds = [b"user_market", leg_b_market.key().as_ref(), position_owner.key().as_ref()],
+        bump,
+    )]
+    pub leg_b_user_market: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<LiquidateSpreadPosition>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        !ctx.accounts.leg_a_market.is_expired(current_time)
+            && !ctx.accounts.leg_b_market.is_expired(current_time),
+        PerpsError::MarketExpired
+    );
+
+    // Remaining accounts hold leg A's index feeds, then leg B's
+    let (_, leg_b_feeds) = ctx.accounts.leg_a_market.split_index_feeds(ctx.remaining_accounts)?;
+    let price_a = ctx.accounts.leg_a_market.index_price(&ctx.accounts.leg_a_price_feed, ctx.remaining_accounts, current_time)?;
+    let price_b = ctx.accounts.leg_b_market.index_price(&ctx.accounts.leg_b_price_feed, leg_b_feeds, current_time)?;
+
+    let leg_a_market = &mut ctx.accounts.leg_a_market;
+    let leg_b_market = &mut ctx.accounts.leg_b_market;
+    let spread_position = &mut ctx.accounts.spread_position;
+    let leg_a = spread_position.leg_a;
+    let leg_b = spread_position.leg_b;
+
+    // Eligibility is judged on both smoothed mark prices; amounts settle at the oracle
+    for (market, price) in [(&mut *leg_a_market, price_a), (&mut *leg_b_market, price_b)] {
+        market.update_mark_price(price, current_time);
+        market.update_price_band(price, current_time);
+    }
+    require!(
+        spread_position.is_liquidatable(
+            leg_a_market.mark_price,
+            leg_b_market.mark_price,
+            ctx.accounts.spread_market.maintenance_margin_ratio,
+        )?,
+        PerpsError::NotLiquidatable
+    );
+
+    let pnl = spread_position.unrealized_pnl(price_a, price_b)?;
+    let remaining_collateral = perps_math::equity(spread_position.collateral, pnl).map_err(PerpsError::from)?;
+    let leg_b_transfer = spread_position.leg_b_transfer(leg_a.unrealized_pnl(price_a)?, leg_b.unrealized_pnl(price_b)?);
+
+    let liquidation_reward = Bps::from(leg_a_market.liquidation_fee)
+        .of(remaining_collateral, Rounding::Down)
+        .map_err(PerpsError::from)?;
+    let to_insurance = remaining_collateral.saturating_sub(liquidation_reward);
+
+    leg_a_market.untrack_spread_leg(&leg_a, spread_position.collateral)?;
+    leg_b_market.untrack_spread_leg(&leg_b, 0)?;
+
+    for (market, leg) in [(&mut *leg_a_market, &leg_a), (&mut *leg_b_market, &leg_b)] {
+        match leg.side {
+            Side::Long => {
+                market.long_open_interest = market.long_open_interest.saturating_sub(leg.size);
+            }
+            Side::Short => {
+                market.short_open_interest = market.short_open_interest.saturating_sub(leg.size);
+            }
+        }
+    }
+    for (user_market, leg) in [
+        (&ctx.accounts.leg_a_user_market, &leg_a),
+        (&ctx.accounts.leg_b_user_market, &leg_b),
+    ] {
+        if let Some(mut user_market) = load_initialized_user_market(user_market)? {
+            user_market.remove_open_interest(leg.size);
+            user_market.record_liquidation();
+            user_market.exit(&crate::ID)?;
+        }
+    }
+
+    leg_a_market.insurance_fund = leg_a_market.insurance_fund.saturating_add(to_insurance);
+
+    spread_position.status = PositionStatus::Liquidated;
+    spread_position.realized_pnl = pnl;
+    spread_position.last_updated_at = current_time;
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.realized_pnl = user_account.realized_pnl.saturating_add(pnl);
+
+    let spread_market = &mut ctx.accounts.spread_market;
+    spread_market.open_spreads = spread_market.open_spreads.saturating_sub(1);
+
+    move_leg_b_settlement(
+        leg_b_transfer,
+        &mut ctx.accounts.vault,
+        &ctx.accounts.vault_token_account,
+        &mut ctx.accounts.leg_b_vault,
+        &ctx.accounts.leg_b_vault_token_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    if liquidation_reward > 0 {
+        let market_key = leg_a_market.key();
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.liquidator_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, liquidation_reward)?;
+        ctx.accounts.vault.record_outflow(liquidation_reward);
+    }
+
+    msg!(
+        "Spread liquidated: owner={}, size={}, reward={}, insurance={}, leg B transfer={}",
+        spread_position.owner,
+        leg_a.size,
+        liquidation_reward,
+        to_insurance,
+        leg_b_transfer
+    );
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::Market;
+use crate::errors::PerpsError;
+use crate::migration::{check_migration_version, load_for_migration, store_migrated};
+
+#[derive(Accounts)]
+pub struct MigrateMarket<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Decoded by version in the handler
+    #[account(mut, owner = crate::ID)]
+    pub market: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateMarket>) -> Result<()> {
+    let mut market: Market = load_for_migration(
+        &ctx.accounts.market,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Market::LEN,
+    )?;
+    require!(
+        market.authority == ctx.accounts.authority.key(),
+        PerpsError::Unauthorized
+    );
+
+    let from_version = market.version;
+    check_migration_version(from_version, 0, Market::VERSION)?;
+    market.upgrade_from(from_version, Clock::get()?.unix_timestamp);
+
+    store_migrated(&ctx.accounts.market, &market)?;
+
+    msg!("Market migrated: v{} -> v{}", from_version, Market::VERSION);
+    Ok(())
+}
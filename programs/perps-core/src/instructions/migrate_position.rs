@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, Position, PositionStatus};
+use crate::errors::PerpsError;
+use crate::migration::{check_migration_version, legacy_byte, load_for_migration, store_migrated};
+
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Decoded by version in the handler
+    #[account(mut, owner = crate::ID)]
+    pub position: AccountInfo<'info>,
+
+    // Open positions join the market's solvency aggregates, so the market must be
+    // migrated first; checked against the decoded position in the handler
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigratePosition>) -> Result<()> {
+    let mut position: Position = load_for_migration(
+        &ctx.accounts.position,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Position::LEN,
+    )?;
+
+    let from_version = position.version;
+    check_migration_version(from_version, 0, Position::VERSION)?;
+
+    // Version 0 kept the bump where position_id now starts; its seed nonce was the
+    // market's position count, which is not recoverable, so the id is left at 0.
+    // Version 1 has the same layout
+    if from_version < 1 {
+        position.bump = legacy_byte(&ctx.accounts.position, Position::V0_BUMP_OFFSET)?;
+        position.position_id = 0;
+    }
+    require!(position.market == ctx.accounts.market.key(), PerpsError::Unauthorized);
+    position.version = Position::VERSION;
+    if position.status == PositionStatus::Open {
+        ctx.accounts.market.track_position(&position)?;
+    }
+
+    store_migrated(&ctx.accounts.position, &position)?;
+
+    msg!("Position migrated: v{} -> v{}", from_version, Position::VERSION);
+    Ok(())
+}
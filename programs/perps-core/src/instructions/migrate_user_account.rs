@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::UserAccount;
+use crate::migration::{check_migration_version, legacy_byte, load_for_migration, store_migrated};
+
+#[derive(Accounts)]
+pub struct MigrateUserAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Decoded by version in the handler
+    #[account(mut, owner = crate::ID)]
+    pub user_account: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateUserAccount>) -> Result<()> {
+    let mut user_account: UserAccount = load_for_migration(
+        &ctx.accounts.user_account,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        UserAccount::LEN,
+    )?;
+
+    let from_version = user_account.version;
+    check_migration_version(from_version, 0, UserAccount::VERSION)?;
+
+    // Version 0 had no position nonce and kept the bump in its place
+    if from_version < 1 {
+        user_account.bump = legacy_byte(&ctx.accounts.user_account, UserAccount::V0_BUMP_OFFSET)?;
+        user_account.next_position_id = UserAccount::V0_FIRST_POSITION_ID;
+    }
+
+    // Version 2 accounts start outside any competition, which the zeroed realloc gives
+    user_account.version = UserAccount::VERSION;
+
+    store_migrated(&ctx.accounts.user_account, &user_account)?;
+
+    msg!("User account migrated: v{} -> v{}", from_version, UserAccount::VERSION);
+    Ok(())
+}
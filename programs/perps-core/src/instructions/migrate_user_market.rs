@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::UserMarketAccount;
+use crate::migration::{check_migration_version, load_for_migration, store_migrated};
+
+#[derive(Accounts)]
+pub struct MigrateUserMarket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Decoded by version in the handler
+    #[account(mut, owner = crate::ID)]
+    pub user_market: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateUserMarket>) -> Result<()> {
+    let mut user_market: UserMarketAccount = load_for_migration(
+        &ctx.accounts.user_market,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        UserMarketAccount::LEN,
+    )?;
+
+    let from_version = user_market.version;
+    // Version 2 statistics start at zero, which is what the zeroed realloc gives
+    check_migration_version(from_version, 1, UserMarketAccount::VERSION)?;
+    user_market.version = UserMarketAccount::VERSION;
+
+    store_migrated(&ctx.accounts.user_market, &user_market)?;
+
+    msg!("User market account migrated: v{} -> v{}", from_version, UserMarketAccount::VERSION);
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::Vault;
+use crate::errors::PerpsError;
+use crate::migration::{check_migration_version, load_for_migration, store_migrated};
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Decoded by version in the handler
+    #[account(mut, owner = crate::ID)]
+    pub vault: AccountInfo<'info>,
+
+    // Checked against the decoded vault in the handler
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateVault>) -> Result<()> {
+    let mut vault: Vault = load_for_migration(
+        &ctx.accounts.vault,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Vault::LEN,
+    )?;
+
+    let from_version = vault.version;
+    // Versions 0 and 1 have the same layout
+    check_migration_version(from_version, 0, Vault::VERSION)?;
+    require!(
+        vault.token_account == ctx.accounts.vault_token_account.key(),
+        PerpsError::InvalidVaultTokenAccount
+    );
+
+    // Earlier versions only counted deposits; start tracking from the actual balance
+    vault.total_deposits = ctx.accounts.vault_token_account.amount;
+    vault.version = Vault::VERSION;
+
+    store_migrated(&ctx.accounts.vault, &vault)?;
+
+    msg!("Vault migrated: v{} -> v{}", from_version, Vault::VERSION);
+    Ok(())
+}
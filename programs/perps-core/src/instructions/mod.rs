@@ -1,25 +1,145 @@
 pub mod initialize_market;
+pub mod initialize_futures_market;
 pub mod initialize_user;
 pub mod deposit_collateral;
 pub mod withdraw_collateral;
+pub mod initialize_collateral_registry;
+pub mod add_collateral_asset;
+pub mod update_collateral_asset;
+pub mod initialize_margin_account;
 pub mod open_position;
 pub mod close_position;
+pub mod increase_position;
+pub mod reduce_position;
 pub mod add_margin;
+pub mod transfer_position;
+pub mod initialize_position_mint;
+pub mod wrap_position;
+pub mod unwrap_position;
 pub mod liquidate;
+pub mod liquidate_many;
+pub mod set_liquidation_mode;
+pub mod start_liquidation_auction;
+pub mod take_over_position;
+pub mod cancel_liquidation_auction;
+pub mod place_trigger_order;
+pub mod cancel_trigger_order;
+pub mod execute_trigger_order;
+pub mod reap_trigger_order;
+pub mod initialize_crank_rewards;
+pub mod update_crank_rewards;
 pub mod update_funding;
+pub mod update_mark_price;
+pub mod set_mark_price_half_life;
+pub mod set_price_band;
+pub mod reset_circuit_breaker;
+pub mod initialize_user_market;
+pub mod set_open_interest_caps;
+pub mod initiate_global_settlement;
+pub mod close_out_position;
+pub mod claim_global_settlement;
+pub mod migrate_market;
+pub mod migrate_vault;
+pub mod audit_vault;
+pub mod migrate_user_account;
+pub mod migrate_position;
+pub mod migrate_user_market;
+pub mod create_competition;
+pub mod fund_competition;
+pub mod join_competition;
+pub mod finalize_competition;
+pub mod claim_competition_prize;
+pub mod set_fee_schedule;
+pub mod initialize_trading_schedule;
+pub mod update_trading_schedule;
+pub mod sample_settlement_price;
+pub mod finalize_settlement;
+pub mod settle_position;
+pub mod roll_position;
 pub mod create_referral_code;
 pub mod apply_referral_code;
 pub mod claim_referral_rewards;
+pub mod initialize_spread_market;
+pub mod update_spread_market;
+pub mod open_spread_position;
+pub mod close_spread_position;
+pub mod liquidate_spread_position;
+pub mod set_index_basket;
+pub mod set_contract_specs;
+pub mod set_risk_tiers;
+pub mod seize_margin_collateral;
+pub mod close_out_spread_position;
+pub mod claim_spread_settlement;
 
 pub use initialize_market::*;
+pub use initialize_futures_market::*;
 pub use initialize_user::*;
 pub use deposit_collateral::*;
 pub use withdraw_collateral::*;
+pub use initialize_collateral_registry::*;
+pub use add_collateral_asset::*;
+pub use update_collateral_asset::*;
+pub use initialize_margin_account::*;
 pub use open_position::*;
 pub use close_position::*;
+pub use increase_position::*;
+pub use reduce_position::*;
 pub use add_margin::*;
+pub use transfer_position::*;
+pub use initialize_position_mint::*;
+pub use wrap_position::*;
+pub use unwrap_position::*;
 pub use liquidate::*;
+pub use liquidate_many::*;
+pub use set_liquidation_mode::*;
+pub use start_liquidation_auction::*;
+pub use take_over_position::*;
+pub use cancel_liquidation_auction::*;
+pub use place_trigger_order::*;
+pub use cancel_trigger_order::*;
+pub use execute_trigger_order::*;
+pub use reap_trigger_order::*;
+pub use initialize_crank_rewards::*;
+pub use update_crank_rewards::*;
 pub use update_funding::*;
+pub use update_mark_price::*;
+pub use set_mark_price_half_life::*;
+pub use set_price_band::*;
+pub use reset_circuit_breaker::*;
+pub use initialize_user_market::*;
+pub use set_open_interest_caps::*;
+pub use initiate_global_settlement::*;
+pub use close_out_position::*;
+pub use claim_global_settlement::*;
+pub use migrate_market::*;
+pub use migrate_vault::*;
+pub use audit_vault::*;
+pub use migrate_user_account::*;
+pub use migrate_position::*;
+pub use migrate_user_market::*;
+pub use create_competition::*;
+pub use fund_competition::*;
+pub use join_competition::*;
+pub use finalize_competition::*;
+pub use claim_competition_prize::*;
+pub use set_fee_schedule::*;
+pub use initialize_trading_schedule::*;
+pub use update_trading_schedule::*;
+pub use sample_settlement_price::*;
+pub use finalize_settlement::*;
+pub use settle_position::*;
+pub use roll_position::*;
 pub use create_referral_code::*;
 pub use apply_referral_code::*;
 pub use claim_referral_rewards::*;
+pub use initialize_spread_market::*;
+pub use update_spread_market::*;
+pub use open_spread_position::*;
+pub use close_spread_position::*;
+pub use liquidate_spread_position::*;
+pub use set_index_basket::*;
+pub use set_contract_specs::*;
+pub use set_risk_tiers::*;
+pub use seize_margin_collateral::*;
+pub use close_out_spread_position::*;
+pub use claim_spread_settlement::*;
@@ -1,6 +1,5 @@
 use anchor_lang::prelude::*;
-use pyth_sdk_solana::load_price_feed_from_account_info;
-use crate::state::{Market, Position, UserAccount, Side, PositionStatus, ExecutionSource};
+use crate::state::{CollateralRegistry, MarginAccount, TradingSchedule, Market, Position, PositionFill, UserAccount, Side, PositionStatus, ExecutionSource, MarketStatus, UserMarketAccount, is_within_deadline};
 use crate::errors::PerpsError;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -8,14 +7,19 @@ pub struct OpenPositionParams {
     pub side: u8,           // 0 = Long, 1 = Short
     pub size: u64,          // Position size in base units
     pub leverage: u32,      // Leverage (3 decimals: 10x = 10_000)
+    pub acceptable_price: u64,  // Worst fill accepted (6 decimals)
+    pub deadline: i64,      // Unix timestamp after which the open is rejected (0 = none)
 }
 
 #[derive(Accounts)]
 #[instruction(params: OpenPositionParams)]
 pub struct OpenPosition<'info> {
-    #[account(mut)]
     pub owner: Signer<'info>,
 
+    // Funds the position account's rent, so a program-derived owner holding data can open
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     #[account(
         mut,
         seeds = [b"user", owner.key().as_ref()],
@@ -26,17 +30,18 @@ pub struct OpenPosition<'info> {
 
     #[account(
         mut,
-        seeds = [b"market", market.collateral_mint.as_ref()],
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
         bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
         constraint = !market.is_paused @ PerpsError::MarketPaused
     )]
     pub market: Account<'info, Market>,
 
     #[account(
         init,
-        payer = owner,
+        payer = payer,
         space = Position::LEN,
-        seeds = [b"position", owner.key().as_ref(), market.key().as_ref(), &market.total_positions.to_le_bytes()],
+        seeds = [b"position", owner.key().as_ref(), market.key().as_ref(), &user_account.next_position_id.to_le_bytes()],
         bump
     )]
     pub position: Account<'info, Position>,
@@ -46,12 +51,49 @@ pub struct OpenPosition<'info> {
     pub pyth_price_feed: AccountInfo<'info>,
 
     pub system_program: Program<'info, System>,
+
+    // Optional multi-collateral accounts - needed when free balance is short.
+    // Price feeds for the user's collateral assets follow any index feeds in remaining accounts.
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump,
+    )]
+    pub collateral_registry: Option<Account<'info, CollateralRegistry>>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), owner.key().as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    // Required when the market follows a trading schedule
+    #[account(
+        seeds = [b"trading_schedule", market.key().as_ref()],
+        bump = trading_schedule.bump,
+    )]
+    pub trading_schedule: Option<Account<'info, TradingSchedule>>,
+
+    // Counts the owner's open interest in this market, created on their first trade
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_market: Account<'info, UserMarketAccount>,
 }
 
-pub fn handler(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result<()> {
+pub fn handler(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result<PositionFill> {
     let market = &mut ctx.accounts.market;
     let user_account = &mut ctx.accounts.user_account;
     let position = &mut ctx.accounts.position;
+    ctx.accounts.user_market.initialize_if_new(
+        ctx.accounts.owner.key(),
+        market.key(),
+        *ctx.bumps.get("user_market").unwrap(),
+    );
 
     // Validate leverage
     require!(
@@ -59,41 +101,53 @@ pub fn handler(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result
         PerpsError::ExcessiveLeverage
     );
 
-    // Get oracle price
-    let price_feed = load_price_feed_from_account_info(&ctx.accounts.pyth_price_feed)
-        .map_err(|_| PerpsError::InvalidOraclePrice)?;
+    // Sizes trade in whole lots and limits sit on ticks
+    require!(market.is_lot_multiple(params.size), PerpsError::SizeNotLotMultiple);
+    require!(market.is_on_tick(params.acceptable_price), PerpsError::PriceNotOnTick);
 
     let current_time = Clock::get()?.unix_timestamp;
-    let price_data = price_feed
-        .get_price_no_older_than(current_time, 60)
-        .ok_or(PerpsError::StaleOraclePrice)?;
-
-    require!(price_data.price > 0, PerpsError::InvalidOraclePrice);
-
-    // Convert Pyth price to our format (6 decimals)
-    let oracle_price = (price_data.price as u64)
-        .checked_mul(10u64.pow((6 + price_data.expo.abs() as u32) as u32))
-        .and_then(|p| p.checked_div(10u64.pow(price_data.expo.abs() as u32)))
-        .ok_or(PerpsError::MathOverflow)? as u64;
-
-    // Calculate required collateral
-    // notional = size * price / 1_000_000
-    // required_collateral = notional * initial_margin_ratio / 10000
-    let notional = (params.size as u128)
-        .checked_mul(oracle_price as u128)
-        .and_then(|v| v.checked_div(1_000_000))
-        .ok_or(PerpsError::MathOverflow)? as u64;
-
-    let required_collateral = (notional as u128)
-        .checked_mul(market.initial_margin_ratio as u128)
-        .and_then(|v| v.checked_div(10000))
-        .ok_or(PerpsError::MathOverflow)? as u64;
+    require!(
+        is_within_deadline(params.deadline, current_time),
+        PerpsError::DeadlineExceeded
+    );
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+
+    // Outside trading hours the market is reduce-only
+    if market.has_trading_schedule {
+        let schedule = ctx.accounts.trading_schedule
+            .as_ref()
+            .ok_or(PerpsError::TradingScheduleRequired)?;
+        require!(schedule.is_open(current_time), PerpsError::MarketClosed);
+    }
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
 
+    // Opens halt once the price leaves the band around the reference price; the
+    // breach is latched by the next crank, reduction or close that succeeds
+    require!(market.within_price_band(oracle_price), PerpsError::PriceBandBreached);
+    market.update_price_band(oracle_price, current_time);
+
+    // Calculate required collateral, at the risk tier of the position's notional
+    let (notional, required_collateral) = market.initial_margin(params.size, oracle_price)?;
+    require!(market.within_risk_tiers(notional), PerpsError::RiskTierExceeded);
     require!(
-        user_account.collateral_balance >= required_collateral,
-        PerpsError::InsufficientCollateral
+        params.leverage <= market.max_leverage_for(notional),
+        PerpsError::ExcessiveLeverage
     );
 
+    // Any shortfall in free balance is lent against multi-collateral at haircut value
+    let margin_credit = required_collateral.saturating_sub(user_account.collateral_balance);
+    if margin_credit > 0 {
+        let (Some(registry), Some(margin_account)) =
+            (&ctx.accounts.collateral_registry, &mut ctx.accounts.margin_account)
+        else {
+            return Err(PerpsError::InsufficientCollateral.into());
+        };
+        let (_, asset_feeds) = market.split_index_feeds(ctx.remaining_accounts)?;
+        margin_account.draw_credit(registry, asset_feeds, margin_credit, current_time)?;
+    }
+
     // Parse side
     let side = match params.side {
         0 => Side::Long,
@@ -101,6 +155,11 @@ pub fn handler(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result
         _ => return Err(PerpsError::InvalidPositionSide.into()),
     };
 
+    require!(
+        side.is_acceptable_price(oracle_price, params.acceptable_price),
+        PerpsError::SlippageExceeded
+    );
+
     // Check OI caps
     match side {
         Side::Long => require!(
@@ -112,10 +171,16 @@ pub fn handler(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result
             PerpsError::OpenInterestCapExceeded
         ),
     }
+    market.apply_open_interest_caps(
+        side,
+        params.size,
+        oracle_price,
+        &mut ctx.accounts.user_market,
+    )?;
 
     // Deduct collateral from user
     user_account.collateral_balance = user_account.collateral_balance
-        .checked_sub(required_collateral)
+        .checked_sub(required_collateral - margin_credit)
         .ok_or(PerpsError::MathOverflow)?;
 
     // Initialize position
@@ -132,7 +197,18 @@ pub fn handler(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result
     position.last_updated_at = current_time;
     position.status = PositionStatus::Open;
     position.execution_source = ExecutionSource::OrderBook;
+    position.position_id = user_account.next_position_id;
+    position.margin_credit = margin_credit;
+    position.is_wrapped = false;
+    position.settlement_claim = 0;
     position.bump = *ctx.bumps.get("position").unwrap();
+    position.version = Position::VERSION;
+
+    market.track_position(position)?;
+    market.debit_user_collateral(required_collateral - margin_credit);
+
+    // Every fill feeds the mark price
+    market.update_mark_price(oracle_price, current_time);
 
     // Update market OI
     match side {
@@ -163,6 +239,11 @@ pub fn handler(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result
     user_account.total_trades = user_account.total_trades
         .checked_add(1)
         .ok_or(PerpsError::MathOverflow)?;
+    ctx.accounts.user_market.record_trade(notional, 0);
+
+    user_account.next_position_id = user_account.next_position_id
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
 
     msg!(
         "Position opened: {} {} @ {} with {}x leverage",
@@ -172,5 +253,6 @@ pub fn handler(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result
         params.leverage / 1000
     );
 
-    Ok(())
+    let maintenance_margin_ratio = market.position_maintenance_margin(position)?;
+    position.fill(position.key(), oracle_price, params.size, maintenance_margin_ratio)
 }
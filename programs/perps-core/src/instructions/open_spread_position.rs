@@ -0,0 +1,275 @@
+use anchor_lang::prelude::*;
+use crate::state::{
+    Market, MarketStatus, PositionStatus, Side, SpreadLeg, SpreadMarket,
+    SpreadPosition, TradingSchedule, UserAccount, UserMarketAccount, is_within_deadline,
+};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct OpenSpreadPositionParams {
+    pub side: u8,                   // 0 = long leg A / short leg B, 1 = the reverse
+    pub size: u64,                  // Leg A base units; leg B follows the hedge ratio
+    pub acceptable_price_a: u64,    // Worst leg A fill accepted (6 decimals)
+    pub acceptable_price_b: u64,    // Worst leg B fill accepted (6 decimals)
+    pub deadline: i64,              // Unix timestamp after which the open is rejected (0 = none)
+}
+
+#[derive(Accounts)]
+pub struct OpenSpreadPosition<'info> {
+    pub owner: Signer<'info>,
+
+    // Funds the spread position account's rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.owner == owner.key()
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"spread_market", spread_market.leg_a_market.as_ref(), spread_market.leg_b_market.as_ref()],
+        bump = spread_market.bump,
+        constraint = spread_market.is_active @ PerpsError::SpreadMarketInactive
+    )]
+    pub spread_market: Box<Account<'info, SpreadMarket>>,
+
+    #[account(
+        mut,
+        address = spread_market.leg_a_market @ PerpsError::InvalidSpreadLeg,
+        constraint = leg_a_market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = !leg_a_market.is_paused @ PerpsError::MarketPaused
+    )]
+    pub leg_a_market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        address = spread_market.leg_b_market @ PerpsError::InvalidSpreadLeg,
+        constraint = leg_b_market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = !leg_b_market.is_paused @ PerpsError::MarketPaused
+    )]
+    pub leg_b_market: Box<Account<'info, Market>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = SpreadPosition::LEN,
+        seeds = [b"spread_position", owner.key().as_ref(), spread_market.key().as_ref(), &user_account.next_position_id.to_le_bytes()],
+        bump
+    )]
+    pub spread_position: Box<Account<'info, SpreadPosition>>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = leg_a_price_feed.key() == leg_a_market.pyth_price_feed)]
+    pub leg_a_price_feed: AccountInfo<'info>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = leg_b_price_feed.key() == leg_b_market.pyth_price_feed)]
+    pub leg_b_price_feed: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Required when the leg's market follows a trading schedule
+    #[account(
+        seeds = [b"trading_schedule", leg_a_market.key().as_ref()],
+        bump = leg_a_schedule.bump,
+    )]
+    pub leg_a_schedule: Option<Account<'info, TradingSchedule>>,
+
+    #[account(
+        seeds = [b"trading_schedule", leg_b_market.key().as_ref()],
+        bump = leg_b_schedule.bump,
+    )]
+    pub leg_b_schedule: Option<Account<'info, TradingSchedule>>,
+
+    // Count the owner's open interest in each leg, created on their first trade
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", leg_a_market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub leg_a_user_market: Box<Account<'info, UserMarketAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", leg_b_market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub leg_b_user_market: Box<Account<'info, UserMarketAccount>>,
+}
+
+pub fn handler(ctx: Context<OpenSpreadPosition>, params: OpenSpreadPositionParams) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        is_within_deadline(params.deadline, current_time),
+        PerpsError::DeadlineExceeded
+    );
+    require!(params.size > 0, PerpsError::PositionTooSmall);
+
+    let side = match params.side {
+        0 => Side::Long,
+        1 => Side::Short,
+        _ => return Err(PerpsError::InvalidPositionSide.into()),
+    };
+
+    let leg_b_size = ctx.accounts.spread_market.leg_b_size(params.size)?;
+    require!(leg_b_size > 0, PerpsError::PositionTooSmall);
+
+    // Each leg trades in its own market's lots and ticks
+    for (market, size, acceptable_price) in [
+        (&ctx.accounts.leg_a_market, params.size, params.acceptable_price_a),
+        (&ctx.accounts.leg_b_market, leg_b_size, params.acceptable_price_b),
+    ] {
+        require!(market.is_lot_multiple(size), PerpsError::SizeNotLotMultiple);
+        require!(market.is_on_tick(acceptable_price), PerpsError::PriceNotOnTick);
+    }
+
+    // Both legs must be tradable, or neither is opened
+    for (market, schedule) in [
+        (&ctx.accounts.leg_a_market, &ctx.accounts.leg_a_schedule),
+        (&ctx.accounts.leg_b_market, &ctx.accounts.leg_b_schedule),
+    ] {
+        require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+        if market.has_trading_schedule {
+            let schedule = schedule.as_ref().ok_or(PerpsError::TradingScheduleRequired)?;
+            require!(schedule.is_open(current_time), PerpsError::MarketClosed);
+        }
+    }
+
+    // Remaining accounts hold leg A's index feeds, then leg B's
+    let (_, leg_b_feeds) = ctx.accounts.leg_a_market.split_index_feeds(ctx.remaining_accounts)?;
+    let price_a = ctx.accounts.leg_a_market.index_price(&ctx.accounts.leg_a_price_feed, ctx.remaining_accounts, current_time)?;
+    let price_b = ctx.accounts.leg_b_market.index_price(&ctx.accounts.leg_b_price_feed, leg_b_feeds, current_time)?;
+
+    let leg_a_market = &mut ctx.accounts.leg_a_market;
+    let leg_b_market = &mut ctx.accounts.leg_b_market;
+    require!(
+        leg_a_market.within_price_band(price_a) && leg_b_market.within_price_band(price_b),
+        PerpsError::PriceBandBreached
+    );
+    leg_a_market.update_price_band(price_a, current_time);
+    leg_b_market.update_price_band(price_b, current_time);
+
+    let leg_a = SpreadLeg {
+        market: leg_a_market.key(),
+        side,
+        size: params.size,
+        entry_price: price_a,
+        last_funding_rate: leg_a_market.funding_rate,
+    };
+    let leg_b = SpreadLeg {
+        market: leg_b_market.key(),
+        side: side.opposite(),
+        size: leg_b_size,
+        entry_price: price_b,
+        last_funding_rate: leg_b_market.funding_rate,
+    };
+
+    require!(
+        leg_a.side.is_acceptable_price(price_a, params.acceptable_price_a)
+            && leg_b.side.is_acceptable_price(price_b, params.acceptable_price_b),
+        PerpsError::SlippageExceeded
+    );
+
+    // Each leg counts against its own market's caps
+    let owner = ctx.accounts.owner.key();
+    for (market, leg, user_market, bump) in [
+        (&mut *leg_a_market, &leg_a, &mut ctx.accounts.leg_a_user_market, *ctx.bumps.get("leg_a_user_market").unwrap()),
+        (&mut *leg_b_market, &leg_b, &mut ctx.accounts.leg_b_user_market, *ctx.bumps.get("leg_b_user_market").unwrap()),
+    ] {
+        user_market.initialize_if_new(owner, market.key(), bump);
+        let within_cap = match leg.side {
+            Side::Long => market.can_increase_long_oi(leg.size),
+            Side::Short => market.can_increase_short_oi(leg.size),
+        };
+        require!(within_cap, PerpsError::OpenInterestCapExceeded);
+        market.apply_open_interest_caps(leg.side, leg.size, leg.entry_price, user_market)?;
+    }
+
+    // Margined on the spread, not on the legs outright
+    let gross_notional = leg_a.notional_value()?
+        .checked_add(leg_b.notional_value()?)
+        .ok_or(PerpsError::MathOverflow)?;
+    let required_collateral = ctx.accounts.spread_market.initial_margin(gross_notional)?;
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.collateral_balance = user_account.collateral_balance
+        .checked_sub(required_collateral)
+        .ok_or(PerpsError::InsufficientCollateral)?;
+
+    let spread_position = &mut ctx.accounts.spread_position;
+    spread_position.owner = ctx.accounts.owner.key();
+    spread_position.spread_market = ctx.accounts.spread_market.key();
+    spread_position.side = side;
+    spread_position.leg_a = leg_a;
+    spread_position.leg_b = leg_b;
+    spread_position.collateral = required_collateral;
+    spread_position.realized_pnl = 0;
+    spread_position.opened_at = current_time;
+    spread_position.last_updated_at = current_time;
+    spread_position.status = PositionStatus::Open;
+    spread_position.position_id = user_account.next_position_id;
+    spread_position.bump = *ctx.bumps.get("spread_position").unwrap();
+    spread_position.version = SpreadPosition::VERSION;
+
+    // Collateral sits with leg A; leg B only carries exposure
+    leg_a_market.track_spread_leg(&leg_a, required_collateral)?;
+    leg_a_market.debit_user_collateral(required_collateral);
+    leg_b_market.track_spread_leg(&leg_b, 0)?;
+
+    for (market, leg) in [(&mut *leg_a_market, &leg_a), (&mut *leg_b_market, &leg_b)] {
+        market.update_mark_price(leg.entry_price, current_time);
+        match leg.side {
+            Side::Long => {
+                market.long_open_interest = market.long_open_interest
+                    .checked_add(leg.size)
+                    .ok_or(PerpsError::MathOverflow)?;
+            }
+            Side::Short => {
+                market.short_open_interest = market.short_open_interest
+                    .checked_add(leg.size)
+                    .ok_or(PerpsError::MathOverflow)?;
+            }
+        }
+        market.total_trades = market.total_trades
+            .checked_add(1)
+            .ok_or(PerpsError::MathOverflow)?;
+    }
+    ctx.accounts.leg_a_user_market.record_trade(leg_a.notional_value()?, 0);
+    ctx.accounts.leg_b_user_market.record_trade(leg_b.notional_value()?, 0);
+
+    user_account.total_positions = user_account.total_positions
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+    user_account.total_trades = user_account.total_trades
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+    user_account.next_position_id = user_account.next_position_id
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    let spread_market = &mut ctx.accounts.spread_market;
+    spread_market.open_spreads = spread_market.open_spreads
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    msg!(
+        "Spread opened: {} {} @ {} / {} @ {}, collateral={}",
+        if side == Side::Long { "LONG" } else { "SHORT" },
+        params.size,
+        price_a,
+        leg_b_size,
+        price_b,
+        required_collateral
+    );
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, Position, PositionStatus, TriggerKind, TriggerOrder};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TriggerOrderParams {
+    pub kind: u8,               // 0 = take-profit, 1 = stop-loss
+    pub trigger_price: u64,     // Index price that fires the order (6 decimals)
+    pub acceptable_price: u64,  // Worst fill accepted when it executes (6 decimals)
+}
+
+#[derive(Accounts)]
+#[instruction(params: TriggerOrderParams)]
+pub struct PlaceTriggerOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = TriggerOrder::LEN,
+        seeds = [b"trigger_order", position.key().as_ref(), &[params.kind]],
+        bump
+    )]
+    pub trigger_order: Account<'info, TriggerOrder>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<PlaceTriggerOrder>, params: TriggerOrderParams) -> Result<()> {
+    let market = &ctx.accounts.market;
+
+    let kind = match params.kind {
+        0 => TriggerKind::TakeProfit,
+        1 => TriggerKind::StopLoss,
+        _ => return Err(PerpsError::InvalidTriggerOrder.into()),
+    };
+    require!(params.trigger_price > 0, PerpsError::InvalidTriggerOrder);
+    require!(
+        market.is_on_tick(params.trigger_price) && market.is_on_tick(params.acceptable_price),
+        PerpsError::PriceNotOnTick
+    );
+
+    let trigger_order = &mut ctx.accounts.trigger_order;
+    trigger_order.owner = ctx.accounts.owner.key();
+    trigger_order.market = market.key();
+    trigger_order.position = ctx.accounts.position.key();
+    trigger_order.kind = kind;
+    trigger_order.trigger_price = params.trigger_price;
+    trigger_order.acceptable_price = params.acceptable_price;
+    trigger_order.created_at = Clock::get()?.unix_timestamp;
+    trigger_order.bump = *ctx.bumps.get("trigger_order").unwrap();
+    trigger_order.version = TriggerOrder::VERSION;
+
+    msg!(
+        "Trigger order placed: {:?} on {} at {}",
+        kind,
+        trigger_order.position,
+        params.trigger_price
+    );
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::state::{CrankRewards, Market, Position, PositionStatus, TriggerOrder, UserAccount};
+use crate::errors::PerpsError;
+
+/// Permissionless: closes a trigger order whose position was closed or changed hands
+/// without it, refunding the rent to the order's owner. Only orders left behind by a
+/// liquidation earn the crank reward: an owner can close or transfer their own position
+/// at will, so rewarding those would let them farm it from a second wallet.
+#[derive(Accounts)]
+pub struct ReapTriggerOrder<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(constraint = position.market == market.key())]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"trigger_order", position.key().as_ref(), &[trigger_order.kind as u8]],
+        bump = trigger_order.bump,
+        close = owner
+    )]
+    pub trigger_order: Account<'info, TriggerOrder>,
+
+    /// CHECK: Rent refund destination, must be the order's owner
+    #[account(mut, constraint = owner.key() == trigger_order.owner)]
+    pub owner: AccountInfo<'info>,
+
+    // Optional crank reward, paid into the keeper's free collateral
+    #[account(
+        mut,
+        seeds = [b"crank_rewards", market.key().as_ref()],
+        bump = crank_rewards.bump,
+    )]
+    pub crank_rewards: Option<Account<'info, CrankRewards>>,
+
+    // Owners reaping their own orders earn nothing, as cancelling them is free
+    #[account(
+        mut,
+        seeds = [b"user", keeper.key().as_ref()],
+        bump = keeper_user_account.bump,
+        constraint = keeper.key() != owner.key() @ PerpsError::Unauthorized,
+    )]
+    pub keeper_user_account: Option<Account<'info, UserAccount>>,
+}
+
+pub fn handler(ctx: Context<ReapTriggerOrder>) -> Result<()> {
+    let position = &ctx.accounts.position;
+    require!(
+        position.status != PositionStatus::Open || position.owner != ctx.accounts.trigger_order.owner,
+        PerpsError::TriggerOrderLive
+    );
+
+    // Only orders a liquidation left behind are rewarded
+    if let (PositionStatus::Liquidated, Some(crank_rewards), Some(keeper_user_account)) = (
+        position.status,
+        ctx.accounts.crank_rewards.as_deref_mut(),
+        ctx.accounts.keeper_user_account.as_deref_mut(),
+    ) {
+        let current_time = Clock::get()?.unix_timestamp;
+        crank_rewards.pay(&mut ctx.accounts.market, keeper_user_account, current_time);
+    }
+
+    msg!("Trigger order reaped: {} on {}", ctx.accounts.trigger_order.key(), position.key());
+    Ok(())
+}
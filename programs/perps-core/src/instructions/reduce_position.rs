@@ -0,0 +1,315 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::{Price, Rounding, Size};
+use crate::state::{Market, Position, PositionFill, Vault, UserAccount, Side, PositionStatus, ReferralCode, UserReferral, MarginAccount, MarketStatus, UserMarketAccount, Competition, record_competition_result, is_within_deadline};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ReducePositionParams {
+    pub size: u64,              // Size to close in base units (must leave a remainder)
+    pub acceptable_price: u64,  // Worst fill accepted (6 decimals)
+    pub deadline: i64,          // Unix timestamp after which the reduction is rejected (0 = none)
+}
+
+#[derive(Accounts)]
+pub struct ReducePosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Funds the user market account's rent, so a program-derived owner holding data can trade
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.owner == owner.key()
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == owner.key(),
+        constraint = user_token_account.mint == market.collateral_mint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    // Optional referral accounts - if user has a referral, include these
+    #[account(
+        mut,
+        seeds = [b"user_referral", owner.key().as_ref()],
+        bump = user_referral.bump,
+    )]
+    pub user_referral: Option<Account<'info, UserReferral>>,
+
+    #[account(
+        mut,
+        seeds = [b"referral_code", referral_code.code.as_ref()],
+        bump = referral_code.bump,
+    )]
+    pub referral_code: Option<Account<'info, ReferralCode>>,
+
+    // Required when the position drew margin credit against multi-collateral
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), owner.key().as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    // Counts the owner's open interest in this market, created if they have none yet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_market: Account<'info, UserMarketAccount>,
+
+    // Co-signs when the reduction filled a resting order, making the owner the maker
+    #[account(constraint = fill_authority.key() == market.fill_authority @ PerpsError::Unauthorized)]
+    pub fill_authority: Option<Signer<'info>>,
+
+    // Required while the owner is entered in a running competition, recording the reduction
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+}
+
+pub fn handler(ctx: Context<ReducePosition>, params: ReducePositionParams) -> Result<PositionFill> {
+    let market = &mut ctx.accounts.market;
+    let position = &mut ctx.accounts.position;
+    let user_account = &mut ctx.accounts.user_account;
+    ctx.accounts.user_market.initialize_if_new(
+        ctx.accounts.owner.key(),
+        market.key(),
+        *ctx.bumps.get("user_market").unwrap(),
+    );
+
+    // A full reduction is a close
+    require!(
+        params.size > 0 && params.size < position.size,
+        PerpsError::InvalidPositionReduction
+    );
+
+    // Sizes trade in whole lots and limits sit on ticks
+    require!(market.is_lot_multiple(params.size), PerpsError::SizeNotLotMultiple);
+    require!(market.is_on_tick(params.acceptable_price), PerpsError::PriceNotOnTick);
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        is_within_deadline(params.deadline, current_time),
+        PerpsError::DeadlineExceeded
+    );
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    // Reducing trades against the position's side
+    require!(
+        position.side.opposite().is_acceptable_price(oracle_price, params.acceptable_price),
+        PerpsError::SlippageExceeded
+    );
+
+    // PnL, funding and collateral are released pro rata to the closed size
+    let closed_size = Size(params.size);
+    let pnl = closed_size
+        .pnl(
+            Price(position.entry_price),
+            Price(oracle_price),
+            position.side == Side::Long,
+            Rounding::Down,
+        )
+        .map_err(PerpsError::from)?;
+
+    let funding_payment = position.funding_payment(params.size, market.funding_rate)?;
+
+    let released_collateral = closed_size
+        .pro_rata(position.collateral, Size(position.size), Rounding::Down)
+        .map_err(PerpsError::from)?;
+
+    // Calculate fees on the closed notional
+    let notional = Price(position.entry_price)
+        .notional(closed_size, Rounding::Up)
+        .map_err(PerpsError::from)?;
+    let is_maker = ctx.accounts.fill_authority.is_some();
+    let (base_fee, rebate) = market.fill_fee(notional, is_maker)?;
+
+    // Process referral if user has one
+    let (fee, referral_reward) = if let (Some(user_referral), Some(referral_code)) =
+        (&mut ctx.accounts.user_referral, &mut ctx.accounts.referral_code)
+    {
+        let referral_code_key = referral_code.key();
+        user_referral.process_trade_fee(referral_code_key, referral_code, base_fee, notional)?
+    } else {
+        (base_fee, 0)
+    };
+
+    // Final settlement amount for the closed portion
+    let total_pnl = pnl + funding_payment - fee as i64 + rebate as i64;
+    let settlement = perps_math::equity(released_collateral, total_pnl).map_err(PerpsError::from)?;
+
+    // Margin lent against multi-collateral is repaid before anything is paid out;
+    // what the settlement can't repay becomes debt against the pledged assets
+    let released_credit = closed_size
+        .pro_rata(position.margin_credit, Size(position.size), Rounding::Up)
+        .map_err(PerpsError::from)?;
+    let credit_repaid = if released_credit > 0 {
+        let margin_account = ctx.accounts.margin_account
+            .as_mut()
+            .ok_or(PerpsError::MarginCreditOutstanding)?;
+        margin_account.settle_credit(released_credit, settlement)
+    } else {
+        0
+    };
+    let payout = settlement - credit_repaid;
+
+    market.untrack_position(position)?;
+
+    // Band breaches are recorded here too; reductions stay allowed while reduce-only
+    market.update_price_band(oracle_price, current_time);
+
+    // Every fill feeds the mark price at the index price it settled at; a maker's
+    // acceptable price is only a bound, so it never moves the mark
+    market.update_mark_price(oracle_price, current_time);
+
+    // Update market OI
+    match position.side {
+        Side::Long => {
+            market.long_open_interest = market.long_open_interest
+                .saturating_sub(params.size);
+        }
+        Side::Short => {
+            market.short_open_interest = market.short_open_interest
+                .saturating_sub(params.size);
+        }
+    }
+    ctx.accounts.user_market.remove_open_interest(params.size);
+
+    // Add fee to insurance fund (minus referral reward which stays for claiming)
+    let insurance_fee = fee.saturating_sub(referral_reward);
+    market.insurance_fund = market.insurance_fund.saturating_add(insurance_fee);
+    market.referral_rewards = market.referral_rewards.saturating_add(referral_reward as i64);
+    if is_maker {
+        market.pay_maker_rebate(rebate);
+    } else {
+        market.collect_taker_fee(insurance_fee);
+    }
+
+    market.total_trades = market.total_trades.saturating_add(1);
+
+    // Shrink the position; entry price is unchanged by a reduction
+    position.size = position.size.saturating_sub(params.size);
+    position.collateral = position.collateral.saturating_sub(released_collateral);
+    position.margin_credit = position.margin_credit.saturating_sub(released_credit);
+    position.realized_pnl = position.realized_pnl.saturating_add(total_pnl);
+    position.last_updated_at = current_time;
+
+    market.track_position(position)?;
+
+    // Update user stats
+    user_account.realized_pnl = user_account.realized_pnl
+        .saturating_add(total_pnl);
+    user_account.total_trades = user_account.total_trades
+        .saturating_add(1);
+    let user_market = &mut ctx.accounts.user_market;
+    user_market.record_trade(notional, fee);
+    user_market.record_funding(funding_payment);
+    user_market.record_pnl(total_pnl);
+    record_competition_result(
+        ctx.accounts.competition.as_deref_mut(),
+        user_account,
+        &market.key(),
+        total_pnl,
+        released_collateral,
+        current_time,
+    )?;
+
+    // Transfer settlement to user
+    if payout > 0 {
+        let market_key = market.key();
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, payout)?;
+        ctx.accounts.vault.record_outflow(payout);
+    }
+
+    msg!(
+        "Position reduced: size={}, remaining={}, PnL={}, Funding={}, Fee={}, Rebate={}, CreditRepaid={}, Settlement={}",
+        params.size,
+        position.size,
+        pnl,
+        funding_payment,
+        fee,
+        rebate,
+        credit_repaid,
+        payout
+    );
+
+    let maintenance_margin_ratio = market.position_maintenance_margin(position)?;
+    let mut fill = position.fill(position.key(), oracle_price, params.size, maintenance_margin_ratio)?;
+    fill.fee = fee;
+    fill.rebate = rebate;
+    fill.realized_pnl = total_pnl;
+    Ok(fill)
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::state::Market;
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct ResetCircuitBreaker<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    // Re-anchor the band at the current price and reopen the market
+    market.reset_reference_price(oracle_price, current_time);
+    market.circuit_breaker_tripped = false;
+
+    msg!("Circuit breaker reset: reference={}", oracle_price);
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{Market, MarketType, Position, PositionStatus, Side, TradingSchedule, UserAccount, Vault, ExecutionSource, MarketStatus, UserMarketAccount, Competition, record_competition_result, is_within_deadline};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RollPositionParams {
+    pub deadline: i64,          // Unix timestamp after which the roll is rejected (0 = none)
+}
+
+#[derive(Accounts)]
+pub struct RollPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.owner == owner.key()
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = market.market_type == MarketType::DatedFuture @ PerpsError::NotDatedFuture
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// The later-dated contract on the same underlying
+    #[account(
+        mut,
+        seeds = [b"market", target_market.collateral_mint.as_ref(), target_market.series_seed().as_ref()],
+        bump = target_market.bump,
+        constraint = target_market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = !target_market.is_paused @ PerpsError::MarketPaused,
+        constraint = target_market.market_type == MarketType::DatedFuture @ PerpsError::InvalidRollTarget,
+        constraint = target_market.collateral_mint == market.collateral_mint @ PerpsError::InvalidRollTarget,
+        constraint = target_market.pyth_price_feed == market.pyth_price_feed @ PerpsError::InvalidRollTarget,
+        constraint = target_market.expiry_timestamp > market.expiry_timestamp @ PerpsError::InvalidRollTarget
+    )]
+    pub target_market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Position::LEN,
+        seeds = [b"position", owner.key().as_ref(), target_market.key().as_ref(), &user_account.next_position_id.to_le_bytes()],
+        bump
+    )]
+    pub target_position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", target_market.key().as_ref()],
+        bump = target_vault.bump
+    )]
+    pub target_vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", target_market.key().as_ref()],
+        bump
+    )]
+    pub target_vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth price feed shared by both contracts, validated below
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    // Required when the target market follows a trading schedule
+    #[account(
+        seeds = [b"trading_schedule", target_market.key().as_ref()],
+        bump = trading_schedule.bump,
+    )]
+    pub trading_schedule: Option<Account<'info, TradingSchedule>>,
+
+    // Per-user open interest in each contract, created if the owner has none yet
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_market: Account<'info, UserMarketAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", target_market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub target_user_market: Account<'info, UserMarketAccount>,
+
+    // Required while the owner is entered in a running competition, recording the roll
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+}
+
+pub fn handler(ctx: Context<RollPosition>, params: RollPositionParams) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let target_market = &mut ctx.accounts.target_market;
+    let position = &mut ctx.accounts.position;
+    let target_position = &mut ctx.accounts.target_position;
+    let user_account = &mut ctx.accounts.user_account;
+    let user_market = &mut ctx.accounts.user_market;
+    let target_user_market = &mut ctx.accounts.target_user_market;
+    let owner = ctx.accounts.owner.key();
+    user_market.initialize_if_new(owner, market.key(), *ctx.bumps.get("user_market").unwrap());
+    target_user_market.initialize_if_new(
+        owner,
+        target_market.key(),
+        *ctx.bumps.get("target_user_market").unwrap(),
+    );
+
+    // Positions holding multi-collateral credit must be closed and reopened instead
+    require!(position.margin_credit == 0, PerpsError::MarginCreditOutstanding);
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        is_within_deadline(params.deadline, current_time),
+        PerpsError::DeadlineExceeded
+    );
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+    require!(!target_market.is_expired(current_time), PerpsError::InvalidRollTarget);
+
+    if target_market.has_trading_schedule {
+        let schedule = ctx.accounts.trading_schedule
+            .as_ref()
+            .ok_or(PerpsError::TradingScheduleRequired)?;
+        require!(schedule.is_open(current_time), PerpsError::MarketClosed);
+    }
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    // The new contract takes on exposure, so its band must be intact
+    require!(target_market.within_price_band(oracle_price), PerpsError::PriceBandBreached);
+    target_market.update_price_band(oracle_price, current_time);
+
+    // Close leg: pays the taker fee, futures carry no funding
+    let pnl = position.unrealized_pnl(oracle_price)?;
+    let (fee, _) = market.fill_fee(position.notional_value()?, false)?;
+    let total_pnl = pnl - fee as i64;
+    let equity = perps_math::equity(position.collateral, total_pnl).map_err(PerpsError::from)?;
+
+    // Open leg: the whole equity carries over and must meet the target's initial margin
+    let (notional, required_collateral) = target_market.initial_margin(position.size, oracle_price)?;
+    require!(equity >= required_collateral, PerpsError::InsufficientCollateral);
+
+    match position.side {
+        Side::Long => require!(
+            target_market.can_increase_long_oi(position.size),
+            PerpsError::OpenInterestCapExceeded
+        ),
+        Side::Short => require!(
+            target_market.can_increase_short_oi(position.size),
+            PerpsError::OpenInterestCapExceeded
+        ),
+    }
+    target_market.apply_open_interest_caps(
+        position.side,
+        position.size,
+        oracle_price,
+        target_user_market,
+    )?;
+
+    market.update_mark_price(oracle_price, current_time);
+    target_market.update_mark_price(oracle_price, current_time);
+
+    // Retire the expiring position
+    market.untrack_position(position)?;
+    match position.side {
+        Side::Long => {
+            market.long_open_interest = market.long_open_interest
+                .saturating_sub(position.size);
+        }
+        Side::Short => {
+            market.short_open_interest = market.short_open_interest
+                .saturating_sub(position.size);
+        }
+    }
+    user_market.remove_open_interest(position.size);
+    market.insurance_fund = market.insurance_fund.saturating_add(fee);
+    market.collect_taker_fee(fee);
+    market.total_trades = market.total_trades.saturating_add(1);
+
+    position.status = PositionStatus::Closed;
+    position.realized_pnl = total_pnl;
+    position.last_updated_at = current_time;
+
+    // Open the same exposure in the next contract
+    target_position.owner = ctx.accounts.owner.key();
+    target_position.market = target_market.key();
+    target_position.side = position.side;
+    target_position.size = position.size;
+    target_position.collateral = equity;
+    target_position.entry_price = oracle_price;
+    target_position.leverage = position.leverage;
+    target_position.realized_pnl = 0;
+    target_position.last_funding_payment = target_market.funding_rate;
+    target_position.opened_at = current_time;
+    target_position.last_updated_at = current_time;
+    target_position.status = PositionStatus::Open;
+    target_position.execution_source = ExecutionSource::OrderBook;
+    target_position.position_id = user_account.next_position_id;
+    target_position.margin_credit = 0;
+    target_position.is_wrapped = false;
+    target_position.settlement_claim = 0;
+    target_position.bump = *ctx.bumps.get("target_position").unwrap();
+    target_position.version = Position::VERSION;
+    target_market.track_position(target_position)?;
+
+    match position.side {
+        Side::Long => {
+            target_market.long_open_interest = target_market.long_open_interest
+                .checked_add(position.size)
+                .ok_or(PerpsError::MathOverflow)?;
+        }
+        Side::Short => {
+            target_market.short_open_interest = target_market.short_open_interest
+                .checked_add(position.size)
+                .ok_or(PerpsError::MathOverflow)?;
+        }
+    }
+    target_market.total_positions = target_market.total_positions
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+    target_market.total_trades = target_market.total_trades
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    user_account.realized_pnl = user_account.realized_pnl
+        .saturating_add(total_pnl);
+    user_account.total_positions = user_account.total_positions
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+    user_account.total_trades = user_account.total_trades
+        .checked_add(2)
+        .ok_or(PerpsError::MathOverflow)?;
+    user_account.next_position_id = user_account.next_position_id
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+    user_market.record_trade(position.notional_value()?, fee);
+    user_market.record_pnl(total_pnl);
+    user_market.record_close(total_pnl);
+    record_competition_result(
+        ctx.accounts.competition.as_deref_mut(),
+        user_account,
+        &market.key(),
+        total_pnl,
+        position.collateral,
+        current_time,
+    )?;
+    target_user_market.record_trade(notional, 0);
+
+    // Move the carried collateral to the target market's vault
+    if equity > 0 {
+        let market_key = market.key();
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.target_vault_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, equity)?;
+        ctx.accounts.vault.record_outflow(equity);
+        ctx.accounts.target_vault.record_inflow(equity);
+    }
+
+    msg!(
+        "Position rolled: {} {} @ {} from expiry {} to {}, PnL={}, Fee={}, Collateral={}",
+        if position.side == Side::Long { "LONG" } else { "SHORT" },
+        position.size,
+        oracle_price,
+        market.expiry_timestamp,
+        target_market.expiry_timestamp,
+        pnl,
+        fee,
+        equity
+    );
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::state::{FuturesSettlement, Market, MarketType, MarketStatus};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct SampleSettlementPrice<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = market.market_type == MarketType::DatedFuture @ PerpsError::NotDatedFuture
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"futures_settlement", market.key().as_ref()],
+        bump = futures_settlement.bump
+    )]
+    pub futures_settlement: Account<'info, FuturesSettlement>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<SampleSettlementPrice>) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let settlement = &mut ctx.accounts.futures_settlement;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // Samples are only taken inside the final window before expiry, at most one per second
+    require!(
+        current_time >= market.expiry_timestamp - settlement.settlement_window
+            && current_time < market.expiry_timestamp,
+        PerpsError::OutsideSettlementWindow
+    );
+    require!(
+        current_time > settlement.last_sample_time,
+        PerpsError::OutsideSettlementWindow
+    );
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    settlement.record_sample(oracle_price, current_time)?;
+
+    msg!(
+        "Settlement sample: price={}, samples={}",
+        oracle_price,
+        settlement.num_samples
+    );
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::{Bps, Rounding};
+use crate::state::{CollateralRegistry, Market, MarginAccount, Vault, load_collateral_price};
+use crate::errors::PerpsError;
+
+/// Repays margin debt in settlement tokens and takes pledged collateral worth the
+/// same at its haircut price, so the haircut is the seizer's discount. Debt left
+/// once every pledged balance is gone is written off against the insurance fund,
+/// up to what the fund holds; the uncovered rest remains owed by the account.
+#[derive(Accounts)]
+pub struct SeizeMarginCollateral<'info> {
+    #[account(mut)]
+    pub seizer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump
+    )]
+    pub collateral_registry: Account<'info, CollateralRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), margin_account.owner.as_ref()],
+        bump = margin_account.bump,
+        constraint = margin_account.debt > 0 @ PerpsError::NoMarginDebt
+    )]
+    pub margin_account: Account<'info, MarginAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", market.key().as_ref(), seizer_asset_token_account.mint.as_ref()],
+        bump
+    )]
+    pub asset_vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = seizer_token_account.owner == seizer.key(),
+        constraint = seizer_token_account.mint == market.collateral_mint
+    )]
+    pub seizer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = seizer_asset_token_account.owner == seizer.key()
+    )]
+    pub seizer_asset_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth price feed of the seized asset, validated below
+    pub asset_price_feed: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<SeizeMarginCollateral>, max_repay: u64) -> Result<()> {
+    let registry = &ctx.accounts.collateral_registry;
+    let margin_account = &mut ctx.accounts.margin_account;
+    let mint = ctx.accounts.seizer_asset_token_account.mint;
+
+    let index = registry
+        .find_asset(&mint)
+        .ok_or(PerpsError::CollateralAssetNotFound)?;
+    let asset = registry.assets[index];
+    require!(
+        ctx.accounts.asset_price_feed.key() == asset.pyth_price_feed,
+        PerpsError::InvalidOraclePrice
+    );
+
+    // Settlement value of one whole token after the haircut
+    let current_time = Clock::get()?.unix_timestamp;
+    let price = load_collateral_price(&ctx.accounts.asset_price_feed, current_time)?;
+    let haircut_price = Bps::from(asset.haircut_bps)
+        .complement()
+        .and_then(|haircut| haircut.of(price, Rounding::Down))
+        .map_err(PerpsError::from)?;
+    require!(haircut_price > 0, PerpsError::InvalidOraclePrice);
+
+    let unit = 10u64.pow(asset.decimals as u32);
+    let mut repaid = max_repay.min(margin_account.debt);
+    let seized = perps_math::mul_div(repaid, unit, haircut_price, Rounding::Down)
+        .map_err(PerpsError::from)?
+        .min(margin_account.balances[index]);
+
+    // Taking the whole balance only costs what it is worth
+    if seized == margin_account.balances[index] {
+        repaid = perps_math::mul_div(seized, haircut_price, unit, Rounding::Up)
+            .map_err(PerpsError::from)?
+            .min(repaid);
+    }
+    require!(repaid > 0 && seized > 0, PerpsError::NothingToSeize);
+
+    margin_account.balances[index] -= seized;
+    margin_account.debt -= repaid;
+
+    // Nothing is left to recover the rest from, so the insurance fund covers what
+    // it can; anything beyond it stays on the account as debt against future pledges
+    let mut written_off = 0;
+    if margin_account.is_empty() && margin_account.debt > 0 {
+        let market = &mut ctx.accounts.market;
+        written_off = margin_account.debt.min(market.insurance_fund);
+        market.insurance_fund -= written_off;
+        margin_account.debt -= written_off;
+    }
+
+    // Repayment from the seizer into the vault
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.seizer_token_account.to_account_info(),
+        to: ctx.accounts.vault_token_account.to_account_info(),
+        authority: ctx.accounts.seizer.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::transfer(cpi_ctx, repaid)?;
+    ctx.accounts.vault.record_inflow(repaid);
+
+    // Create vault signer seeds
+    let market_key = ctx.accounts.market.key();
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_seeds = &[
+        b"vault".as_ref(),
+        market_key.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = &[&vault_seeds[..]];
+
+    // Seized collateral from the asset vault to the seizer
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.asset_vault_token_account.to_account_info(),
+        to: ctx.accounts.seizer_asset_token_account.to_account_info(),
+        authority: ctx.accounts.vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::transfer(cpi_ctx, seized)?;
+
+    msg!(
+        "Margin collateral seized: owner={}, asset={}, seized={}, repaid={}, written off={}, debt left={}",
+        ctx.accounts.margin_account.owner,
+        mint,
+        seized,
+        repaid,
+        written_off,
+        ctx.accounts.margin_account.debt
+    );
+    Ok(())
+}
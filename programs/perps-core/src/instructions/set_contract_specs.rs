@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::Market;
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ContractSpecsParams {
+    pub contract_unit: String,      // Unit the commodity is quoted in, e.g. "bbl" (max 8 bytes)
+    pub contract_multiplier: u64,   // Base units in one contract, e.g. 1000 bbl = 1_000_000_000
+    pub lot_size: u64,              // Base units every traded size is a multiple of
+    pub tick_size: u64,             // Increment limit prices land on (6 decimals)
+}
+
+/// Set the market's contract specs. Positions opened before a lot size change keep
+/// their size; they can still be closed, and reduced in whole lots.
+#[derive(Accounts)]
+pub struct SetContractSpecs<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(ctx: Context<SetContractSpecs>, params: ContractSpecsParams) -> Result<()> {
+    require!(
+        !params.contract_unit.is_empty() && params.contract_unit.len() <= 8,
+        PerpsError::InvalidContractSpecs
+    );
+    require!(
+        Market::valid_contract_specs(params.contract_multiplier, params.lot_size, params.tick_size),
+        PerpsError::InvalidContractSpecs
+    );
+
+    let market = &mut ctx.accounts.market;
+    market.contract_unit = Market::commodity_from_str(&params.contract_unit);
+    market.contract_multiplier = params.contract_multiplier;
+    market.lot_size = params.lot_size;
+    market.tick_size = params.tick_size;
+
+    msg!(
+        "Contract specs set: 1 contract = {} base units of {}, lot={}, tick={}",
+        params.contract_multiplier,
+        params.contract_unit,
+        params.lot_size,
+        params.tick_size
+    );
+    Ok(())
+}
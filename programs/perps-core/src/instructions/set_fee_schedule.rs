@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::Market;
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FeeScheduleParams {
+    pub taker_fee: u32,             // Basis points
+    pub maker_fee: i32,             // Basis points, negative = rebate paid out of taker fees
+    pub fill_authority: Pubkey,     // Signer whose fills count as maker, default = none
+}
+
+#[derive(Accounts)]
+pub struct SetFeeSchedule<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(ctx: Context<SetFeeSchedule>, params: FeeScheduleParams) -> Result<()> {
+    require!(
+        Market::valid_fees(params.taker_fee, params.maker_fee),
+        PerpsError::InvalidMarketConfig
+    );
+
+    let market = &mut ctx.accounts.market;
+    market.taker_fee = params.taker_fee;
+    market.maker_fee = params.maker_fee;
+    market.fill_authority = params.fill_authority;
+
+    msg!(
+        "Fee schedule set: taker={} bps, maker={} bps, fill authority={}",
+        params.taker_fee,
+        params.maker_fee,
+        params.fill_authority
+    );
+    Ok(())
+}
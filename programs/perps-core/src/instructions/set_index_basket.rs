@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::state::{IndexComponent, Market};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct IndexBasketParams {
+    pub components: Vec<IndexComponent>,    // Feeds and weights, the first becoming the market's feed
+}
+
+/// Price the market off a weighted basket of feeds. The basket only changes while the
+/// market has no open interest, since it moves every position's PnL. The first feed is
+/// passed as `price_feed` and the rest as remaining accounts, in basket order.
+#[derive(Accounts)]
+pub struct SetIndexBasket<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: Pyth price feed of the first component, validated when the index is read
+    pub price_feed: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<SetIndexBasket>, params: IndexBasketParams) -> Result<()> {
+    let components = &params.components;
+    require!(IndexComponent::valid_basket(components), PerpsError::InvalidIndexBasket);
+
+    let market = &mut ctx.accounts.market;
+    require!(
+        market.long_open_interest == 0 && market.short_open_interest == 0,
+        PerpsError::IndexBasketLocked
+    );
+
+    market.index_components = Default::default();
+    market.index_components[..components.len()].copy_from_slice(components);
+    market.index_component_count = components.len() as u8;
+    market.pyth_price_feed = components[0].price_feed;
+
+    // Reading the new index checks every feed is live, and reseeds the prices that
+    // were tracking the old one
+    let current_time = Clock::get()?.unix_timestamp;
+    let index_price = market.index_price(&ctx.accounts.price_feed, ctx.remaining_accounts, current_time)?;
+    market.mark_price = index_price;
+    market.mark_price_updated_at = current_time;
+    market.reset_reference_price(index_price, current_time);
+
+    msg!(
+        "Index basket set: {} components, index price {}",
+        components.len(),
+        index_price
+    );
+    Ok(())
+}
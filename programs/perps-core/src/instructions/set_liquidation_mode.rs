@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::state::{LiquidationMode, Market};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LiquidationModeParams {
+    pub mode: LiquidationMode,
+    pub auction_floor_discount_bps: u16,
+    pub auction_cap_discount_bps: u16,
+    pub auction_duration: u32,      // Seconds
+}
+
+#[derive(Accounts)]
+pub struct SetLiquidationMode<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(ctx: Context<SetLiquidationMode>, params: LiquidationModeParams) -> Result<()> {
+    if params.mode == LiquidationMode::DutchAuction {
+        require!(
+            params.auction_floor_discount_bps <= params.auction_cap_discount_bps
+                && params.auction_cap_discount_bps <= Market::MAX_AUCTION_DISCOUNT_BPS
+                && params.auction_duration > 0,
+            PerpsError::InvalidAuctionParams
+        );
+    }
+
+    let market = &mut ctx.accounts.market;
+    market.liquidation_mode = params.mode;
+    market.auction_floor_discount_bps = params.auction_floor_discount_bps;
+    market.auction_cap_discount_bps = params.auction_cap_discount_bps;
+    market.auction_duration = params.auction_duration;
+
+    msg!(
+        "Liquidation mode set: auction={}, discount {}-{} bps over {}s",
+        params.mode == LiquidationMode::DutchAuction,
+        params.auction_floor_discount_bps,
+        params.auction_cap_discount_bps,
+        params.auction_duration
+    );
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::state::Market;
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct SetMarkPriceHalfLife<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(ctx: Context<SetMarkPriceHalfLife>, half_life: u32) -> Result<()> {
+    // A day is far beyond any sensible smoothing for liquidations
+    require!(half_life <= 86_400, PerpsError::InvalidMarketConfig);
+
+    let market = &mut ctx.accounts.market;
+    market.mark_price_half_life = half_life;
+
+    msg!("Mark price half-life set to {}s", half_life);
+    Ok(())
+}
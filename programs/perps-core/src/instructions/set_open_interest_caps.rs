@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::Market;
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct OpenInterestCapsParams {
+    pub max_open_interest_notional: u64,            // Per side, 6 decimals (0 = disabled)
+    pub max_user_open_interest_notional: u64,       // Per user, 6 decimals (0 = disabled)
+    pub max_open_interest_imbalance_notional: u64,  // |long - short|, 6 decimals (0 = disabled)
+}
+
+#[derive(Accounts)]
+pub struct SetOpenInterestCaps<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(ctx: Context<SetOpenInterestCaps>, params: OpenInterestCapsParams) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    market.max_open_interest_notional = params.max_open_interest_notional;
+    market.max_user_open_interest_notional = params.max_user_open_interest_notional;
+    market.max_open_interest_imbalance_notional = params.max_open_interest_imbalance_notional;
+
+    msg!(
+        "Open interest caps set: side={}, user={}, imbalance={}",
+        params.max_open_interest_notional,
+        params.max_user_open_interest_notional,
+        params.max_open_interest_imbalance_notional
+    );
+    Ok(())
+}
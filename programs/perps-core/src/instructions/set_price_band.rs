@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use crate::state::Market;
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct SetPriceBand<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(ctx: Context<SetPriceBand>, price_band_bps: u16) -> Result<()> {
+    require!(price_band_bps <= 10000, PerpsError::InvalidMarketConfig);
+
+    let market = &mut ctx.accounts.market;
+    market.price_band_bps = price_band_bps;
+
+    msg!("Price band set to {} bps", price_band_bps);
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, RiskTier};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RiskTiersParams {
+    pub tiers: Vec<RiskTier>,       // Ascending by notional, empty = flat market-wide ratios
+}
+
+/// Replace the market's risk tier table. Open positions are judged against the new
+/// table right away, so tightening it can make large positions liquidatable.
+#[derive(Accounts)]
+pub struct SetRiskTiers<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(ctx: Context<SetRiskTiers>, params: RiskTiersParams) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    require!(market.valid_risk_tiers(&params.tiers), PerpsError::InvalidRiskTiers);
+
+    market.risk_tiers = Default::default();
+    market.risk_tiers[..params.tiers.len()].copy_from_slice(&params.tiers);
+    market.risk_tier_count = params.tiers.len() as u8;
+
+    msg!(
+        "Risk tiers set: {} tiers, largest notional {}",
+        params.tiers.len(),
+        params.tiers.last().map_or(0, |tier| tier.max_notional)
+    );
+    Ok(())
+}
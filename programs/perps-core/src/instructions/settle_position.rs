@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{FuturesSettlement, Market, MarginAccount, Position, PositionStatus, Side, UserAccount, Vault, MarketStatus, Competition, record_competition_result, load_initialized_user_market};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct SettlePosition<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", position.owner.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"futures_settlement", market.key().as_ref()],
+        bump = futures_settlement.bump,
+        constraint = futures_settlement.is_finalized @ PerpsError::SettlementNotFinalized
+    )]
+    pub futures_settlement: Account<'info, FuturesSettlement>,
+
+    #[account(
+        mut,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token", market.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == position.owner @ PerpsError::Unauthorized,
+        constraint = owner_token_account.mint == market.collateral_mint
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    // Required when the position drew margin credit against multi-collateral
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), position.owner.as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    /// CHECK: The owner's user market PDA, counting their open interest. Only read if
+    /// initialized, since the keeper can't be made to create it for the owner.
+    #[account(
+        mut,
+        seeds = [b"user_market", market.key().as_ref(), position.owner.as_ref()],
+        bump,
+    )]
+    pub user_market: UncheckedAccount<'info>,
+
+    // Required while the owner is entered in a running competition, recording the settlement
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+}
+
+pub fn handler(ctx: Context<SettlePosition>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let position = &mut ctx.accounts.position;
+    let user_account = &mut ctx.accounts.user_account;
+    let settlement_price = ctx.accounts.futures_settlement.settlement_price;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // Expiry settlement is fee-free and futures carry no funding
+    let pnl = position.unrealized_pnl(settlement_price)?;
+    let settlement = perps_math::equity(position.collateral, pnl).map_err(PerpsError::from)?;
+
+    // Margin lent against multi-collateral is repaid before anything is paid out;
+    // what the settlement can't repay becomes debt against the pledged assets
+    let credit_repaid = if position.margin_credit > 0 {
+        let margin_account = ctx.accounts.margin_account
+            .as_mut()
+            .ok_or(PerpsError::MarginCreditOutstanding)?;
+        margin_account.settle_credit(position.margin_credit, settlement)
+    } else {
+        0
+    };
+    let payout = settlement - credit_repaid;
+
+    market.untrack_position(position)?;
+
+    // Update market OI
+    match position.side {
+        Side::Long => {
+            market.long_open_interest = market.long_open_interest
+                .saturating_sub(position.size);
+        }
+        Side::Short => {
+            market.short_open_interest = market.short_open_interest
+                .saturating_sub(position.size);
+        }
+    }
+    let mut user_market = load_initialized_user_market(&ctx.accounts.user_market)?;
+    if let Some(user_market) = user_market.as_mut() {
+        user_market.remove_open_interest(position.size);
+    }
+
+    position.status = PositionStatus::Closed;
+    position.margin_credit = 0;
+    position.realized_pnl = pnl;
+    position.last_updated_at = current_time;
+
+    user_account.realized_pnl = user_account.realized_pnl
+        .saturating_add(pnl);
+    if let Some(user_market) = user_market.as_mut() {
+        user_market.record_pnl(pnl);
+        user_market.record_close(pnl);
+        user_market.exit(&crate::ID)?;
+    }
+    record_competition_result(
+        ctx.accounts.competition.as_deref_mut(),
+        user_account,
+        &market.key(),
+        pnl,
+        position.collateral,
+        current_time,
+    )?;
+
+    // Transfer settlement to the position owner
+    if payout > 0 {
+        let market_key = market.key();
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            market_key.as_ref(),
+            &[vault_bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, payout)?;
+        ctx.accounts.vault.record_outflow(payout);
+    }
+
+    msg!(
+        "Position settled: price={}, PnL={}, CreditRepaid={}, Settlement={}",
+        settlement_price,
+        pnl,
+        credit_repaid,
+        payout
+    );
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use crate::state::{CollateralRegistry, LiquidationAuction, LiquidationMode, MarginAccount, Market, MarketStatus, Position, PositionStatus, unbacked_credit};
+use crate::errors::PerpsError;
+
+/// Permissionless: anyone may start an auction on a liquidatable position
+#[derive(Accounts)]
+pub struct StartLiquidationAuction<'info> {
+    #[account(mut)]
+    pub starter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = market.liquidation_mode == LiquidationMode::DutchAuction @ PerpsError::WrongLiquidationMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        init,
+        payer = starter,
+        space = LiquidationAuction::LEN,
+        seeds = [b"liquidation_auction", position.key().as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, LiquidationAuction>,
+
+    /// CHECK: Pyth price feed
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Values the pledged collateral behind margin credit; required with the margin
+    // account when the position drew any, with the asset feeds after the index feeds
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump,
+    )]
+    pub collateral_registry: Option<Account<'info, CollateralRegistry>>,
+
+    #[account(
+        seeds = [b"margin_account", market.key().as_ref(), position.owner.as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+}
+
+pub fn handler(ctx: Context<StartLiquidationAuction>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let position = &ctx.accounts.position;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    // Same eligibility as a fixed liquidation: judged on the mark price
+    market.update_mark_price(oracle_price, current_time);
+    market.update_price_band(oracle_price, current_time);
+    let (_, asset_feeds) = market.split_index_feeds(ctx.remaining_accounts)?;
+    let unbacked = unbacked_credit(
+        position,
+        ctx.accounts.collateral_registry.as_deref(),
+        ctx.accounts.margin_account.as_deref(),
+        asset_feeds,
+        current_time,
+    )?;
+    require!(
+        position.is_backed_liquidatable(market.mark_price, market.position_maintenance_margin(position)?, unbacked)?,
+        PerpsError::NotLiquidatable
+    );
+
+    let auction = &mut ctx.accounts.auction;
+    auction.market = market.key();
+    auction.position = position.key();
+    auction.starter = ctx.accounts.starter.key();
+    auction.started_at = current_time;
+    auction.bump = *ctx.bumps.get("auction").unwrap();
+    auction.version = LiquidationAuction::VERSION;
+
+    msg!(
+        "Liquidation auction started: position={}, discount {}-{} bps over {}s",
+        position.key(),
+        market.auction_floor_discount_bps,
+        market.auction_cap_discount_bps,
+        market.auction_duration
+    );
+    Ok(())
+}
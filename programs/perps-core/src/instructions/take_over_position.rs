@@ -0,0 +1,257 @@
+use anchor_lang::prelude::*;
+use crate::state::{LiquidationAuction, LiquidationMode, MarginAccount, Market, MarketStatus, Position, PositionStatus, TradingSchedule, UserAccount, UserMarketAccount, Competition, CollateralRegistry, record_competition_result, unbacked_credit, load_initialized_user_market, move_user_open_interest};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TakeOverPositionParams {
+    pub min_discount_bps: u16,  // Reject if the auction has not reached this discount yet
+}
+
+/// Fills a liquidation auction: the liquidator becomes the position's owner at the
+/// auction price and posts fresh initial margin from their free balance.
+#[derive(Accounts)]
+pub struct TakeOverPosition<'info> {
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user", liquidator.key().as_ref()],
+        bump = liquidator_user_account.bump,
+        constraint = liquidator_user_account.owner == liquidator.key()
+    )]
+    pub liquidator_user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"user", position.owner.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = market.liquidation_mode == LiquidationMode::DutchAuction @ PerpsError::WrongLiquidationMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = position.owner != liquidator.key() @ PerpsError::Unauthorized
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidation_auction", position.key().as_ref()],
+        bump = auction.bump,
+        close = starter
+    )]
+    pub auction: Account<'info, LiquidationAuction>,
+
+    /// CHECK: Rent refund destination, must be the auction's starter
+    #[account(mut, constraint = starter.key() == auction.starter)]
+    pub starter: AccountInfo<'info>,
+
+    /// CHECK: Pyth price feed
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Required when the market follows a trading schedule, as the liquidator opens exposure
+    #[account(
+        seeds = [b"trading_schedule", market.key().as_ref()],
+        bump = trading_schedule.bump,
+    )]
+    pub trading_schedule: Option<Account<'info, TradingSchedule>>,
+
+    // Required when the position drew margin credit against multi-collateral
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), position.owner.as_ref()],
+        bump = margin_account.bump,
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    // Values the pledged collateral behind margin credit; required with the margin
+    // account when the position drew any, with the asset feeds after the index feeds
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump,
+    )]
+    pub collateral_registry: Option<Account<'info, CollateralRegistry>>,
+
+    /// CHECK: The owner's user market PDA, counting their open interest. Only read if
+    /// initialized, since the liquidator can't be made to create it for the owner.
+    #[account(
+        mut,
+        seeds = [b"user_market", market.key().as_ref(), position.owner.as_ref()],
+        bump,
+    )]
+    pub user_market: UncheckedAccount<'info>,
+
+    // Per-user open interest moves to the liquidator, whose account is created if needed
+    #[account(
+        init_if_needed,
+        payer = liquidator,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), liquidator.key().as_ref()],
+        bump,
+    )]
+    pub liquidator_user_market: Account<'info, UserMarketAccount>,
+
+    // Required while the owner is entered in a running competition, recording the liquidation
+    #[account(
+        mut,
+        seeds = [b"competition", competition.authority.as_ref(), &competition.id.to_le_bytes()],
+        bump = competition.bump,
+    )]
+    pub competition: Option<Box<Account<'info, Competition>>>,
+}
+
+pub fn handler(ctx: Context<TakeOverPosition>, params: TakeOverPositionParams) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let position = &mut ctx.accounts.position;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(!market.is_expired(current_time), PerpsError::MarketExpired);
+
+    if market.has_trading_schedule {
+        let schedule = ctx.accounts.trading_schedule
+            .as_ref()
+            .ok_or(PerpsError::TradingScheduleRequired)?;
+        require!(schedule.is_open(current_time), PerpsError::MarketClosed);
+    }
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    market.update_mark_price(oracle_price, current_time);
+    market.update_price_band(oracle_price, current_time);
+    let (_, asset_feeds) = market.split_index_feeds(ctx.remaining_accounts)?;
+    let unbacked = unbacked_credit(
+        position,
+        ctx.accounts.collateral_registry.as_deref(),
+        ctx.accounts.margin_account.as_deref(),
+        asset_feeds,
+        current_time,
+    )?;
+    require!(
+        position.is_backed_liquidatable(market.mark_price, market.position_maintenance_margin(position)?, unbacked)?,
+        PerpsError::NotLiquidatable
+    );
+
+    let discount_bps = market.auction_discount_bps(ctx.accounts.auction.started_at, current_time);
+    require!(discount_bps >= params.min_discount_bps, PerpsError::SlippageExceeded);
+    let take_over_price = LiquidationAuction::take_over_price(position.side, oracle_price, discount_bps)?;
+
+    // The previous owner is closed out at the auction price
+    let pnl = position.unrealized_pnl(take_over_price)?;
+    let funding_payment = position.funding_payment(position.size, market.funding_rate)?;
+    let total_pnl = pnl + funding_payment;
+    let equity = perps_math::equity(position.collateral, total_pnl).map_err(PerpsError::from)?;
+
+    // Margin lent against multi-collateral is repaid first; any shortfall becomes
+    // debt against the pledged assets
+    let credit_repaid = if position.margin_credit > 0 {
+        let margin_account = ctx.accounts.margin_account
+            .as_mut()
+            .ok_or(PerpsError::MarginCreditOutstanding)?;
+        margin_account.settle_credit(position.margin_credit, equity)
+    } else {
+        0
+    };
+
+    // As with a fixed liquidation the owner forfeits what is left; the discount is
+    // the liquidator's reward, so all of it goes to the insurance fund
+    let to_insurance = equity - credit_repaid;
+    market.insurance_fund = market.insurance_fund.saturating_add(to_insurance);
+
+    market.untrack_position(position)?;
+
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.realized_pnl = user_account.realized_pnl.saturating_add(total_pnl);
+    let mut user_market = load_initialized_user_market(&ctx.accounts.user_market)?;
+    if let Some(user_market) = user_market.as_deref_mut() {
+        user_market.record_funding(funding_payment);
+        user_market.record_pnl(total_pnl);
+        user_market.record_liquidation();
+    }
+    record_competition_result(
+        ctx.accounts.competition.as_deref_mut(),
+        user_account,
+        &market.key(),
+        total_pnl,
+        position.collateral,
+        current_time,
+    )?;
+
+    // The liquidator posts initial margin on the position at the oracle price
+    let (notional, required_collateral) = market.initial_margin(position.size, oracle_price)?;
+    require!(market.within_risk_tiers(notional), PerpsError::RiskTierExceeded);
+
+    let liquidator_user_account = &mut ctx.accounts.liquidator_user_account;
+    liquidator_user_account.collateral_balance = liquidator_user_account.collateral_balance
+        .checked_sub(required_collateral)
+        .ok_or(PerpsError::InsufficientCollateral)?;
+    liquidator_user_account.total_positions = liquidator_user_account.total_positions
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+    liquidator_user_account.total_trades = liquidator_user_account.total_trades
+        .checked_add(1)
+        .ok_or(PerpsError::MathOverflow)?;
+
+    let liquidator_user_market = &mut ctx.accounts.liquidator_user_market;
+    liquidator_user_market.initialize_if_new(
+        ctx.accounts.liquidator.key(),
+        market.key(),
+        *ctx.bumps.get("liquidator_user_market").unwrap(),
+    );
+    move_user_open_interest(
+        market,
+        position,
+        user_market.as_deref_mut(),
+        liquidator_user_market,
+    )?;
+    if let Some(user_market) = user_market {
+        user_market.exit(&crate::ID)?;
+    }
+
+    // Open interest is unchanged: the same exposure changes hands.
+    // A wrapped position's token no longer unwraps anything.
+    let previous_owner = position.owner;
+    position.owner = ctx.accounts.liquidator.key();
+    position.entry_price = take_over_price;
+    position.collateral = required_collateral;
+    position.leverage = 10_000_000 / market.initial_margin_ratio_for(notional).max(1);
+    position.realized_pnl = 0;
+    position.last_funding_payment = market.funding_rate;
+    position.margin_credit = 0;
+    position.is_wrapped = false;
+    position.opened_at = current_time;
+    position.last_updated_at = current_time;
+
+    market.track_position(position)?;
+    market.debit_user_collateral(required_collateral);
+    liquidator_user_market.record_trade(notional, 0);
+
+    market.total_trades = market.total_trades.saturating_add(1);
+
+    msg!(
+        "Position taken over: {} from {} by {} @ {} ({} bps discount), insurance={}",
+        position.key(),
+        previous_owner,
+        position.owner,
+        take_over_price,
+        discount_bps,
+        to_insurance
+    );
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, Position, PositionStatus, UserAccount, UserMarketAccount, move_user_open_interest};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct TransferPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    /// CHECK: Receiving wallet, must have a user account
+    pub new_owner: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"user", new_owner.key().as_ref()],
+        bump = new_owner_user_account.bump,
+        constraint = new_owner_user_account.owner == new_owner.key()
+    )]
+    pub new_owner_user_account: Account<'info, UserAccount>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = position.market == market.key()
+    )]
+    pub market: Account<'info, Market>,
+
+    // Per-user open interest moves with the position; the owner pays for either account
+    // not created yet
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_market: Account<'info, UserMarketAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), new_owner.key().as_ref()],
+        bump,
+    )]
+    pub new_owner_user_market: Account<'info, UserMarketAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<TransferPosition>) -> Result<()> {
+    let position = &mut ctx.accounts.position;
+
+    // Credit is owed by the sender's margin account and cannot follow the position
+    require!(position.margin_credit == 0, PerpsError::MarginCreditOutstanding);
+
+    let market_key = ctx.accounts.market.key();
+    ctx.accounts.user_market.initialize_if_new(
+        ctx.accounts.owner.key(),
+        market_key,
+        *ctx.bumps.get("user_market").unwrap(),
+    );
+    ctx.accounts.new_owner_user_market.initialize_if_new(
+        ctx.accounts.new_owner.key(),
+        market_key,
+        *ctx.bumps.get("new_owner_user_market").unwrap(),
+    );
+    move_user_open_interest(
+        &ctx.accounts.market,
+        position,
+        Some(&mut ctx.accounts.user_market),
+        &mut ctx.accounts.new_owner_user_market,
+    )?;
+
+    let previous_owner = position.owner;
+    position.owner = ctx.accounts.new_owner.key();
+    position.last_updated_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Position transferred: {} from {} to {}",
+        position.key(),
+        previous_owner,
+        position.owner
+    );
+
+    Ok(())
+}
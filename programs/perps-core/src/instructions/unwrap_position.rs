@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use crate::state::{Market, Position, PositionStatus, UserAccount, UserMarketAccount, load_initialized_user_market, move_user_open_interest};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct UnwrapPosition<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [b"user", holder.key().as_ref()],
+        bump = holder_user_account.bump,
+        constraint = holder_user_account.owner == holder.key()
+    )]
+    pub holder_user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        constraint = position.status == PositionStatus::Open || position.settlement_claim > 0
+            @ PerpsError::PositionAlreadyClosed,
+        constraint = position.is_wrapped @ PerpsError::PositionNotWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"position_mint", position.key().as_ref()],
+        bump
+    )]
+    pub position_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = holder_token_account.owner == holder.key() @ PerpsError::Unauthorized,
+        constraint = holder_token_account.mint == position_mint.key(),
+        constraint = holder_token_account.amount == 1 @ PerpsError::Unauthorized
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = position.market == market.key()
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: The previous owner's user market PDA, counting their open interest. Only read
+    /// if initialized, since the holder can't be made to create it for them.
+    #[account(
+        mut,
+        seeds = [b"user_market", market.key().as_ref(), position.owner.as_ref()],
+        bump,
+    )]
+    pub previous_owner_user_market: UncheckedAccount<'info>,
+
+    // Per-user open interest moves to the holder, whose account is created if needed
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = UserMarketAccount::LEN,
+        seeds = [b"user_market", market.key().as_ref(), holder.key().as_ref()],
+        bump,
+    )]
+    pub holder_user_market: Account<'info, UserMarketAccount>,
+}
+
+pub fn handler(ctx: Context<UnwrapPosition>) -> Result<()> {
+    let cpi_accounts = Burn {
+        mint: ctx.accounts.position_mint.to_account_info(),
+        from: ctx.accounts.holder_token_account.to_account_info(),
+        authority: ctx.accounts.holder.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::burn(cpi_ctx, 1)?;
+
+    // Whoever held the token now owns the position outright
+    let position = &mut ctx.accounts.position;
+    ctx.accounts.holder_user_market.initialize_if_new(
+        ctx.accounts.holder.key(),
+        ctx.accounts.market.key(),
+        *ctx.bumps.get("holder_user_market").unwrap(),
+    );
+    if position.status == PositionStatus::Open {
+        let mut previous_owner_user_market =
+            load_initialized_user_market(&ctx.accounts.previous_owner_user_market)?;
+        move_user_open_interest(
+            &ctx.accounts.market,
+            position,
+            previous_owner_user_market.as_deref_mut(),
+            &mut ctx.accounts.holder_user_market,
+        )?;
+        if let Some(previous_owner_user_market) = previous_owner_user_market {
+            previous_owner_user_market.exit(&crate::ID)?;
+        }
+    }
+
+    position.owner = ctx.accounts.holder.key();
+    position.is_wrapped = false;
+    position.last_updated_at = Clock::get()?.unix_timestamp;
+
+    msg!("Position unwrapped: {} to {}", position.key(), position.owner);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::CollateralRegistry;
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateCollateralAssetParams {
+    pub mint: Pubkey,
+    pub pyth_price_feed: Pubkey,
+    pub haircut_bps: u16,
+    pub is_active: bool,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCollateralAsset<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_registry", collateral_registry.market.as_ref()],
+        bump = collateral_registry.bump,
+        constraint = collateral_registry.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub collateral_registry: Account<'info, CollateralRegistry>,
+}
+
+pub fn handler(ctx: Context<UpdateCollateralAsset>, params: UpdateCollateralAssetParams) -> Result<()> {
+    require!(
+        params.haircut_bps <= CollateralRegistry::MAX_HAIRCUT_BPS,
+        PerpsError::InvalidMarketConfig
+    );
+
+    let registry = &mut ctx.accounts.collateral_registry;
+    let index = registry
+        .find_asset(&params.mint)
+        .ok_or(PerpsError::CollateralAssetNotFound)?;
+
+    let asset = &mut registry.assets[index];
+    asset.pyth_price_feed = params.pyth_price_feed;
+    asset.haircut_bps = params.haircut_bps;
+    asset.is_active = params.is_active;
+
+    msg!(
+        "Collateral asset updated: {} haircut={}bps active={}",
+        params.mint,
+        params.haircut_bps,
+        params.is_active
+    );
+    Ok(())
+}
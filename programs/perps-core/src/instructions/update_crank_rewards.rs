@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::{CrankRewards, Market};
+use crate::errors::PerpsError;
+use crate::instructions::initialize_crank_rewards::CrankRewardsParams;
+
+#[derive(Accounts)]
+pub struct UpdateCrankRewards<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"crank_rewards", market.key().as_ref()],
+        bump = crank_rewards.bump
+    )]
+    pub crank_rewards: Account<'info, CrankRewards>,
+}
+
+pub fn handler(ctx: Context<UpdateCrankRewards>, params: CrankRewardsParams) -> Result<()> {
+    let crank_rewards = &mut ctx.accounts.crank_rewards;
+    crank_rewards.reward_per_crank = params.reward_per_crank;
+    crank_rewards.max_rewards_per_window = params.max_rewards_per_window;
+
+    msg!(
+        "Crank rewards updated: {} per crank, {} per window",
+        params.reward_per_crank,
+        params.max_rewards_per_window
+    );
+    Ok(())
+}
@@ -1,6 +1,5 @@
 use anchor_lang::prelude::*;
-use pyth_sdk_solana::load_price_feed_from_account_info;
-use crate::state::Market;
+use crate::state::{CrankRewards, Market, MarketType, TradingSchedule, MarketStatus, UserAccount};
 use crate::errors::PerpsError;
 
 #[derive(Accounts)]
@@ -10,14 +9,38 @@ pub struct UpdateFunding<'info> {
 
     #[account(
         mut,
-        seeds = [b"market", market.collateral_mint.as_ref()],
-        bump = market.bump
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement,
+        constraint = market.market_type == MarketType::Perpetual @ PerpsError::NotPerpetual
     )]
     pub market: Account<'info, Market>,
 
     /// CHECK: Pyth price feed
     #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
     pub pyth_price_feed: AccountInfo<'info>,
+
+    // Required when the market follows a trading schedule
+    #[account(
+        seeds = [b"trading_schedule", market.key().as_ref()],
+        bump = trading_schedule.bump,
+    )]
+    pub trading_schedule: Option<Account<'info, TradingSchedule>>,
+
+    // Optional crank reward, paid into the keeper's free collateral
+    #[account(
+        mut,
+        seeds = [b"crank_rewards", market.key().as_ref()],
+        bump = crank_rewards.bump,
+    )]
+    pub crank_rewards: Option<Account<'info, CrankRewards>>,
+
+    #[account(
+        mut,
+        seeds = [b"user", keeper.key().as_ref()],
+        bump = keeper_user_account.bump,
+    )]
+    pub keeper_user_account: Option<Account<'info, UserAccount>>,
 }
 
 pub fn handler(ctx: Context<UpdateFunding>) -> Result<()> {
@@ -31,20 +54,23 @@ pub fn handler(ctx: Context<UpdateFunding>) -> Result<()> {
         PerpsError::InvalidMarketConfig
     );
 
-    // Get oracle price
-    let price_feed = load_price_feed_from_account_info(&ctx.accounts.pyth_price_feed)
-        .map_err(|_| PerpsError::InvalidOraclePrice)?;
+    // Funding does not accrue while the market is closed; skip the interval
+    if market.has_trading_schedule {
+        let schedule = ctx.accounts.trading_schedule
+            .as_ref()
+            .ok_or(PerpsError::TradingScheduleRequired)?;
+        if !schedule.is_open(current_time) {
+            market.last_funding_time = current_time;
+            msg!("Funding paused: market closed at {}", current_time);
+            return Ok(());
+        }
+    }
 
-    let price_data = price_feed
-        .get_price_no_older_than(current_time, 60)
-        .ok_or(PerpsError::StaleOraclePrice)?;
-
-    require!(price_data.price > 0, PerpsError::InvalidOraclePrice);
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
 
-    let oracle_price = (price_data.price as u64)
-        .checked_mul(10u64.pow((6 + price_data.expo.abs() as u32) as u32))
-        .and_then(|p| p.checked_div(10u64.pow(price_data.expo.abs() as u32)))
-        .ok_or(PerpsError::MathOverflow)? as u64;
+    market.update_mark_price(oracle_price, current_time);
+    market.update_price_band(oracle_price, current_time);
 
     // Calculate funding rate based on OI imbalance
     // Positive = longs pay shorts, Negative = shorts pay longs
@@ -66,6 +92,13 @@ pub fn handler(ctx: Context<UpdateFunding>) -> Result<()> {
     market.funding_rate = market.funding_rate.saturating_add(funding_rate);
     market.last_funding_time = current_time;
 
+    // Only an interval that accrued funding is rewarded, not one skipped while closed
+    if let (Some(crank_rewards), Some(keeper_user_account)) =
+        (ctx.accounts.crank_rewards.as_deref_mut(), ctx.accounts.keeper_user_account.as_deref_mut())
+    {
+        crank_rewards.pay(market, keeper_user_account, current_time);
+    }
+
     msg!(
         "Funding updated: rate={}, long_oi={}, short_oi={}, price={}",
         funding_rate,
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, MarketStatus};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct UpdateMarkPrice<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.status == MarketStatus::Active @ PerpsError::MarketInGlobalSettlement
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: Pyth price feed, validated below
+    #[account(constraint = pyth_price_feed.key() == market.pyth_price_feed)]
+    pub pyth_price_feed: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<UpdateMarkPrice>) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // Get oracle price
+    let oracle_price = market.index_price(&ctx.accounts.pyth_price_feed, ctx.remaining_accounts, current_time)?;
+
+    market.update_mark_price(oracle_price, current_time);
+    if !market.update_price_band(oracle_price, current_time) {
+        msg!("Circuit breaker tripped: reference={}, price={}", market.reference_price, oracle_price);
+    }
+
+    msg!("Mark price updated: oracle={}, mark={}", oracle_price, market.mark_price);
+
+    Ok(())
+}
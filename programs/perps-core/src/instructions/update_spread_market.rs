@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, SpreadMarket};
+use crate::errors::PerpsError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateSpreadMarketParams {
+    pub initial_margin_ratio: u32,
+    pub maintenance_margin_ratio: u32,
+    pub is_active: bool,
+}
+
+/// Retune spread margins as the spread's volatility changes, or stop new opens.
+/// Margins are re-checked against the legs' current requirements.
+#[derive(Accounts)]
+pub struct UpdateSpreadMarket<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"spread_market", spread_market.leg_a_market.as_ref(), spread_market.leg_b_market.as_ref()],
+        bump = spread_market.bump,
+        constraint = spread_market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub spread_market: Account<'info, SpreadMarket>,
+
+    #[account(address = spread_market.leg_a_market @ PerpsError::InvalidSpreadLeg)]
+    pub leg_a_market: Box<Account<'info, Market>>,
+
+    #[account(address = spread_market.leg_b_market @ PerpsError::InvalidSpreadLeg)]
+    pub leg_b_market: Box<Account<'info, Market>>,
+}
+
+pub fn handler(ctx: Context<UpdateSpreadMarket>, params: UpdateSpreadMarketParams) -> Result<()> {
+    require!(
+        SpreadMarket::valid_margins(
+            params.initial_margin_ratio,
+            params.maintenance_margin_ratio,
+            &ctx.accounts.leg_a_market,
+            &ctx.accounts.leg_b_market,
+        ),
+        PerpsError::InvalidSpreadParams
+    );
+
+    let spread_market = &mut ctx.accounts.spread_market;
+    spread_market.initial_margin_ratio = params.initial_margin_ratio;
+    spread_market.maintenance_margin_ratio = params.maintenance_margin_ratio;
+    spread_market.is_active = params.is_active;
+
+    msg!(
+        "Spread market updated: margin {}/{}bps, active={}",
+        params.initial_margin_ratio,
+        params.maintenance_margin_ratio,
+        params.is_active
+    );
+    Ok(())
+}
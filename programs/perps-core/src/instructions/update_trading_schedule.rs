@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::{Market, TradingSchedule};
+use crate::errors::PerpsError;
+use crate::instructions::initialize_trading_schedule::TradingScheduleParams;
+
+#[derive(Accounts)]
+pub struct UpdateTradingSchedule<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.authority == authority.key() @ PerpsError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"trading_schedule", market.key().as_ref()],
+        bump = trading_schedule.bump
+    )]
+    pub trading_schedule: Account<'info, TradingSchedule>,
+}
+
+pub fn handler(ctx: Context<UpdateTradingSchedule>, params: TradingScheduleParams) -> Result<()> {
+    params.validate()?;
+
+    let schedule = &mut ctx.accounts.trading_schedule;
+    schedule.weekly_sessions = params.weekly_sessions;
+    schedule.holidays = params.holidays;
+
+    ctx.accounts.market.has_trading_schedule = params.enabled;
+
+    msg!(
+        "Trading schedule updated for market {} (enabled={})",
+        schedule.market,
+        params.enabled
+    );
+    Ok(())
+}
@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::state::{Market, Vault, UserAccount};
+use crate::state::{CollateralRegistry, Market, MarketStatus, MarginAccount, Vault, UserAccount};
 use crate::errors::PerpsError;
 
+/// Withdraws the market's collateral mint from the user's free balance, or a pledged
+/// mint from their margin account for this market. Price feeds for every asset still
+/// pledged are passed as remaining accounts
 #[derive(Accounts)]
 pub struct WithdrawCollateral<'info> {
     #[account(mut)]
@@ -17,7 +20,8 @@ pub struct WithdrawCollateral<'info> {
     pub user_account: Account<'info, UserAccount>,
 
     #[account(
-        seeds = [b"market", market.collateral_mint.as_ref()],
+        mut,
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
         bump = market.bump
     )]
     pub market: Account<'info, Market>,
@@ -38,15 +42,41 @@ pub struct WithdrawCollateral<'info> {
 
     #[account(
         mut,
-        constraint = user_token_account.owner == owner.key(),
-        constraint = user_token_account.mint == market.collateral_mint
+        constraint = user_token_account.owner == owner.key()
     )]
     pub user_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+
+    // Required when withdrawing a mint other than the market's collateral mint
+    #[account(
+        seeds = [b"collateral_registry", market.key().as_ref()],
+        bump = collateral_registry.bump
+    )]
+    pub collateral_registry: Option<Account<'info, CollateralRegistry>>,
+
+    #[account(
+        mut,
+        seeds = [b"margin_account", market.key().as_ref(), owner.key().as_ref()],
+        bump = margin_account.bump,
+        constraint = margin_account.owner == owner.key()
+    )]
+    pub margin_account: Option<Account<'info, MarginAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", market.key().as_ref(), user_token_account.mint.as_ref()],
+        bump
+    )]
+    pub asset_vault_token_account: Option<Account<'info, TokenAccount>>,
 }
 
 pub fn handler(ctx: Context<WithdrawCollateral>, amount: u64) -> Result<()> {
+    let mint = ctx.accounts.user_token_account.mint;
+    if mint != ctx.accounts.market.collateral_mint {
+        return withdraw_asset(ctx, mint, amount);
+    }
+
     let user_account = &mut ctx.accounts.user_account;
 
     require!(
@@ -54,6 +84,15 @@ pub fn handler(ctx: Context<WithdrawCollateral>, amount: u64) -> Result<()> {
         PerpsError::InsufficientCollateral
     );
 
+    // Once in global settlement the vault only pays out free collateral deposited
+    // through this market; the rest is owed to settlement claims
+    if ctx.accounts.market.status != MarketStatus::Active {
+        require!(
+            amount as i64 <= ctx.accounts.market.user_collateral,
+            PerpsError::SettlementBalanceReserved
+        );
+    }
+
     // Create vault signer seeds
     let market_key = ctx.accounts.market.key();
     let vault_bump = ctx.accounts.vault.bump;
@@ -81,8 +120,71 @@ pub fn handler(ctx: Context<WithdrawCollateral>, amount: u64) -> Result<()> {
 
     // Update vault
     let vault = &mut ctx.accounts.vault;
-    vault.total_deposits = vault.total_deposits.checked_sub(amount).unwrap();
+    vault.record_outflow(amount);
+    ctx.accounts.market.debit_user_collateral(amount);
 
     msg!("Withdrew {} collateral for user {}", amount, ctx.accounts.owner.key());
     Ok(())
 }
+
+fn withdraw_asset(ctx: Context<WithdrawCollateral>, mint: Pubkey, amount: u64) -> Result<()> {
+    let registry = ctx.accounts.collateral_registry
+        .as_ref()
+        .ok_or(PerpsError::CollateralAssetAccountsRequired)?;
+    let asset_vault_token_account = ctx.accounts.asset_vault_token_account
+        .as_ref()
+        .ok_or(PerpsError::CollateralAssetAccountsRequired)?;
+    let margin_account = ctx.accounts.margin_account
+        .as_mut()
+        .ok_or(PerpsError::CollateralAssetAccountsRequired)?;
+
+    let index = registry
+        .find_asset(&mint)
+        .ok_or(PerpsError::CollateralAssetNotFound)?;
+
+    require!(
+        margin_account.balances[index] >= amount,
+        PerpsError::InsufficientCollateral
+    );
+
+    margin_account.balances[index] -= amount;
+
+    // Remaining collateral must still cover margin lent to open positions and any
+    // debt they left behind
+    if margin_account.obligations() > 0 {
+        let current_time = Clock::get()?.unix_timestamp;
+        let value = margin_account.haircut_value(registry, ctx.remaining_accounts, current_time)?;
+        require!(
+            value >= margin_account.obligations(),
+            PerpsError::InsufficientCollateral
+        );
+    }
+
+    // Create vault signer seeds
+    let market_key = ctx.accounts.market.key();
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_seeds = &[
+        b"vault".as_ref(),
+        market_key.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = &[&vault_seeds[..]];
+
+    // Transfer tokens from the asset vault to user
+    let cpi_accounts = Transfer {
+        from: asset_vault_token_account.to_account_info(),
+        to: ctx.accounts.user_token_account.to_account_info(),
+        authority: ctx.accounts.vault.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    msg!(
+        "Withdrew {} of collateral asset {} for user {}",
+        amount,
+        mint,
+        ctx.accounts.owner.key()
+    );
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use crate::state::{Market, Position, PositionStatus, Vault};
+use crate::errors::PerpsError;
+
+#[derive(Accounts)]
+pub struct WrapPosition<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"market", market.collateral_mint.as_ref(), market.series_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = position.owner == owner.key() @ PerpsError::Unauthorized,
+        constraint = position.market == market.key(),
+        constraint = position.status == PositionStatus::Open @ PerpsError::PositionAlreadyClosed,
+        constraint = !position.is_wrapped @ PerpsError::PositionWrapped
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        seeds = [b"vault", market.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"position_mint", position.key().as_ref()],
+        bump
+    )]
+    pub position_mint: Account<'info, Mint>,
+
+    /// Receives the position token; may belong to any wallet or escrow
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == position_mint.key()
+    )]
+    pub destination_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<WrapPosition>) -> Result<()> {
+    let position = &mut ctx.accounts.position;
+
+    // Credit is owed by the owner's margin account and cannot follow the token
+    require!(position.margin_credit == 0, PerpsError::MarginCreditOutstanding);
+
+    position.is_wrapped = true;
+    position.last_updated_at = Clock::get()?.unix_timestamp;
+
+    let market_key = ctx.accounts.market.key();
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_seeds = &[
+        b"vault".as_ref(),
+        market_key.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = &[&vault_seeds[..]];
+
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.position_mint.to_account_info(),
+        to: ctx.accounts.destination_token_account.to_account_info(),
+        authority: ctx.accounts.vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::mint_to(cpi_ctx, 1)?;
+
+    msg!(
+        "Position wrapped: {} as token {}",
+        position.key(),
+        ctx.accounts.position_mint.key()
+    );
+
+    Ok(())
+}
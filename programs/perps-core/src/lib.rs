@@ -2,9 +2,11 @@ use anchor_lang::prelude::*;
 
 pub mod errors;
 pub mod instructions;
+pub mod migration;
 pub mod state;
 
 use instructions::*;
+use state::PositionFill;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
@@ -19,6 +21,13 @@ pub mod perps_core {
         instructions::initialize_market::handler(ctx, params)
     }
 
+    pub fn initialize_futures_market(
+        ctx: Context<InitializeFuturesMarket>,
+        params: InitializeFuturesMarketParams,
+    ) -> Result<()> {
+        instructions::initialize_futures_market::handler(ctx, params)
+    }
+
     pub fn initialize_user(ctx: Context<InitializeUser>) -> Result<()> {
         instructions::initialize_user::handler(ctx)
     }
@@ -31,26 +40,280 @@ pub mod perps_core {
         instructions::withdraw_collateral::handler(ctx, amount)
     }
 
-    pub fn open_position(ctx: Context<OpenPosition>, params: OpenPositionParams) -> Result<()> {
+    // Multi-collateral instructions
+    pub fn initialize_collateral_registry(ctx: Context<InitializeCollateralRegistry>) -> Result<()> {
+        instructions::initialize_collateral_registry::handler(ctx)
+    }
+
+    pub fn add_collateral_asset(
+        ctx: Context<AddCollateralAsset>,
+        params: AddCollateralAssetParams,
+    ) -> Result<()> {
+        instructions::add_collateral_asset::handler(ctx, params)
+    }
+
+    pub fn update_collateral_asset(
+        ctx: Context<UpdateCollateralAsset>,
+        params: UpdateCollateralAssetParams,
+    ) -> Result<()> {
+        instructions::update_collateral_asset::handler(ctx, params)
+    }
+
+    pub fn initialize_margin_account(ctx: Context<InitializeMarginAccount>) -> Result<()> {
+        instructions::initialize_margin_account::handler(ctx)
+    }
+
+    pub fn open_position(
+        ctx: Context<OpenPosition>,
+        params: OpenPositionParams,
+    ) -> Result<PositionFill> {
         instructions::open_position::handler(ctx, params)
     }
 
-    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
-        instructions::close_position::handler(ctx)
+    pub fn close_position(
+        ctx: Context<ClosePosition>,
+        params: ClosePositionParams,
+    ) -> Result<PositionFill> {
+        instructions::close_position::handler(ctx, params)
+    }
+
+    pub fn increase_position(
+        ctx: Context<IncreasePosition>,
+        params: IncreasePositionParams,
+    ) -> Result<PositionFill> {
+        instructions::increase_position::handler(ctx, params)
+    }
+
+    pub fn reduce_position(
+        ctx: Context<ReducePosition>,
+        params: ReducePositionParams,
+    ) -> Result<PositionFill> {
+        instructions::reduce_position::handler(ctx, params)
     }
 
     pub fn add_margin(ctx: Context<AddMargin>, amount: u64) -> Result<()> {
         instructions::add_margin::handler(ctx, amount)
     }
 
+    // Position ownership instructions
+    pub fn transfer_position(ctx: Context<TransferPosition>) -> Result<()> {
+        instructions::transfer_position::handler(ctx)
+    }
+
+    pub fn initialize_position_mint(ctx: Context<InitializePositionMint>) -> Result<()> {
+        instructions::initialize_position_mint::handler(ctx)
+    }
+
+    pub fn wrap_position(ctx: Context<WrapPosition>) -> Result<()> {
+        instructions::wrap_position::handler(ctx)
+    }
+
+    pub fn unwrap_position(ctx: Context<UnwrapPosition>) -> Result<()> {
+        instructions::unwrap_position::handler(ctx)
+    }
+
     pub fn liquidate(ctx: Context<Liquidate>) -> Result<()> {
         instructions::liquidate::handler(ctx)
     }
 
+    pub fn liquidate_many(ctx: Context<LiquidateMany>) -> Result<()> {
+        instructions::liquidate_many::handler(ctx)
+    }
+
+    // Liquidation auction instructions
+    pub fn set_liquidation_mode(
+        ctx: Context<SetLiquidationMode>,
+        params: LiquidationModeParams,
+    ) -> Result<()> {
+        instructions::set_liquidation_mode::handler(ctx, params)
+    }
+
+    pub fn start_liquidation_auction(ctx: Context<StartLiquidationAuction>) -> Result<()> {
+        instructions::start_liquidation_auction::handler(ctx)
+    }
+
+    pub fn take_over_position(
+        ctx: Context<TakeOverPosition>,
+        params: TakeOverPositionParams,
+    ) -> Result<()> {
+        instructions::take_over_position::handler(ctx, params)
+    }
+
+    pub fn cancel_liquidation_auction(ctx: Context<CancelLiquidationAuction>) -> Result<()> {
+        instructions::cancel_liquidation_auction::handler(ctx)
+    }
+
+    // Trigger order instructions
+    pub fn place_trigger_order(
+        ctx: Context<PlaceTriggerOrder>,
+        params: TriggerOrderParams,
+    ) -> Result<()> {
+        instructions::place_trigger_order::handler(ctx, params)
+    }
+
+    pub fn cancel_trigger_order(ctx: Context<CancelTriggerOrder>) -> Result<()> {
+        instructions::cancel_trigger_order::handler(ctx)
+    }
+
+    pub fn execute_trigger_order(ctx: Context<ExecuteTriggerOrder>) -> Result<()> {
+        instructions::execute_trigger_order::handler(ctx)
+    }
+
+    pub fn reap_trigger_order(ctx: Context<ReapTriggerOrder>) -> Result<()> {
+        instructions::reap_trigger_order::handler(ctx)
+    }
+
+    // Crank reward instructions
+    pub fn initialize_crank_rewards(
+        ctx: Context<InitializeCrankRewards>,
+        params: CrankRewardsParams,
+    ) -> Result<()> {
+        instructions::initialize_crank_rewards::handler(ctx, params)
+    }
+
+    pub fn update_crank_rewards(
+        ctx: Context<UpdateCrankRewards>,
+        params: CrankRewardsParams,
+    ) -> Result<()> {
+        instructions::update_crank_rewards::handler(ctx, params)
+    }
+
     pub fn update_funding(ctx: Context<UpdateFunding>) -> Result<()> {
         instructions::update_funding::handler(ctx)
     }
 
+    pub fn update_mark_price(ctx: Context<UpdateMarkPrice>) -> Result<()> {
+        instructions::update_mark_price::handler(ctx)
+    }
+
+    pub fn set_mark_price_half_life(ctx: Context<SetMarkPriceHalfLife>, half_life: u32) -> Result<()> {
+        instructions::set_mark_price_half_life::handler(ctx, half_life)
+    }
+
+    // Circuit breaker instructions
+    pub fn set_price_band(ctx: Context<SetPriceBand>, price_band_bps: u16) -> Result<()> {
+        instructions::set_price_band::handler(ctx, price_band_bps)
+    }
+
+    pub fn reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+        instructions::reset_circuit_breaker::handler(ctx)
+    }
+
+    // Open interest cap instructions
+    pub fn initialize_user_market(ctx: Context<InitializeUserMarket>) -> Result<()> {
+        instructions::initialize_user_market::handler(ctx)
+    }
+
+    pub fn set_open_interest_caps(
+        ctx: Context<SetOpenInterestCaps>,
+        params: OpenInterestCapsParams,
+    ) -> Result<()> {
+        instructions::set_open_interest_caps::handler(ctx, params)
+    }
+
+    // Global settlement instructions
+    pub fn initiate_global_settlement(
+        ctx: Context<InitiateGlobalSettlement>,
+        settlement_price: u64,
+    ) -> Result<()> {
+        instructions::initiate_global_settlement::handler(ctx, settlement_price)
+    }
+
+    pub fn close_out_position(ctx: Context<CloseOutPosition>) -> Result<()> {
+        instructions::close_out_position::handler(ctx)
+    }
+
+    pub fn claim_global_settlement(ctx: Context<ClaimGlobalSettlement>) -> Result<()> {
+        instructions::claim_global_settlement::handler(ctx)
+    }
+
+    // Competition instructions
+    pub fn create_competition(
+        ctx: Context<CreateCompetition>,
+        params: CompetitionParams,
+    ) -> Result<()> {
+        instructions::create_competition::handler(ctx, params)
+    }
+
+    pub fn fund_competition(ctx: Context<FundCompetition>, amount: u64) -> Result<()> {
+        instructions::fund_competition::handler(ctx, amount)
+    }
+
+    pub fn join_competition(ctx: Context<JoinCompetition>) -> Result<()> {
+        instructions::join_competition::handler(ctx)
+    }
+
+    pub fn finalize_competition(ctx: Context<FinalizeCompetition>) -> Result<()> {
+        instructions::finalize_competition::handler(ctx)
+    }
+
+    pub fn claim_competition_prize(ctx: Context<ClaimCompetitionPrize>) -> Result<()> {
+        instructions::claim_competition_prize::handler(ctx)
+    }
+
+    // Fee schedule instructions
+    pub fn set_fee_schedule(ctx: Context<SetFeeSchedule>, params: FeeScheduleParams) -> Result<()> {
+        instructions::set_fee_schedule::handler(ctx, params)
+    }
+
+    // Vault accounting instructions
+    pub fn audit_vault(ctx: Context<AuditVault>) -> Result<VaultAudit> {
+        instructions::audit_vault::handler(ctx)
+    }
+
+    // Account migration instructions
+    pub fn migrate_market(ctx: Context<MigrateMarket>) -> Result<()> {
+        instructions::migrate_market::handler(ctx)
+    }
+
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        instructions::migrate_vault::handler(ctx)
+    }
+
+    pub fn migrate_user_account(ctx: Context<MigrateUserAccount>) -> Result<()> {
+        instructions::migrate_user_account::handler(ctx)
+    }
+
+    pub fn migrate_position(ctx: Context<MigratePosition>) -> Result<()> {
+        instructions::migrate_position::handler(ctx)
+    }
+
+    pub fn migrate_user_market(ctx: Context<MigrateUserMarket>) -> Result<()> {
+        instructions::migrate_user_market::handler(ctx)
+    }
+
+    // Trading session instructions
+    pub fn initialize_trading_schedule(
+        ctx: Context<InitializeTradingSchedule>,
+        params: TradingScheduleParams,
+    ) -> Result<()> {
+        instructions::initialize_trading_schedule::handler(ctx, params)
+    }
+
+    pub fn update_trading_schedule(
+        ctx: Context<UpdateTradingSchedule>,
+        params: TradingScheduleParams,
+    ) -> Result<()> {
+        instructions::update_trading_schedule::handler(ctx, params)
+    }
+
+    // Dated futures instructions
+    pub fn sample_settlement_price(ctx: Context<SampleSettlementPrice>) -> Result<()> {
+        instructions::sample_settlement_price::handler(ctx)
+    }
+
+    pub fn finalize_settlement(ctx: Context<FinalizeSettlement>) -> Result<()> {
+        instructions::finalize_settlement::handler(ctx)
+    }
+
+    pub fn settle_position(ctx: Context<SettlePosition>) -> Result<()> {
+        instructions::settle_position::handler(ctx)
+    }
+
+    pub fn roll_position(ctx: Context<RollPosition>, params: RollPositionParams) -> Result<()> {
+        instructions::roll_position::handler(ctx, params)
+    }
+
     // Referral system instructions
     pub fn create_referral_code(
         ctx: Context<CreateReferralCode>,
@@ -66,4 +329,65 @@ pub mod perps_core {
     pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
         instructions::claim_referral_rewards::handler(ctx)
     }
+
+    // Spread instructions
+    pub fn initialize_spread_market(
+        ctx: Context<InitializeSpreadMarket>,
+        params: SpreadMarketParams,
+    ) -> Result<()> {
+        instructions::initialize_spread_market::handler(ctx, params)
+    }
+
+    pub fn update_spread_market(
+        ctx: Context<UpdateSpreadMarket>,
+        params: UpdateSpreadMarketParams,
+    ) -> Result<()> {
+        instructions::update_spread_market::handler(ctx, params)
+    }
+
+    pub fn open_spread_position(
+        ctx: Context<OpenSpreadPosition>,
+        params: OpenSpreadPositionParams,
+    ) -> Result<()> {
+        instructions::open_spread_position::handler(ctx, params)
+    }
+
+    pub fn close_spread_position(
+        ctx: Context<CloseSpreadPosition>,
+        params: CloseSpreadPositionParams,
+    ) -> Result<()> {
+        instructions::close_spread_position::handler(ctx, params)
+    }
+
+    pub fn liquidate_spread_position(ctx: Context<LiquidateSpreadPosition>) -> Result<()> {
+        instructions::liquidate_spread_position::handler(ctx)
+    }
+
+    pub fn close_out_spread_position(ctx: Context<CloseOutSpreadPosition>) -> Result<()> {
+        instructions::close_out_spread_position::handler(ctx)
+    }
+
+    pub fn claim_spread_settlement(ctx: Context<ClaimSpreadSettlement>) -> Result<()> {
+        instructions::claim_spread_settlement::handler(ctx)
+    }
+
+    // Index market instructions
+    pub fn set_index_basket(ctx: Context<SetIndexBasket>, params: IndexBasketParams) -> Result<()> {
+        instructions::set_index_basket::handler(ctx, params)
+    }
+
+    // Contract spec instructions
+    pub fn set_contract_specs(ctx: Context<SetContractSpecs>, params: ContractSpecsParams) -> Result<()> {
+        instructions::set_contract_specs::handler(ctx, params)
+    }
+
+    // Risk tier instructions
+    pub fn set_risk_tiers(ctx: Context<SetRiskTiers>, params: RiskTiersParams) -> Result<()> {
+        instructions::set_risk_tiers::handler(ctx, params)
+    }
+
+    // Margin debt instructions
+    pub fn seize_margin_collateral(ctx: Context<SeizeMarginCollateral>, max_repay: u64) -> Result<()> {
+        instructions::seize_margin_collateral::handler(ctx, max_repay)
+    }
 }
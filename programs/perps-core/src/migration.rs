@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::PerpsError;
+
+/// Load a program account for migration, first growing it to `len` bytes so that
+/// fields added since it was written deserialize as zeros. The payer covers the extra rent.
+pub fn load_for_migration<'info, T: AccountDeserialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    len: usize,
+) -> Result<T> {
+    if account.data_len() < len {
+        let rent = Rent::get()?.minimum_balance(len);
+        let top_up = rent.saturating_sub(account.lamports());
+        if top_up > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            };
+            system_program::transfer(CpiContext::new(system_program.clone(), cpi_accounts), top_up)?;
+        }
+        account.realloc(len, true)?;
+    }
+
+    let data = account.try_borrow_data()?;
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Check that an account written at `version` can be migrated to `current`, which
+/// is supported from `oldest` on
+pub fn check_migration_version(version: u8, oldest: u8, current: u8) -> Result<()> {
+    if version == current {
+        return err!(PerpsError::AccountAlreadyMigrated);
+    }
+    require!(
+        (oldest..current).contains(&version),
+        PerpsError::UnsupportedAccountVersion
+    );
+    Ok(())
+}
+
+/// Read a single byte of the account's raw data, for fields a legacy layout kept elsewhere
+pub fn legacy_byte(account: &AccountInfo, offset: usize) -> Result<u8> {
+    let data = account.try_borrow_data()?;
+    data.get(offset)
+        .copied()
+        .ok_or_else(|| error!(ErrorCode::AccountDidNotDeserialize))
+}
+
+/// Write a migrated account back in the current layout
+pub fn store_migrated<T: AccountSerialize>(account: &AccountInfo, value: &T) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    value.try_serialize(&mut &mut data[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_migration_version() {
+        // Every version from the oldest supported up to the current one migrates
+        for version in 0..8 {
+            assert!(check_migration_version(version, 0, 8).is_ok());
+        }
+        assert_eq!(
+            check_migration_version(8, 0, 8).unwrap_err(),
+            PerpsError::AccountAlreadyMigrated.into()
+        );
+
+        // Versions below the oldest supported or from a newer program are rejected
+        assert_eq!(
+            check_migration_version(0, 1, 2).unwrap_err(),
+            PerpsError::UnsupportedAccountVersion.into()
+        );
+        assert_eq!(
+            check_migration_version(9, 0, 8).unwrap_err(),
+            PerpsError::UnsupportedAccountVersion.into()
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use perps_math::{scale_price, Bps, Rounding};
+use pyth_sdk_solana::load_price_feed_from_account_info;
+use crate::errors::PerpsError;
+use crate::state::Position;
+
+pub const MAX_COLLATERAL_ASSETS: usize = 8;
+
+/// A non-settlement token accepted as margin, valued at its oracle price less a haircut
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct CollateralAsset {
+    pub mint: Pubkey,
+    pub pyth_price_feed: Pubkey,
+    pub haircut_bps: u16,               // Value discount (basis points, 1000 = 10%)
+    pub decimals: u8,                   // Mint decimals
+    pub is_active: bool,                // Inactive assets can be withdrawn but not deposited
+}
+
+impl CollateralAsset {
+    pub const LEN: usize = 32 + 32 + 2 + 1 + 1;
+}
+
+/// Collateral assets accepted by a market in addition to its settlement mint
+/// PDA seeds: [b"collateral_registry", market]
+#[account]
+#[derive(Default)]
+pub struct CollateralRegistry {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub assets: [CollateralAsset; MAX_COLLATERAL_ASSETS],
+    pub num_assets: u8,
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+}
+
+impl CollateralRegistry {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // market
+        32 +  // authority
+        CollateralAsset::LEN * MAX_COLLATERAL_ASSETS +  // assets
+        1 +   // num_assets
+        1 +   // bump
+        1 +   // version
+        31;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    pub const MAX_HAIRCUT_BPS: u16 = 9000;  // 90% max haircut
+
+    pub fn find_asset(&self, mint: &Pubkey) -> Option<usize> {
+        self.assets[..self.num_assets as usize]
+            .iter()
+            .position(|asset| asset.mint == *mint)
+    }
+}
+
+/// A user's non-settlement collateral in one market and the margin drawn against it
+/// PDA seeds: [b"margin_account", market, owner]
+#[account]
+#[derive(Default)]
+pub struct MarginAccount {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+
+    /// Token balances, indexed like `CollateralRegistry::assets`
+    pub balances: [u64; MAX_COLLATERAL_ASSETS],
+
+    /// Settlement-token margin currently lent to positions against these balances
+    pub credit_used: u64,
+
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+
+    /// Lent margin that positions lost and did not repay, owed to the vault and
+    /// recovered by `seize_margin_collateral` (added in version 2, read from padding)
+    pub debt: u64,
+}
+
+impl MarginAccount {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // owner
+        32 +  // market
+        8 * MAX_COLLATERAL_ASSETS +  // balances
+        8 +   // credit_used
+        1 +   // bump
+        1 +   // version
+        8 +   // debt
+        23;   // padding
+
+    pub const VERSION: u8 = 2;
+
+    /// Value of all balances in settlement units (6 decimals) at haircut oracle prices.
+    /// The price feed of every asset with a balance must be passed in `price_feeds`.
+    pub fn haircut_value(
+        &self,
+        registry: &CollateralRegistry,
+        price_feeds: &[AccountInfo],
+        current_time: i64,
+    ) -> Result<u64> {
+        let mut total: u128 = 0;
+
+        for (asset, &balance) in registry.assets[..registry.num_assets as usize]
+            .iter()
+            .zip(self.balances.iter())
+        {
+            if balance == 0 {
+                continue;
+            }
+
+            let feed = price_feeds
+                .iter()
+                .find(|info| info.key() == asset.pyth_price_feed)
+                .ok_or(PerpsError::InvalidOraclePrice)?;
+            let price = load_collateral_price(feed, current_time)?;
+
+            // value = balance * price / 10^decimals, then apply the haircut
+            let haircut = Bps::from(asset.haircut_bps).complement().map_err(PerpsError::from)?;
+            let value = perps_math::mul_div_wide(
+                balance as u128,
+                price as u128,
+                10u128.pow(asset.decimals as u32),
+                Rounding::Down,
+            )
+            .and_then(|v| perps_math::mul_div_wide(v, haircut.0 as u128, perps_math::BPS_SCALE as u128, Rounding::Down))
+            .map_err(PerpsError::from)?;
+
+            total = total.checked_add(value).ok_or(PerpsError::MathOverflow)?;
+        }
+
+        Ok(total.min(u64::MAX as u128) as u64)
+    }
+
+    /// Credit and debt the pledged balances must cover
+    pub fn obligations(&self) -> u64 {
+        self.credit_used.saturating_add(self.debt)
+    }
+
+    /// Credit and debt the haircut value of the balances no longer covers
+    pub fn shortfall(
+        &self,
+        registry: &CollateralRegistry,
+        price_feeds: &[AccountInfo],
+        current_time: i64,
+    ) -> Result<u64> {
+        let value = self.haircut_value(registry, price_feeds, current_time)?;
+        Ok(self.obligations().saturating_sub(value))
+    }
+
+    /// Lend `amount` of settlement-token margin if the haircut value covers all credit
+    /// and debt.
+    pub fn draw_credit(
+        &mut self,
+        registry: &CollateralRegistry,
+        price_feeds: &[AccountInfo],
+        amount: u64,
+        current_time: i64,
+    ) -> Result<()> {
+        let new_credit = self.credit_used
+            .checked_add(amount)
+            .ok_or(PerpsError::MathOverflow)?;
+        let value = self.haircut_value(registry, price_feeds, current_time)?;
+        require!(
+            value >= new_credit.saturating_add(self.debt),
+            PerpsError::InsufficientCollateral
+        );
+
+        self.credit_used = new_credit;
+        Ok(())
+    }
+
+    /// Release `credit` lent to a position leaving the book, repaid out of the
+    /// `available` settlement. What the settlement can't cover becomes debt against
+    /// the pledged balances. Returns the amount repaid, which stays in the vault.
+    pub fn settle_credit(&mut self, credit: u64, available: u64) -> u64 {
+        let repaid = credit.min(available);
+        self.credit_used = self.credit_used.saturating_sub(credit);
+        self.debt = self.debt.saturating_add(credit - repaid);
+        repaid
+    }
+
+    /// Whether every pledged balance is gone
+    pub fn is_empty(&self) -> bool {
+        self.balances.iter().all(|&balance| balance == 0)
+    }
+}
+
+/// Margin a position drew that its owner's pledged collateral no longer backs, counted
+/// against the position in its margin checks. Positions with margin credit need the
+/// registry, margin account and asset price feeds to be judged.
+pub fn unbacked_credit(
+    position: &Position,
+    registry: Option<&CollateralRegistry>,
+    margin_account: Option<&MarginAccount>,
+    price_feeds: &[AccountInfo],
+    current_time: i64,
+) -> Result<u64> {
+    if position.margin_credit == 0 {
+        return Ok(0);
+    }
+    let (Some(registry), Some(margin_account)) = (registry, margin_account) else {
+        return Err(PerpsError::CollateralRegistryRequired.into());
+    };
+    require_keys_eq!(margin_account.owner, position.owner, PerpsError::Unauthorized);
+    margin_account.shortfall(registry, price_feeds, current_time)
+}
+
+pub fn load_collateral_price(feed: &AccountInfo, current_time: i64) -> Result<u64> {
+    let price_feed = load_price_feed_from_account_info(feed)
+        .map_err(|_| PerpsError::InvalidOraclePrice)?;
+
+    let price_data = price_feed
+        .get_price_no_older_than(current_time, 60)
+        .ok_or(PerpsError::StaleOraclePrice)?;
+
+    require!(price_data.price > 0, PerpsError::InvalidOraclePrice);
+
+    // Convert Pyth price to our format (6 decimals)
+    let price = scale_price(price_data.price, price_data.expo, Rounding::Down)
+        .map_err(PerpsError::from)?;
+
+    Ok(price)
+}
@@ -0,0 +1,249 @@
+use anchor_lang::prelude::*;
+use crate::errors::PerpsError;
+use crate::state::UserAccount;
+
+pub const MAX_COMPETITION_MARKETS: usize = 4;
+pub const MAX_COMPETITION_ENTRIES: usize = 32;
+pub const MAX_PRIZE_RANKS: usize = 5;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompetitionMetric {
+    #[default]
+    Pnl,            // Realized PnL in the window
+    Roi,            // Realized PnL over the collateral of the positions it came from
+}
+
+/// A participant's results, recorded as their positions close or are liquidated
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct CompetitionEntry {
+    pub owner: Pubkey,
+    pub realized_pnl: i64,              // Including funding and fees
+    pub collateral_used: u64,           // Collateral of the positions the PnL came from
+    pub trades: u32,                    // Positions closed or liquidated in the window
+    pub prize: u64,                     // Assigned on finalize
+    pub claimed: bool,
+}
+
+impl CompetitionEntry {
+    pub const LEN: usize = 32 + 8 + 8 + 4 + 8 + 1;
+
+    /// Score under `metric`; ROI is in basis points
+    pub fn score(&self, metric: CompetitionMetric) -> i64 {
+        match metric {
+            CompetitionMetric::Pnl => self.realized_pnl,
+            CompetitionMetric::Roi => {
+                if self.collateral_used == 0 {
+                    return 0;
+                }
+                (self.realized_pnl as i128 * 10000 / self.collateral_used as i128)
+                    .clamp(i64::MIN as i128, i64::MAX as i128) as i64
+            }
+        }
+    }
+}
+
+/// A trading competition over a time window on a set of markets.
+/// The prize pool is held in a token escrow owned by this account and split by rank.
+/// PDA seeds: [b"competition", authority, id]
+#[account]
+#[derive(Default)]
+pub struct Competition {
+    pub authority: Pubkey,
+    pub prize_mint: Pubkey,
+    pub id: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub metric: CompetitionMetric,
+
+    pub markets: [Pubkey; MAX_COMPETITION_MARKETS],
+    pub num_markets: u8,
+
+    /// Share of the prize pool for each rank, best first (basis points)
+    pub payout_bps: [u16; MAX_PRIZE_RANKS],
+    pub prize_pool: u64,
+
+    pub entries: [CompetitionEntry; MAX_COMPETITION_ENTRIES],
+    pub num_entries: u8,
+
+    pub is_finalized: bool,
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+}
+
+impl Competition {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // authority
+        32 +  // prize_mint
+        8 +   // id
+        8 +   // start_time
+        8 +   // end_time
+        1 +   // metric
+        32 * MAX_COMPETITION_MARKETS +  // markets
+        1 +   // num_markets
+        2 * MAX_PRIZE_RANKS +  // payout_bps
+        8 +   // prize_pool
+        CompetitionEntry::LEN * MAX_COMPETITION_ENTRIES +  // entries
+        1 +   // num_entries
+        1 +   // is_finalized
+        1 +   // bump
+        1 +   // version
+        32;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    pub fn find_entry(&self, owner: &Pubkey) -> Option<usize> {
+        self.entries[..self.num_entries as usize]
+            .iter()
+            .position(|entry| entry.owner == *owner)
+    }
+
+    pub fn includes_market(&self, market: &Pubkey) -> bool {
+        self.markets[..self.num_markets as usize].contains(market)
+    }
+
+    /// Record a closed or liquidated position. Returns false, recording nothing, when
+    /// the close is outside the window, in another market or by a non-participant.
+    pub fn record(
+        &mut self,
+        market: &Pubkey,
+        owner: &Pubkey,
+        pnl: i64,
+        collateral: u64,
+        current_time: i64,
+    ) -> bool {
+        if self.is_finalized
+            || current_time < self.start_time
+            || current_time > self.end_time
+            || !self.includes_market(market)
+        {
+            return false;
+        }
+        let Some(index) = self.find_entry(owner) else {
+            return false;
+        };
+
+        let entry = &mut self.entries[index];
+        entry.realized_pnl = entry.realized_pnl.saturating_add(pnl);
+        entry.collateral_used = entry.collateral_used.saturating_add(collateral);
+        entry.trades = entry.trades.saturating_add(1);
+        true
+    }
+
+    /// Indexes of entries with a positive score, best first; only these are paid
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranked: Vec<usize> = (0..self.num_entries as usize)
+            .filter(|&i| self.entries[i].trades > 0 && self.entries[i].score(self.metric) > 0)
+            .collect();
+        // Stable, so ties go to whoever joined first
+        ranked.sort_by_key(|&i| std::cmp::Reverse(self.entries[i].score(self.metric)));
+        ranked
+    }
+}
+
+/// Record a closed, reduced or liquidated position for the owner of `user_account`.
+/// While they are entered in a running competition its account is required, so no
+/// result can be left off the leaderboard.
+pub fn record_competition_result(
+    competition: Option<&mut Account<Competition>>,
+    user_account: &UserAccount,
+    market: &Pubkey,
+    pnl: i64,
+    collateral: u64,
+    current_time: i64,
+) -> Result<()> {
+    let Some(entered) = user_account.active_competition(current_time) else {
+        return Ok(());
+    };
+    let competition = competition.ok_or(PerpsError::CompetitionAccountRequired)?;
+    require_keys_eq!(competition.key(), entered, PerpsError::CompetitionAccountRequired);
+
+    competition.record(market, &user_account.owner, pnl, collateral, current_time);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn competition(metric: CompetitionMetric, owners: &[Pubkey], market: Pubkey) -> Competition {
+        let mut competition = Competition {
+            start_time: 1_000,
+            end_time: 2_000,
+            metric,
+            num_markets: 1,
+            ..Default::default()
+        };
+        competition.markets[0] = market;
+        for (entry, owner) in competition.entries.iter_mut().zip(owners) {
+            entry.owner = *owner;
+        }
+        competition.num_entries = owners.len() as u8;
+        competition
+    }
+
+    #[test]
+    fn test_record_within_window_and_markets() {
+        let market = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut competition = competition(CompetitionMetric::Pnl, &[owner], market);
+
+        assert!(competition.record(&market, &owner, 500, 1_000, 1_000));
+        assert!(competition.record(&market, &owner, -200, 1_000, 2_000));
+
+        // Outside the window, in another market or by a non-participant
+        assert!(!competition.record(&market, &owner, 100, 1_000, 999));
+        assert!(!competition.record(&market, &owner, 100, 1_000, 2_001));
+        assert!(!competition.record(&Pubkey::new_unique(), &owner, 100, 1_000, 1_500));
+        assert!(!competition.record(&market, &Pubkey::new_unique(), 100, 1_000, 1_500));
+
+        competition.is_finalized = true;
+        assert!(!competition.record(&market, &owner, 100, 1_000, 1_500));
+
+        let entry = &competition.entries[0];
+        assert_eq!(entry.realized_pnl, 300);
+        assert_eq!(entry.collateral_used, 2_000);
+        assert_eq!(entry.trades, 2);
+    }
+
+    #[test]
+    fn test_roi_score() {
+        let entry = CompetitionEntry { realized_pnl: 250, collateral_used: 1_000, ..Default::default() };
+        assert_eq!(entry.score(CompetitionMetric::Pnl), 250);
+        assert_eq!(entry.score(CompetitionMetric::Roi), 2_500);
+
+        let entry = CompetitionEntry { realized_pnl: -250, collateral_used: 0, ..Default::default() };
+        assert_eq!(entry.score(CompetitionMetric::Roi), 0);
+    }
+
+    #[test]
+    fn test_ranking() {
+        let market = Pubkey::new_unique();
+        let owners: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let mut competition = competition(CompetitionMetric::Roi, &owners, market);
+
+        competition.record(&market, &owners[0], 100, 1_000, 1_500);     // 10%
+        competition.record(&market, &owners[1], 300, 1_000, 1_500);     // 30%
+        competition.record(&market, &owners[2], 1_000, 10_000, 1_500);  // 10%, joined later
+        // owners[3] never traded
+
+        assert_eq!(competition.ranking(), vec![1, 0, 2]);
+
+        // By PnL the biggest winner leads
+        competition.metric = CompetitionMetric::Pnl;
+        assert_eq!(competition.ranking(), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_ranking_skips_non_positive_scores() {
+        let market = Pubkey::new_unique();
+        let owners: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let mut competition = competition(CompetitionMetric::Pnl, &owners, market);
+
+        // Wash trades that break even or lose earn no rank
+        competition.record(&market, &owners[0], 0, 1_000, 1_500);
+        competition.record(&market, &owners[1], -50, 1_000, 1_500);
+        competition.record(&market, &owners[2], 10, 1_000, 1_500);
+
+        assert_eq!(competition.ranking(), vec![2]);
+    }
+}
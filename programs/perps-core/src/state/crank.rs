@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use super::{Market, UserAccount};
+
+/// Seconds over which `max_rewards_per_window` applies
+pub const CRANK_REWARD_WINDOW: i64 = 3_600;
+
+/// Rewards for permissionless maintenance cranks, paid from the market's insurance fund
+/// into the keeper's free collateral
+/// PDA seeds: [b"crank_rewards", market]
+#[account]
+#[derive(Default)]
+pub struct CrankRewards {
+    pub market: Pubkey,
+
+    pub reward_per_crank: u64,        // Settlement units (6 decimals), 0 = disabled
+    pub max_rewards_per_window: u64,  // Cap on rewards paid per CRANK_REWARD_WINDOW
+
+    pub window_start: i64,
+    pub paid_in_window: u64,
+    pub total_paid: u64,
+
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+}
+
+impl CrankRewards {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // market
+        8 +   // reward_per_crank
+        8 +   // max_rewards_per_window
+        8 +   // window_start
+        8 +   // paid_in_window
+        8 +   // total_paid
+        1 +   // bump
+        1 +   // version
+        31;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    /// Pay one crank's reward from the insurance fund to `keeper`, returning the amount.
+    /// Pays less, or nothing, once the window's cap or the insurance fund runs out.
+    pub fn pay(&mut self, market: &mut Market, keeper: &mut UserAccount, current_time: i64) -> u64 {
+        if current_time - self.window_start >= CRANK_REWARD_WINDOW {
+            self.window_start = current_time;
+            self.paid_in_window = 0;
+        }
+
+        let reward = self.reward_per_crank
+            .min(self.max_rewards_per_window.saturating_sub(self.paid_in_window))
+            .min(market.insurance_fund);
+        if reward == 0 {
+            return 0;
+        }
+
+        market.insurance_fund -= reward;
+        keeper.collateral_balance = keeper.collateral_balance.saturating_add(reward);
+        market.credit_user_collateral(reward);
+        self.paid_in_window += reward;
+        self.total_paid = self.total_paid.saturating_add(reward);
+
+        msg!("Crank reward: {} to {}", reward, keeper.owner);
+        reward
+    }
+}
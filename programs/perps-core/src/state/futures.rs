@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use crate::errors::PerpsError;
+use crate::state::MAX_PRICE_AGE;
+
+/// Longest a settlement sample stands for: no longer than its oracle price stays fresh
+pub const MAX_SAMPLE_GAP: u64 = MAX_PRICE_AGE;
+
+/// Settlement state for a dated futures market.
+/// Oracle samples taken during the final window before expiry are averaged into the
+/// settlement price once the market expires, each weighted by the seconds until the
+/// next sample, up to `MAX_SAMPLE_GAP`.
+/// PDA seeds: [b"futures_settlement", market]
+#[account]
+#[derive(Default)]
+pub struct FuturesSettlement {
+    pub market: Pubkey,
+
+    /// Length of the TWAP window ending at expiry (seconds)
+    pub settlement_window: i64,
+
+    // TWAP accumulator: sum of each sample times the seconds it stood
+    pub price_sum: u128,
+    pub num_samples: u32,
+    pub last_sample_time: i64,
+
+    /// Final settlement price (6 decimals), set once finalized
+    pub settlement_price: u64,
+    pub is_finalized: bool,
+
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+
+    pub last_price: u64,              // Latest sample, weighted once the next one arrives
+    pub sampled_seconds: u64,         // Seconds weighted into `price_sum`, between samples only
+}
+
+impl FuturesSettlement {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // market
+        8 +   // settlement_window
+        16 +  // price_sum
+        4 +   // num_samples
+        8 +   // last_sample_time
+        8 +   // settlement_price
+        1 +   // is_finalized
+        1 +   // bump
+        1 +   // version
+        8 +   // last_price
+        8 +   // sampled_seconds
+        15;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    /// Add a sample, weighting the previous one by the seconds since it was taken.
+    /// A longer gap only counts `MAX_SAMPLE_GAP`, so sparse sampling leaves the
+    /// window uncovered instead of letting one print stand for it.
+    pub fn record_sample(&mut self, price: u64, current_time: i64) -> Result<()> {
+        if self.num_samples > 0 {
+            let elapsed = current_time
+                .checked_sub(self.last_sample_time)
+                .ok_or(PerpsError::MathOverflow)?;
+            let elapsed = perps_math::to_unsigned(elapsed)
+                .map_err(PerpsError::from)?
+                .min(MAX_SAMPLE_GAP);
+            self.price_sum = self.price_sum
+                .checked_add(self.last_price as u128 * elapsed as u128)
+                .ok_or(PerpsError::MathOverflow)?;
+            self.sampled_seconds = self.sampled_seconds.saturating_add(elapsed);
+        }
+
+        self.last_price = price;
+        self.last_sample_time = current_time;
+        self.num_samples = self.num_samples
+            .checked_add(1)
+            .ok_or(PerpsError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Time-weighted average price over the intervals between samples, or None unless
+    /// they cover at least half of the settlement window. Time after the last sample
+    /// has no sample closing it and counts for nothing.
+    pub fn twap(&self) -> Option<u64> {
+        let covered = self.sampled_seconds;
+        if covered == 0 || covered.saturating_mul(2) < self.settlement_window as u64 {
+            return None;
+        }
+
+        Some((self.price_sum / covered as u128) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settlement(settlement_window: i64) -> FuturesSettlement {
+        FuturesSettlement {
+            settlement_window,
+            version: FuturesSettlement::VERSION,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_twap_weights_samples_by_time_between_them() {
+        let mut settlement = settlement(120);
+        settlement.record_sample(100_000_000, 0).unwrap();
+        settlement.record_sample(200_000_000, 20).unwrap();
+        // A lone first sample has nothing closing it yet
+        assert_eq!(settlement.sampled_seconds, 20);
+        assert_eq!(settlement.twap(), None);
+
+        settlement.record_sample(400_000_000, 60).unwrap();
+        assert_eq!(settlement.sampled_seconds, 60);
+        assert_eq!(settlement.twap(), Some(166_666_666));
+    }
+
+    #[test]
+    fn test_twap_coverage_caps_each_sample() {
+        // Two prints ten minutes apart cover only a minute of the window
+        let mut settlement = settlement(600);
+        settlement.record_sample(100_000_000, 0).unwrap();
+        settlement.record_sample(200_000_000, 600).unwrap();
+        assert_eq!(settlement.sampled_seconds, MAX_SAMPLE_GAP);
+        assert_eq!(settlement.twap(), None);
+
+        // Sampling every minute fills the rest in
+        for i in 1..=4 {
+            settlement.record_sample(100_000_000, 600 + i * 60).unwrap();
+        }
+        assert_eq!(settlement.sampled_seconds, 5 * MAX_SAMPLE_GAP);
+        assert_eq!(settlement.twap(), Some(120_000_000));
+    }
+}
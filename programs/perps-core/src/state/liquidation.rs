@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use perps_math::{Bps, Price, Rounding};
+use super::Side;
+use crate::errors::PerpsError;
+
+/// A Dutch auction on a liquidatable position in a `LiquidationMode::DutchAuction` market
+/// PDA seeds: [b"liquidation_auction", position]
+#[account]
+#[derive(Default)]
+pub struct LiquidationAuction {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub starter: Pubkey,              // Paid the rent, refunded when the auction ends
+    pub started_at: i64,
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+}
+
+impl LiquidationAuction {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // market
+        32 +  // position
+        32 +  // starter
+        8 +   // started_at
+        1 +   // bump
+        1 +   // version
+        32;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    /// Entry price the liquidator takes `side` over at: below the oracle for longs,
+    /// above it for shorts, rounded toward the oracle
+    pub fn take_over_price(side: Side, oracle_price: u64, discount_bps: u16) -> Result<u64> {
+        let price = match side {
+            Side::Long => Price(oracle_price).sub_bps(Bps::from(discount_bps), Rounding::Up),
+            Side::Short => Price(oracle_price).add_bps(Bps::from(discount_bps), Rounding::Down),
+        };
+        Ok(price.map_err(PerpsError::from)?.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_over_price_rounds_toward_oracle() {
+        assert_eq!(LiquidationAuction::take_over_price(Side::Long, 100_000_000, 250).unwrap(), 97_500_000);
+        assert_eq!(LiquidationAuction::take_over_price(Side::Short, 100_000_000, 250).unwrap(), 102_500_000);
+
+        // 1% of 3 is 0.03: longs round up to the oracle, shorts down to it
+        assert_eq!(LiquidationAuction::take_over_price(Side::Long, 3, 100).unwrap(), 3);
+        assert_eq!(LiquidationAuction::take_over_price(Side::Short, 3, 100).unwrap(), 3);
+    }
+}
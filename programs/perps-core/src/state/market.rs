@@ -1,4 +1,52 @@
 use anchor_lang::prelude::*;
+use perps_math::{Bps, Price, Rounding, Size};
+use super::{pyth_price, IndexComponent, Position, Side, SpreadLeg, UserMarketAccount, MAX_INDEX_COMPONENTS};
+use crate::errors::PerpsError;
+
+/// Fixed-point scale for EMA weights
+const EMA_SCALE: u64 = 1_000_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarketType {
+    #[default]
+    Perpetual,
+    DatedFuture,    // Expires and settles at a fixed price, no funding
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarketStatus {
+    #[default]
+    Active,
+    Settling,       // Frozen at the global settlement price, positions being closed out
+    Settled,        // All positions closed out, claims are payable
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LiquidationMode {
+    #[default]
+    Fixed,          // Closed by `liquidate` for a fixed `liquidation_fee`
+    DutchAuction,   // Taken over by a liquidator at a discount that rises over time
+}
+
+/// Most brackets a market's risk tier table can hold
+pub const MAX_RISK_TIERS: usize = 5;
+
+/// Leverage and margin for positions up to `max_notional` of entry notional
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RiskTier {
+    pub max_notional: u64,              // Largest entry notional in this tier (6 decimals)
+    pub max_leverage: u32,              // 3 decimals, 20x = 20_000
+    pub maintenance_margin_ratio: u32,  // Basis points
+}
+
+impl RiskTier {
+    pub const LEN: usize = 8 + 4 + 4;
+
+    /// Initial margin implied by the tier's leverage, in basis points rounded up
+    pub fn initial_margin_ratio(&self) -> u32 {
+        10_000_000u32.div_ceil(self.max_leverage.max(1))
+    }
+}
 
 #[account]
 #[derive(Default)]
@@ -16,7 +64,7 @@ pub struct Market {
     pub maintenance_margin_ratio: u32,  // 5% = 500 (2 decimals, basis points)
     pub initial_margin_ratio: u32,      // 10% = 1000 (2 decimals, basis points)
     pub taker_fee: u32,                 // 0.05% = 5 (2 decimals, basis points)
-    pub maker_fee: u32,                 // 0.02% = 2 (2 decimals, basis points)
+    pub maker_fee: i32,                 // 0.02% = 2 (2 decimals, basis points, negative = rebate)
     pub liquidation_fee: u32,           // 2.5% = 250 (2 decimals, basis points)
 
     // Open interest tracking
@@ -38,6 +86,72 @@ pub struct Market {
 
     pub bump: u8,
     pub is_paused: bool,
+    pub has_trading_schedule: bool,     // Opens and funding follow the TradingSchedule account
+
+    // Dated futures
+    pub market_type: MarketType,
+    pub expiry_timestamp: i64,          // Unix timestamp, 0 for perpetuals
+
+    // Mark price
+    pub mark_price: u64,                // EMA of execution prices (6 decimals), used for liquidations
+    pub mark_price_updated_at: i64,     // Unix timestamp of the last EMA sample
+    pub mark_price_half_life: u32,      // Seconds for a new sample to reach half weight (0 = no smoothing)
+
+    // Price band circuit breaker
+    pub reference_price: u64,           // Slow-moving anchor the band is measured against (6 decimals)
+    pub reference_price_updated_at: i64,
+    pub price_band_bps: u16,            // Max deviation from the reference (basis points, 0 = disabled)
+    pub circuit_breaker_tripped: bool,  // Reduce-only until the authority resets it
+
+    // Emergency wind-down, see GlobalSettlement
+    pub status: MarketStatus,
+
+    pub version: u8,                    // Layout version, 0 = written before versioning
+
+    // Notional risk caps in settlement units at the oracle price (added in version 2, 0 = disabled)
+    pub max_open_interest_notional: u64,            // Per side
+    pub max_user_open_interest_notional: u64,       // Per user, both sides combined
+    pub max_open_interest_imbalance_notional: u64,  // Long minus short, either direction
+
+    // Liquidation auctions (added in version 3), see LiquidationAuction
+    pub liquidation_mode: LiquidationMode,
+    pub auction_floor_discount_bps: u16,    // Discount to the oracle price when an auction starts
+    pub auction_cap_discount_bps: u16,      // Discount reached after `auction_duration`
+    pub auction_duration: u32,              // Seconds for the discount to rise from floor to cap
+
+    // Solvency accounting (added in version 4), see `audit_vault`. Free balances and
+    // referral rewards are shared across markets, so those two are net flows through
+    // this market and only reconcile summed over markets sharing a vault mint.
+    pub user_collateral: i64,               // Free collateral credited minus debited here
+    pub position_collateral: u64,           // Collateral of tracked open positions
+    pub position_margin_credit: u64,        // Part of it lent against multi-collateral, not held in the vault
+    pub long_entry_notional: u64,           // Sum of size * entry price of tracked longs
+    pub short_entry_notional: u64,          // Sum of size * entry price of tracked shorts
+    pub tracked_long_size: u64,             // Open interest of tracked longs
+    pub tracked_short_size: u64,            // Open interest of tracked shorts
+    pub referral_rewards: i64,              // Referral rewards accrued minus claimed here
+
+    // Maker fills (added in version 5). Fills co-signed by `fill_authority` rested on
+    // the book and pay `maker_fee`; everything else pays `taker_fee`.
+    pub fill_authority: Pubkey,             // Orderbook or mm-registry signer, default = no maker fills
+    pub rebate_budget: u64,                 // Taker fees collected and not yet paid out as maker rebates
+
+    // Index basket (added in version 6). An index market prices off the weighted sum of
+    // these feeds; the first is also `pyth_price_feed`. 0 components = single-feed market.
+    pub index_components: [IndexComponent; MAX_INDEX_COMPONENTS],
+    pub index_component_count: u8,
+
+    // Contract specs (added in version 7). Sizes stay in base units, 6 decimals of one
+    // `contract_unit`; a zero lot or tick size leaves sizes or limit prices unrestricted.
+    pub contract_unit: [u8; 8],             // Unit the commodity is quoted in, e.g. "bbl"
+    pub contract_multiplier: u64,           // Base units in one contract
+    pub lot_size: u64,                      // Base units every traded size is a multiple of
+    pub tick_size: u64,                     // Increment limit prices land on (6 decimals)
+
+    // Risk tiers (added in version 8), ascending by notional. Larger positions get less
+    // leverage and more maintenance margin; 0 tiers = flat market-wide ratios.
+    pub risk_tiers: [RiskTier; MAX_RISK_TIERS],
+    pub risk_tier_count: u8,
 }
 
 impl Market {
@@ -64,7 +178,69 @@ impl Market {
         8 +   // total_trades
         1 +   // bump
         1 +   // is_paused
-        56;   // padding for future use (reduced by 8 for commodity)
+        1 +   // has_trading_schedule
+        1 +   // market_type
+        8 +   // expiry_timestamp
+        8 +   // mark_price
+        8 +   // mark_price_updated_at
+        4 +   // mark_price_half_life
+        8 +   // reference_price
+        8 +   // reference_price_updated_at
+        2 +   // price_band_bps
+        1 +   // circuit_breaker_tripped
+        1 +   // status
+        1 +   // version
+        8 +   // max_open_interest_notional
+        8 +   // max_user_open_interest_notional
+        8 +   // max_open_interest_imbalance_notional
+        1 +   // liquidation_mode
+        2 +   // auction_floor_discount_bps
+        2 +   // auction_cap_discount_bps
+        4 +   // auction_duration
+        8 +   // user_collateral
+        8 +   // position_collateral
+        8 +   // position_margin_credit
+        8 +   // long_entry_notional
+        8 +   // short_entry_notional
+        8 +   // tracked_long_size
+        8 +   // tracked_short_size
+        8 +   // referral_rewards
+        32 +  // fill_authority
+        8 +   // rebate_budget
+        IndexComponent::LEN * MAX_INDEX_COMPONENTS +  // index_components
+        1 +   // index_component_count
+        8 +   // contract_unit
+        8 +   // contract_multiplier
+        8 +   // lot_size
+        8 +   // tick_size
+        RiskTier::LEN * MAX_RISK_TIERS +  // risk_tiers
+        1 +   // risk_tier_count
+        32;   // padding for future use, grow with migrate_market instead of shrinking further
+
+    pub const VERSION: u8 = 8;
+
+    /// Upgrade a market written at `from_version` to the current layout, giving fields
+    /// appended since the defaults new markets get
+    pub fn upgrade_from(&mut self, from_version: u8, current_time: i64) {
+        // Fields appended in version 1 read as zero
+        if from_version < 1 {
+            self.mark_price_half_life = Self::DEFAULT_MARK_PRICE_HALF_LIFE;
+            self.reference_price = 0;
+            self.reference_price_updated_at = current_time;
+            self.price_band_bps = Self::DEFAULT_PRICE_BAND_BPS;
+        }
+
+        // Version 2 notional caps start disabled and version 3 markets start in fixed
+        // liquidation mode, which is what the zeroed realloc gives. Version 4 solvency
+        // aggregates start at zero; positions join them as they are migrated, and flows
+        // before this point are only visible in the vault's resynced balance. Version 5
+        // markets have no fill authority until one is set, so every fill stays a taker, and
+        // version 6 markets stay single-feed until an index basket is set. Version 7
+        // contract specs start unset, leaving sizes and limit prices unrestricted until set,
+        // and version 8 markets keep flat margin ratios until risk tiers are set
+
+        self.version = Self::VERSION;
+    }
 
     /// Convert commodity bytes to string
     pub fn commodity_str(&self) -> String {
@@ -82,6 +258,445 @@ impl Market {
         bytes
     }
 
+    /// Extra PDA seed distinguishing a dated future from the perpetual on the same mint.
+    /// Empty for perpetuals, so their address is unchanged.
+    pub fn series_seed(&self) -> Vec<u8> {
+        match self.market_type {
+            MarketType::Perpetual => Vec::new(),
+            MarketType::DatedFuture => self.expiry_timestamp.to_le_bytes().to_vec(),
+        }
+    }
+
+    pub const MAX_AUCTION_DISCOUNT_BPS: u16 = 5000;  // 50%
+
+    /// Liquidator discount (basis points) an auction started at `started_at` offers now,
+    /// rising linearly from the floor to the cap over `auction_duration`
+    pub fn auction_discount_bps(&self, started_at: i64, current_time: i64) -> u16 {
+        let elapsed = current_time.saturating_sub(started_at).max(0) as u64;
+        let duration = self.auction_duration.max(1) as u64;
+        let range = self.auction_cap_discount_bps.saturating_sub(self.auction_floor_discount_bps) as u64;
+        self.auction_floor_discount_bps + (range * elapsed.min(duration) / duration) as u16
+    }
+
+    pub fn is_expired(&self, current_time: i64) -> bool {
+        self.market_type == MarketType::DatedFuture && current_time >= self.expiry_timestamp
+    }
+
+    pub const DEFAULT_MARK_PRICE_HALF_LIFE: u32 = 60;
+
+    /// Most weight one sample carries, however long since the previous one
+    pub const MAX_MARK_SAMPLE_WEIGHT: u64 = EMA_SCALE / 2;
+
+    /// Fold an execution price (oracle plus any book/AMM premium) into the EMA mark price.
+    /// A sample's weight grows with the time since the previous one up to one half after
+    /// `mark_price_half_life` seconds, and stays there, so a single print after a quiet
+    /// spell moves the mark at most halfway. Further samples in the same second are ignored.
+    pub fn update_mark_price(&mut self, price: u64, current_time: i64) {
+        let elapsed = current_time.saturating_sub(self.mark_price_updated_at);
+
+        if self.mark_price == 0 || self.mark_price_half_life == 0 {
+            self.mark_price = price;
+        } else if elapsed > 0 {
+            let decay = ema_decay(elapsed, self.mark_price_half_life as i64)
+                .max(EMA_SCALE - Self::MAX_MARK_SAMPLE_WEIGHT);
+            self.mark_price = ((self.mark_price as u128 * decay as u128
+                + price as u128 * (EMA_SCALE - decay) as u128)
+                / EMA_SCALE as u128) as u64;
+        } else {
+            return;
+        }
+
+        self.mark_price_updated_at = current_time;
+    }
+
+    pub const DEFAULT_PRICE_BAND_BPS: u16 = 1500;  // 15%
+    pub const PRICE_BAND_WINDOW: i64 = 900;         // Reference price re-anchors every 15 minutes
+
+    /// Check a price against the band around the reference price, tripping the breaker
+    /// on a breach. Returns false while the market is reduce-only.
+    /// The reference re-anchors to the current price at most once per `PRICE_BAND_WINDOW`.
+    pub fn update_price_band(&mut self, price: u64, current_time: i64) -> bool {
+        if self.circuit_breaker_tripped {
+            return false;
+        }
+        if self.price_band_bps == 0 {
+            return true;
+        }
+
+        if self.reference_price == 0 {
+            self.reset_reference_price(price, current_time);
+            return true;
+        }
+
+        if !self.within_price_band(price) {
+            self.circuit_breaker_tripped = true;
+            return false;
+        }
+
+        if current_time - self.reference_price_updated_at >= Self::PRICE_BAND_WINDOW {
+            self.reset_reference_price(price, current_time);
+        }
+        true
+    }
+
+    /// Check a price against the band without tripping the breaker, for instructions
+    /// that fail on a breach and so would roll the trip back
+    pub fn within_price_band(&self, price: u64) -> bool {
+        if self.circuit_breaker_tripped {
+            return false;
+        }
+        if self.price_band_bps == 0 || self.reference_price == 0 {
+            return true;
+        }
+
+        match Price(price).deviation_from(Price(self.reference_price), Rounding::Up) {
+            Ok(deviation) => deviation <= Bps::from(self.price_band_bps),
+            Err(_) => false,
+        }
+    }
+
+    pub fn reset_reference_price(&mut self, price: u64, current_time: i64) {
+        self.reference_price = price;
+        self.reference_price_updated_at = current_time;
+    }
+
+    /// Active components of the index basket, empty for single-feed markets
+    pub fn index_components(&self) -> &[IndexComponent] {
+        &self.index_components[..self.index_component_count as usize]
+    }
+
+    /// Split off the leading remaining accounts holding this market's index feeds after
+    /// the first, returning them and the accounts left for the instruction
+    pub fn split_index_feeds<'a, 'info>(
+        &self,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+        let feed_count = self.index_components().len().saturating_sub(1);
+        require!(remaining_accounts.len() >= feed_count, PerpsError::IndexFeedMismatch);
+        Ok(remaining_accounts.split_at(feed_count))
+    }
+
+    /// Price of the market's underlying, which every price read goes through. A
+    /// single-feed market reads `price_feed`; an index market sums its weighted
+    /// components, the first read from `price_feed` and the rest from the leading
+    /// `remaining_accounts` in basket order.
+    pub fn index_price(
+        &self,
+        price_feed: &AccountInfo,
+        remaining_accounts: &[AccountInfo],
+        current_time: i64,
+    ) -> Result<u64> {
+        let Some((first, rest)) = self.index_components().split_first() else {
+            return pyth_price(price_feed, current_time);
+        };
+
+        let (index_feeds, _) = self.split_index_feeds(remaining_accounts)?;
+        let mut price = first.read(price_feed, current_time)?;
+        for (component, feed) in rest.iter().zip(index_feeds) {
+            price = price
+                .checked_add(component.read(feed, current_time)?)
+                .ok_or(PerpsError::MathOverflow)?;
+        }
+        require!(price > 0, PerpsError::InvalidOraclePrice);
+        Ok(price)
+    }
+
+    /// Notional value (6 decimals) of `size` base units at `price`, rounded up so caps
+    /// bind early
+    pub fn notional(size: u64, price: u64) -> u128 {
+        Price(price).notional_wide(Size(size), Rounding::Up)
+    }
+
+    /// Notional of `size` at `price` and the initial margin it requires, both rounded up
+    pub fn initial_margin(&self, size: u64, price: u64) -> Result<(u64, u64)> {
+        self.added_initial_margin(size, price, 0)
+    }
+
+    /// Notional of `size` added at `price` to a position of `existing_notional`, and the
+    /// initial margin it requires at the risk tier of the combined position
+    pub fn added_initial_margin(&self, size: u64, price: u64, existing_notional: u64) -> Result<(u64, u64)> {
+        let notional = Price(price)
+            .notional(Size(size), Rounding::Up)
+            .map_err(PerpsError::from)?;
+        let position_notional = existing_notional
+            .checked_add(notional)
+            .ok_or(PerpsError::MathOverflow)?;
+        let required = Bps::from(self.initial_margin_ratio_for(position_notional))
+            .of(notional, Rounding::Up)
+            .map_err(PerpsError::from)?;
+        Ok((notional, required))
+    }
+
+    pub fn risk_tiers(&self) -> &[RiskTier] {
+        &self.risk_tiers[..self.risk_tier_count as usize]
+    }
+
+    /// Tiers ascend in notional, never loosen as they go, and stay within the
+    /// market-wide leverage and maintenance margin
+    pub fn valid_risk_tiers(&self, tiers: &[RiskTier]) -> bool {
+        tiers.len() <= MAX_RISK_TIERS
+            && tiers.iter().all(|tier| {
+                tier.max_notional > 0
+                    && tier.max_leverage > 0
+                    && tier.max_leverage <= self.max_leverage
+                    && tier.maintenance_margin_ratio >= self.maintenance_margin_ratio
+                    && tier.maintenance_margin_ratio < tier.initial_margin_ratio().max(self.initial_margin_ratio)
+            })
+            && tiers.windows(2).all(|pair| {
+                pair[0].max_notional < pair[1].max_notional
+                    && pair[0].max_leverage >= pair[1].max_leverage
+                    && pair[0].maintenance_margin_ratio <= pair[1].maintenance_margin_ratio
+            })
+    }
+
+    /// Tier covering a position of `notional` entry notional. Positions above the last
+    /// tier, which can't be opened or grown but can change hands, get the last tier.
+    fn risk_tier(&self, notional: u64) -> Option<&RiskTier> {
+        let tiers = self.risk_tiers();
+        tiers
+            .iter()
+            .find(|tier| notional <= tier.max_notional)
+            .or(tiers.last())
+    }
+
+    /// Whether a position of `notional` may be opened or grown to
+    pub fn within_risk_tiers(&self, notional: u64) -> bool {
+        match self.risk_tiers().last() {
+            Some(tier) => notional <= tier.max_notional,
+            None => true,
+        }
+    }
+
+    /// Most leverage a position of `notional` may open with
+    pub fn max_leverage_for(&self, notional: u64) -> u32 {
+        self.risk_tier(notional)
+            .map_or(self.max_leverage, |tier| tier.max_leverage.min(self.max_leverage))
+    }
+
+    /// Initial margin ratio for a position of `notional`, never below the market-wide one
+    pub fn initial_margin_ratio_for(&self, notional: u64) -> u32 {
+        self.risk_tier(notional)
+            .map_or(self.initial_margin_ratio, |tier| tier.initial_margin_ratio().max(self.initial_margin_ratio))
+    }
+
+    /// Maintenance margin ratio for a position of `notional` entry notional
+    pub fn maintenance_margin_for(&self, notional: u64) -> u32 {
+        self.risk_tier(notional)
+            .map_or(self.maintenance_margin_ratio, |tier| tier.maintenance_margin_ratio)
+    }
+
+    /// Maintenance margin ratio the liquidation check applies to `position`
+    pub fn position_maintenance_margin(&self, position: &Position) -> Result<u32> {
+        Ok(self.maintenance_margin_for(position.notional_value()?))
+    }
+
+    /// Whether adding `size` on `side` keeps that side within the notional cap
+    pub fn within_notional_cap(&self, side: Side, size: u64, price: u64) -> bool {
+        if self.max_open_interest_notional == 0 {
+            return true;
+        }
+        let side_oi = match side {
+            Side::Long => self.long_open_interest,
+            Side::Short => self.short_open_interest,
+        };
+        Self::notional(side_oi.saturating_add(size), price) <= self.max_open_interest_notional as u128
+    }
+
+    /// Whether adding `size` on `side` keeps the long/short imbalance within its cap.
+    /// Trades that narrow the imbalance are always allowed.
+    pub fn within_imbalance_cap(&self, side: Side, size: u64, price: u64) -> bool {
+        if self.max_open_interest_imbalance_notional == 0 {
+            return true;
+        }
+        let (long_oi, short_oi) = match side {
+            Side::Long => (self.long_open_interest.saturating_add(size), self.short_open_interest),
+            Side::Short => (self.long_open_interest, self.short_open_interest.saturating_add(size)),
+        };
+        let before = self.long_open_interest.abs_diff(self.short_open_interest);
+        let after = long_oi.abs_diff(short_oi);
+        after <= before
+            || Self::notional(after, price) <= self.max_open_interest_imbalance_notional as u128
+    }
+
+    /// Whether a user holding `user_open_interest` may add `size` more
+    pub fn within_user_cap(&self, user_open_interest: u64, size: u64, price: u64) -> bool {
+        self.max_user_open_interest_notional == 0
+            || Self::notional(user_open_interest.saturating_add(size), price)
+                <= self.max_user_open_interest_notional as u128
+    }
+
+    /// Enforce the notional, imbalance and per-user caps for `size` more on `side`,
+    /// counting the size against the user once it passes
+    pub fn apply_open_interest_caps(
+        &self,
+        side: Side,
+        size: u64,
+        price: u64,
+        user_market: &mut UserMarketAccount,
+    ) -> Result<()> {
+        require!(
+            self.within_notional_cap(side, size, price),
+            PerpsError::OpenInterestCapExceeded
+        );
+        require!(
+            self.within_imbalance_cap(side, size, price),
+            PerpsError::OpenInterestImbalanceExceeded
+        );
+        require!(
+            self.within_user_cap(user_market.open_interest, size, price),
+            PerpsError::UserOpenInterestCapExceeded
+        );
+        user_market.add_open_interest(size)
+    }
+
+    /// Add an open position to the solvency aggregates. Positions written before
+    /// `Position::TRACKED_VERSION` are left out until migrated.
+    pub fn track_position(&mut self, position: &Position) -> Result<()> {
+        if position.version < Position::TRACKED_VERSION {
+            return Ok(());
+        }
+        self.position_collateral = self.position_collateral.saturating_add(position.collateral);
+        self.position_margin_credit = self.position_margin_credit.saturating_add(position.margin_credit);
+        self.add_exposure(position.side, position.size, position.notional_value()?);
+        Ok(())
+    }
+
+    /// Remove a position from the solvency aggregates before it changes or closes
+    pub fn untrack_position(&mut self, position: &Position) -> Result<()> {
+        if position.version < Position::TRACKED_VERSION {
+            return Ok(());
+        }
+        self.position_collateral = self.position_collateral.saturating_sub(position.collateral);
+        self.position_margin_credit = self.position_margin_credit.saturating_sub(position.margin_credit);
+        self.remove_exposure(position.side, position.size, position.notional_value()?);
+        Ok(())
+    }
+
+    /// Add a spread leg to the solvency aggregates with the collateral this market holds
+    /// for it, which is all of it for leg A and none for leg B
+    pub fn track_spread_leg(&mut self, leg: &SpreadLeg, collateral: u64) -> Result<()> {
+        self.position_collateral = self.position_collateral.saturating_add(collateral);
+        self.add_exposure(leg.side, leg.size, leg.notional_value()?);
+        Ok(())
+    }
+
+    pub fn untrack_spread_leg(&mut self, leg: &SpreadLeg, collateral: u64) -> Result<()> {
+        self.position_collateral = self.position_collateral.saturating_sub(collateral);
+        self.remove_exposure(leg.side, leg.size, leg.notional_value()?);
+        Ok(())
+    }
+
+    fn add_exposure(&mut self, side: Side, size: u64, notional: u64) {
+        match side {
+            Side::Long => {
+                self.long_entry_notional = self.long_entry_notional.saturating_add(notional);
+                self.tracked_long_size = self.tracked_long_size.saturating_add(size);
+            }
+            Side::Short => {
+                self.short_entry_notional = self.short_entry_notional.saturating_add(notional);
+                self.tracked_short_size = self.tracked_short_size.saturating_add(size);
+            }
+        }
+    }
+
+    fn remove_exposure(&mut self, side: Side, size: u64, notional: u64) {
+        match side {
+            Side::Long => {
+                self.long_entry_notional = self.long_entry_notional.saturating_sub(notional);
+                self.tracked_long_size = self.tracked_long_size.saturating_sub(size);
+            }
+            Side::Short => {
+                self.short_entry_notional = self.short_entry_notional.saturating_sub(notional);
+                self.tracked_short_size = self.tracked_short_size.saturating_sub(size);
+            }
+        }
+    }
+
+    /// Unrealized PnL of all tracked positions at `price`, owed to traders when positive
+    pub fn unrealized_pnl(&self, price: u64) -> i64 {
+        // Longs valued up and shorts down, so the amount owed to traders errs high
+        let long_value = Price(price).notional_wide(Size(self.tracked_long_size), Rounding::Up) as i128;
+        let short_value = Price(price).notional_wide(Size(self.tracked_short_size), Rounding::Down) as i128;
+        let pnl = (long_value - self.long_entry_notional as i128)
+            + (self.short_entry_notional as i128 - short_value);
+        pnl.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// A maker never pays more than a taker, and a rebate never exceeds the taker fee
+    /// funding it
+    pub fn valid_fees(taker_fee: u32, maker_fee: i32) -> bool {
+        maker_fee as i64 <= taker_fee as i64 && -(maker_fee as i64) <= taker_fee as i64
+    }
+
+    /// A contract is a whole number of lots
+    pub fn valid_contract_specs(contract_multiplier: u64, lot_size: u64, tick_size: u64) -> bool {
+        lot_size > 0
+            && tick_size > 0
+            && contract_multiplier >= lot_size
+            && contract_multiplier.checked_rem(lot_size) == Some(0)
+    }
+
+    /// Whether `size` is a whole number of lots
+    pub fn is_lot_multiple(&self, size: u64) -> bool {
+        // No remainder, or no lot size at all
+        matches!(size.checked_rem(self.lot_size), None | Some(0))
+    }
+
+    /// Whether a limit price lands on a tick. The unbounded limits 0 and `u64::MAX`
+    /// always pass, so clients without a limit need not know the tick size.
+    pub fn is_on_tick(&self, price: u64) -> bool {
+        price == u64::MAX || matches!(price.checked_rem(self.tick_size), None | Some(0))
+    }
+
+    /// Fee charged on a fill of `notional`, and the maker rebate owed when the maker fee
+    /// is negative. Rebates are capped by the taker fees collected and still held.
+    pub fn fill_fee(&self, notional: u64, is_maker: bool) -> Result<(u64, u64)> {
+        if !is_maker {
+            let fee = Bps::from(self.taker_fee)
+                .of(notional, Rounding::Up)
+                .map_err(PerpsError::from)?;
+            return Ok((fee, 0));
+        }
+        if self.maker_fee >= 0 {
+            let fee = Bps::from(self.maker_fee.unsigned_abs())
+                .of(notional, Rounding::Up)
+                .map_err(PerpsError::from)?;
+            Ok((fee, 0))
+        } else {
+            let rebate = Bps::from(self.maker_fee.unsigned_abs())
+                .of(notional, Rounding::Down)
+                .map_err(PerpsError::from)?;
+            Ok((0, rebate.min(self.rebate_budget).min(self.insurance_fund)))
+        }
+    }
+
+    /// Add a taker fee, already in the insurance fund, to what rebates can draw on
+    pub fn collect_taker_fee(&mut self, fee: u64) {
+        self.rebate_budget = self.rebate_budget.saturating_add(fee);
+    }
+
+    /// Take a maker rebate out of the collected taker fees
+    pub fn pay_maker_rebate(&mut self, rebate: u64) {
+        self.rebate_budget = self.rebate_budget.saturating_sub(rebate);
+        self.insurance_fund = self.insurance_fund.saturating_sub(rebate);
+    }
+
+    pub fn credit_user_collateral(&mut self, amount: u64) {
+        self.user_collateral = self.user_collateral.saturating_add(amount as i64);
+    }
+
+    pub fn debit_user_collateral(&mut self, amount: u64) {
+        self.user_collateral = self.user_collateral.saturating_sub(amount as i64);
+    }
+
+    /// Vault balance owed to free collateral, the insurance fund and referral rewards,
+    /// which global settlement claims are not paid from
+    pub fn reserved_balance(&self) -> u64 {
+        (self.user_collateral.max(0) as u64)
+            .saturating_add(self.insurance_fund)
+            .saturating_add(self.referral_rewards.max(0) as u64)
+    }
+
     pub fn can_increase_long_oi(&self, size: u64) -> bool {
         self.long_open_interest.checked_add(size)
             .map(|new_oi| new_oi <= self.max_open_interest)
@@ -95,16 +710,484 @@ impl Market {
     }
 }
 
+/// Weight of the previous EMA value after `elapsed` seconds: 2^(-elapsed / half_life),
+/// exact at whole half-lives and linear in between.
+fn ema_decay(elapsed: i64, half_life: i64) -> u64 {
+    let halvings = elapsed / half_life;
+    if halvings >= 20 {
+        return 0;
+    }
+
+    let whole = EMA_SCALE >> halvings;
+    let remainder = (elapsed % half_life) as u64;
+    whole - whole * remainder / (2 * half_life as u64)
+}
+
 #[account]
 #[derive(Default)]
 pub struct Vault {
     pub market: Pubkey,
     pub collateral_mint: Pubkey,
     pub token_account: Pubkey,
-    pub total_deposits: u64,
+    pub total_deposits: u64,          // Balance the vault token account should hold from the program's own transfers
     pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
 }
 
 impl Vault {
-    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1 + 32;
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1 + 1 + 31;
+
+    /// Version 2 has the version 1 layout; from it on `total_deposits` follows every transfer
+    pub const VERSION: u8 = 2;
+
+    pub fn record_inflow(&mut self, amount: u64) {
+        self.total_deposits = self.total_deposits.saturating_add(amount);
+    }
+
+    pub fn record_outflow(&mut self, amount: u64) {
+        self.total_deposits = self.total_deposits.saturating_sub(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiered_market(tiers: &[RiskTier]) -> Market {
+        let mut market = Market {
+            max_leverage: 20_000,
+            initial_margin_ratio: 500,
+            maintenance_margin_ratio: 250,
+            ..Default::default()
+        };
+        market.risk_tiers[..tiers.len()].copy_from_slice(tiers);
+        market.risk_tier_count = tiers.len() as u8;
+        market
+    }
+
+    fn tier(max_notional: u64, max_leverage: u32, maintenance_margin_ratio: u32) -> RiskTier {
+        RiskTier { max_notional, max_leverage, maintenance_margin_ratio }
+    }
+
+    #[test]
+    fn test_auction_discount_over_time() {
+        let market = Market {
+            auction_floor_discount_bps: 100,
+            auction_cap_discount_bps: 500,
+            auction_duration: 400,
+            ..Default::default()
+        };
+
+        assert_eq!(market.auction_discount_bps(1_000, 1_000), 100);
+        assert_eq!(market.auction_discount_bps(1_000, 1_100), 200);
+        assert_eq!(market.auction_discount_bps(1_000, 1_399), 499);
+        assert_eq!(market.auction_discount_bps(1_000, 1_400), 500);
+        // Holds at the cap, and a clock behind the start reads as the floor
+        assert_eq!(market.auction_discount_bps(1_000, 5_000), 500);
+        assert_eq!(market.auction_discount_bps(1_000, 900), 100);
+    }
+
+    #[test]
+    fn test_valid_risk_tiers() {
+        let market = tiered_market(&[]);
+        let small = tier(1_000_000_000, 20_000, 250);
+        let large = tier(10_000_000_000, 10_000, 500);
+
+        assert!(market.valid_risk_tiers(&[]));
+        assert!(market.valid_risk_tiers(&[small, large]));
+
+        // Notional must ascend and tiers may only tighten
+        assert!(!market.valid_risk_tiers(&[large, small]));
+        assert!(!market.valid_risk_tiers(&[tier(1_000_000_000, 10_000, 500), tier(10_000_000_000, 20_000, 500)]));
+        assert!(!market.valid_risk_tiers(&[tier(1_000_000_000, 10_000, 750), tier(10_000_000_000, 10_000, 500)]));
+
+        // Within the market-wide leverage and maintenance margin, and below the tier's initial margin
+        assert!(!market.valid_risk_tiers(&[tier(1_000_000_000, 25_000, 250)]));
+        assert!(!market.valid_risk_tiers(&[tier(1_000_000_000, 20_000, 200)]));
+        assert!(!market.valid_risk_tiers(&[tier(1_000_000_000, 10_000, 1_000)]));
+        assert!(!market.valid_risk_tiers(&[tier(0, 20_000, 250)]));
+
+        let too_many: Vec<RiskTier> = (1..=MAX_RISK_TIERS as u64 + 1)
+            .map(|i| tier(i * 1_000_000_000, 10_000, 500))
+            .collect();
+        assert!(!market.valid_risk_tiers(&too_many));
+    }
+
+    #[test]
+    fn test_risk_tier_lookup() {
+        let market = tiered_market(&[tier(1_000_000_000, 20_000, 250), tier(10_000_000_000, 10_000, 500)]);
+
+        assert!(market.within_risk_tiers(10_000_000_000));
+        assert!(!market.within_risk_tiers(10_000_000_001));
+        assert!(tiered_market(&[]).within_risk_tiers(u64::MAX));
+
+        assert_eq!(market.max_leverage_for(1_000_000_000), 20_000);
+        assert_eq!(market.max_leverage_for(1_000_000_001), 10_000);
+        assert_eq!(market.initial_margin_ratio_for(500_000_000), 500);
+        assert_eq!(market.initial_margin_ratio_for(5_000_000_000), 1_000);
+
+        // Positions beyond the last tier keep its margin
+        assert_eq!(market.maintenance_margin_for(50_000_000_000), 500);
+        assert_eq!(tiered_market(&[]).maintenance_margin_for(50_000_000_000), 250);
+    }
+
+    #[test]
+    fn test_ema_decay() {
+        assert_eq!(ema_decay(0, 60), EMA_SCALE);
+        assert_eq!(ema_decay(30, 60), 750_000);
+        assert_eq!(ema_decay(60, 60), 500_000);
+        assert_eq!(ema_decay(90, 60), 375_000);
+        assert_eq!(ema_decay(120, 60), 250_000);
+        assert_eq!(ema_decay(20 * 60, 60), 0);
+    }
+
+    #[test]
+    fn test_mark_price_sample_weight_cap() {
+        let mut market = Market { mark_price_half_life: 60, ..Default::default() };
+
+        // The first sample sets the mark outright
+        market.update_mark_price(100_000_000, 1_000);
+        assert_eq!(market.mark_price, 100_000_000);
+
+        // Half a half-life later a sample carries a quarter of the weight
+        market.update_mark_price(200_000_000, 1_030);
+        assert_eq!(market.mark_price, 125_000_000);
+
+        // Further samples in the same second are ignored
+        market.update_mark_price(1_000_000_000, 1_030);
+        assert_eq!(market.mark_price, 125_000_000);
+
+        // However quiet the market was, one print moves the mark at most halfway
+        market.update_mark_price(25_000_000, 100_000);
+        assert_eq!(market.mark_price, 75_000_000);
+        assert_eq!(market.mark_price_updated_at, 100_000);
+    }
+
+    #[test]
+    fn test_within_imbalance_cap() {
+        let mut market = Market {
+            long_open_interest: 3_000_000,
+            short_open_interest: 1_000_000,
+            ..Default::default()
+        };
+        // Uncapped markets accept anything
+        assert!(market.within_imbalance_cap(Side::Long, u64::MAX, 40_000_000));
+
+        // 2 units long of imbalance at 40 is 80 against a cap of 100
+        market.max_open_interest_imbalance_notional = 100_000_000;
+        assert!(market.within_imbalance_cap(Side::Long, 500_000, 40_000_000));
+        assert!(!market.within_imbalance_cap(Side::Long, 1_000_000, 40_000_000));
+
+        // Trades that leave the imbalance no wider are allowed; flipping past the cap is not
+        assert!(market.within_imbalance_cap(Side::Short, 4_000_000, 40_000_000));
+        assert!(!market.within_imbalance_cap(Side::Short, 5_000_000, 40_000_000));
+
+        // An imbalance already over the cap may still narrow
+        market.long_open_interest = 10_000_000;
+        assert!(market.within_imbalance_cap(Side::Short, 1_000_000, 40_000_000));
+    }
+
+    #[test]
+    fn test_within_notional_cap() {
+        let mut market = Market {
+            long_open_interest: 2_000_000,
+            short_open_interest: 500_000,
+            ..Default::default()
+        };
+        assert!(market.within_notional_cap(Side::Long, u64::MAX, 40_000_000));
+
+        // Each side is capped on its own: 2 units long at 40 is 80 against a cap of 100
+        market.max_open_interest_notional = 100_000_000;
+        assert!(market.within_notional_cap(Side::Long, 500_000, 40_000_000));
+        assert!(!market.within_notional_cap(Side::Long, 600_000, 40_000_000));
+        assert!(market.within_notional_cap(Side::Short, 2_000_000, 40_000_000));
+
+        // The cap is in notional, so it tightens as the price rises
+        assert!(!market.within_notional_cap(Side::Long, 0, 60_000_000));
+    }
+
+    #[test]
+    fn test_within_user_cap() {
+        let mut market = Market::default();
+        assert!(market.within_user_cap(u64::MAX, 1, 40_000_000));
+
+        // 1 unit held plus 1.5 more at 40 is exactly the cap of 100
+        market.max_user_open_interest_notional = 100_000_000;
+        assert!(market.within_user_cap(1_000_000, 1_500_000, 40_000_000));
+        assert!(!market.within_user_cap(1_000_000, 1_500_001, 40_000_000));
+    }
+
+    #[test]
+    fn test_apply_open_interest_caps() {
+        let market = Market {
+            long_open_interest: 2_000_000,
+            max_open_interest_notional: 200_000_000,
+            max_open_interest_imbalance_notional: 120_000_000,
+            max_user_open_interest_notional: 100_000_000,
+            ..Default::default()
+        };
+        let mut user_market = UserMarketAccount { open_interest: 1_000_000, ..Default::default() };
+
+        // Passing every cap counts the size against the user
+        market.apply_open_interest_caps(Side::Long, 1_000_000, 40_000_000, &mut user_market).unwrap();
+        assert_eq!(user_market.open_interest, 2_000_000);
+
+        // Each breach surfaces its own error and leaves the user's count alone
+        let err = market.apply_open_interest_caps(Side::Long, 3_500_000, 40_000_000, &mut user_market).unwrap_err();
+        assert_eq!(err, PerpsError::OpenInterestCapExceeded.into());
+        let err = market.apply_open_interest_caps(Side::Long, 1_500_000, 40_000_000, &mut user_market).unwrap_err();
+        assert_eq!(err, PerpsError::OpenInterestImbalanceExceeded.into());
+        let err = market.apply_open_interest_caps(Side::Short, 1_000_000, 40_000_000, &mut user_market).unwrap_err();
+        assert_eq!(err, PerpsError::UserOpenInterestCapExceeded.into());
+        assert_eq!(user_market.open_interest, 2_000_000);
+    }
+
+    #[test]
+    fn test_price_band() {
+        let mut market = Market { price_band_bps: 1500, ..Default::default() };
+
+        // The first price anchors the reference
+        assert!(market.update_price_band(100_000_000, 1_000));
+        assert_eq!(market.reference_price, 100_000_000);
+
+        // Up to 15% either way passes without moving the reference inside the window
+        assert!(market.update_price_band(115_000_000, 1_100));
+        assert!(market.update_price_band(85_000_000, 1_200));
+        assert_eq!(market.reference_price, 100_000_000);
+
+        // After the window it re-anchors to the current price
+        assert!(market.update_price_band(110_000_000, 1_000 + Market::PRICE_BAND_WINDOW));
+        assert_eq!(market.reference_price, 110_000_000);
+        assert_eq!(market.reference_price_updated_at, 1_000 + Market::PRICE_BAND_WINDOW);
+
+        // A breach is only reported by the read-only check, and trips the breaker on update
+        assert!(!market.within_price_band(127_000_000));
+        assert!(!market.circuit_breaker_tripped);
+        assert!(!market.update_price_band(127_000_000, 2_000));
+        assert!(market.circuit_breaker_tripped);
+
+        // Reduce-only until reset, even back inside the band
+        assert!(!market.within_price_band(110_000_000));
+        assert!(!market.update_price_band(110_000_000, 2_100));
+    }
+
+    #[test]
+    fn test_price_band_disabled() {
+        let mut market = Market::default();
+        assert!(market.update_price_band(100_000_000, 1_000));
+        assert!(market.update_price_band(1_000_000_000, 1_001));
+        assert_eq!(market.reference_price, 0);
+    }
+
+    #[test]
+    fn test_fill_fee() {
+        let mut market = Market { taker_fee: 5, maker_fee: 2, ..Default::default() };
+
+        // Takers and fee-paying makers pay their own rate, rounded up
+        assert_eq!(market.fill_fee(100_000_000, false).unwrap(), (50_000, 0));
+        assert_eq!(market.fill_fee(100_000_000, true).unwrap(), (20_000, 0));
+        assert_eq!(market.fill_fee(1, false).unwrap(), (1, 0));
+
+        // A negative maker fee pays a rebate instead, rounded down and only out of
+        // taker fees collected and still in the insurance fund
+        market.maker_fee = -2;
+        assert_eq!(market.fill_fee(100_000_000, true).unwrap(), (0, 0));
+        market.collect_taker_fee(15_000);
+        market.insurance_fund = 50_000;
+        assert_eq!(market.fill_fee(100_000_000, true).unwrap(), (0, 15_000));
+        assert_eq!(market.fill_fee(50_000_000, true).unwrap(), (0, 10_000));
+        assert_eq!(market.fill_fee(1, true).unwrap(), (0, 0));
+        market.insurance_fund = 5_000;
+        assert_eq!(market.fill_fee(50_000_000, true).unwrap(), (0, 5_000));
+
+        // Paying a rebate draws down both the budget and the fund
+        market.insurance_fund = 50_000;
+        market.pay_maker_rebate(10_000);
+        assert_eq!(market.rebate_budget, 5_000);
+        assert_eq!(market.insurance_fund, 40_000);
+    }
+
+    #[test]
+    fn test_valid_fees() {
+        assert!(Market::valid_fees(5, 2));
+        assert!(Market::valid_fees(5, 5));
+        assert!(Market::valid_fees(5, -5));
+        // Makers never pay more than takers, and rebates never exceed the taker fee
+        assert!(!Market::valid_fees(5, 6));
+        assert!(!Market::valid_fees(5, -6));
+    }
+
+    #[test]
+    fn test_valid_contract_specs() {
+        // 1,000 unit contracts in lots of 0.01 with a 0.01 tick
+        assert!(Market::valid_contract_specs(1_000_000_000, 10_000, 10_000));
+        assert!(Market::valid_contract_specs(10_000, 10_000, 1));
+
+        assert!(!Market::valid_contract_specs(1_000_000_000, 0, 10_000));
+        assert!(!Market::valid_contract_specs(1_000_000_000, 10_000, 0));
+        // A contract is a whole number of lots, at least one
+        assert!(!Market::valid_contract_specs(1_000_000_000, 30_000, 10_000));
+        assert!(!Market::valid_contract_specs(5_000, 10_000, 10_000));
+    }
+
+    #[test]
+    fn test_lot_and_tick_checks() {
+        let mut market = Market::default();
+
+        // Markets without contract specs accept any size and price
+        assert!(market.is_lot_multiple(1));
+        assert!(market.is_on_tick(1));
+
+        market.lot_size = 10_000;
+        market.tick_size = 10_000;
+        assert!(market.is_lot_multiple(2_500_000));
+        assert!(!market.is_lot_multiple(2_505_000));
+        assert!(market.is_lot_multiple(0));
+        assert!(market.is_on_tick(71_230_000));
+        assert!(!market.is_on_tick(71_234_567));
+
+        // Unbounded limits pass whatever the tick
+        assert!(market.is_on_tick(0));
+        assert!(market.is_on_tick(u64::MAX));
+    }
+
+    #[test]
+    fn test_solvency_aggregates() {
+        let mut market = Market::default();
+        let long = Position {
+            side: Side::Long,
+            size: 2_000_000,
+            entry_price: 50_000_000,
+            collateral: 20_000_000,
+            margin_credit: 5_000_000,
+            version: Position::VERSION,
+            ..Default::default()
+        };
+        let short = Position {
+            side: Side::Short,
+            size: 1_000_000,
+            entry_price: 60_000_000,
+            collateral: 10_000_000,
+            version: Position::VERSION,
+            ..Default::default()
+        };
+        market.track_position(&long).unwrap();
+        market.track_position(&short).unwrap();
+
+        // Positions from before tracking stay out until migrated
+        market.track_position(&Position { version: Position::TRACKED_VERSION - 1, ..long }).unwrap();
+
+        assert_eq!(market.position_collateral, 30_000_000);
+        assert_eq!(market.position_margin_credit, 5_000_000);
+        assert_eq!(market.long_entry_notional, 100_000_000);
+        assert_eq!(market.short_entry_notional, 60_000_000);
+        assert_eq!(market.tracked_long_size, 2_000_000);
+        assert_eq!(market.tracked_short_size, 1_000_000);
+
+        // At 55 the longs are up 10 and the short up 5
+        assert_eq!(market.unrealized_pnl(55_000_000), 15_000_000);
+        // At 40 the longs' loss of 20 cancels the short's gain
+        assert_eq!(market.unrealized_pnl(40_000_000), 0);
+
+        // Spread legs count their exposure with whatever collateral this market holds
+        let leg = SpreadLeg { side: Side::Long, size: 1_000_000, entry_price: 40_000_000, ..Default::default() };
+        market.track_spread_leg(&leg, 0).unwrap();
+        assert_eq!(market.position_collateral, 30_000_000);
+        assert_eq!(market.unrealized_pnl(55_000_000), 30_000_000);
+        market.untrack_spread_leg(&leg, 0).unwrap();
+
+        market.untrack_position(&long).unwrap();
+        assert_eq!(market.position_collateral, 10_000_000);
+        assert_eq!(market.position_margin_credit, 0);
+        assert_eq!(market.tracked_long_size, 0);
+        assert_eq!(market.unrealized_pnl(55_000_000), 5_000_000);
+    }
+
+    #[test]
+    fn test_unrealized_pnl_rounds_against_vault() {
+        // One base unit at 1.5 is worth 1.5 base units of collateral; what traders are
+        // owed rounds up and what they owe rounds down
+        let long = Market { tracked_long_size: 1, ..Default::default() };
+        assert_eq!(long.unrealized_pnl(1_500_000), 2);
+        let short = Market { tracked_short_size: 1, ..Default::default() };
+        assert_eq!(short.unrealized_pnl(1_500_000), -1);
+    }
+
+    fn component(weight: u64) -> IndexComponent {
+        IndexComponent { price_feed: Pubkey::new_unique(), weight }
+    }
+
+    #[test]
+    fn test_index_component_weighting() {
+        // Half a unit of a 70 commodity and two of a 3.5 one
+        let heavy = component(500_000);
+        let light = component(2_000_000);
+        assert_eq!(heavy.value(70_000_000).unwrap(), 35_000_000);
+        assert_eq!(light.value(3_500_000).unwrap(), 7_000_000);
+
+        // Contributions round down
+        assert_eq!(component(1).value(999_999).unwrap(), 0);
+        assert_eq!(component(333_333).value(3_000_000).unwrap(), 999_999);
+    }
+
+    #[test]
+    fn test_valid_basket() {
+        let a = component(500_000);
+        let b = component(2_000_000);
+        assert!(IndexComponent::valid_basket(&[a]));
+        assert!(IndexComponent::valid_basket(&[a, b, component(1), component(1)]));
+
+        assert!(!IndexComponent::valid_basket(&[]));
+        assert!(!IndexComponent::valid_basket(&[a, b, component(1), component(1), component(1)]));
+        // Every component is weighted, and no feed appears twice
+        assert!(!IndexComponent::valid_basket(&[a, component(0)]));
+        assert!(!IndexComponent::valid_basket(&[a, b, IndexComponent { weight: 1, ..a }]));
+    }
+
+    #[test]
+    fn test_split_index_feeds() {
+        let mut market = Market::default();
+        assert!(market.index_components().is_empty());
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = [];
+        let feed = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false, 0);
+        let remaining = [feed.clone(), feed.clone(), feed];
+
+        // Single-feed markets leave every remaining account to the instruction
+        let (feeds, rest) = market.split_index_feeds(&remaining).unwrap();
+        assert_eq!((feeds.len(), rest.len()), (0, 3));
+
+        // The first component is read from the price feed, the others lead
+        market.index_components[..3].copy_from_slice(&[component(1), component(1), component(1)]);
+        market.index_component_count = 3;
+        assert_eq!(market.index_components().len(), 3);
+        let (feeds, rest) = market.split_index_feeds(&remaining).unwrap();
+        assert_eq!((feeds.len(), rest.len()), (2, 1));
+
+        assert_eq!(
+            market.split_index_feeds(&remaining[..1]).unwrap_err(),
+            PerpsError::IndexFeedMismatch.into()
+        );
+    }
+
+    #[test]
+    fn test_upgrade_from() {
+        // Version 0 markets get the mark and band defaults new markets start with
+        let mut market = Market::default();
+        market.upgrade_from(0, 1_700_000_000);
+        assert_eq!(market.version, Market::VERSION);
+        assert_eq!(market.mark_price_half_life, Market::DEFAULT_MARK_PRICE_HALF_LIFE);
+        assert_eq!(market.price_band_bps, Market::DEFAULT_PRICE_BAND_BPS);
+        assert_eq!(market.reference_price_updated_at, 1_700_000_000);
+
+        // Later versions keep what was set on them
+        let mut market = Market { version: 1, mark_price_half_life: 30, price_band_bps: 0, ..Default::default() };
+        market.upgrade_from(1, 1_700_000_000);
+        assert_eq!(market.version, Market::VERSION);
+        assert_eq!(market.mark_price_half_life, 30);
+        assert_eq!(market.price_band_bps, 0);
+        assert_eq!(market.reference_price_updated_at, 0);
+    }
 }
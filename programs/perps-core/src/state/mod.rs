@@ -1,7 +1,29 @@
+pub mod collateral;
+pub mod competition;
+pub mod crank;
+pub mod futures;
+pub mod liquidation;
 pub mod market;
+pub mod oracle;
 pub mod position;
 pub mod referral;
+pub mod schedule;
+pub mod settlement;
+pub mod spread;
+pub mod trigger;
+pub mod user_market;
 
+pub use collateral::*;
+pub use competition::*;
+pub use crank::*;
+pub use futures::*;
+pub use liquidation::*;
 pub use market::*;
+pub use oracle::*;
 pub use position::*;
 pub use referral::*;
+pub use schedule::*;
+pub use settlement::*;
+pub use spread::*;
+pub use trigger::*;
+pub use user_market::*;
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use perps_math::{scale_price, Rounding};
+use pyth_sdk_solana::load_price_feed_from_account_info;
+use crate::errors::PerpsError;
+
+/// Most feeds an index market's basket can hold
+pub const MAX_INDEX_COMPONENTS: usize = 4;
+
+/// Oldest Pyth price accepted, in seconds
+pub const MAX_PRICE_AGE: u64 = 60;
+
+/// One feed of an index market's basket
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct IndexComponent {
+    pub price_feed: Pubkey,
+    pub weight: u64,                  // Units of this commodity per index unit (6 decimals)
+}
+
+impl IndexComponent {
+    pub const LEN: usize = 32 + 8;
+
+    /// A basket holds one to `MAX_INDEX_COMPONENTS` distinct feeds, each weighted
+    pub fn valid_basket(components: &[IndexComponent]) -> bool {
+        (1..=MAX_INDEX_COMPONENTS).contains(&components.len())
+            && components.iter().enumerate().all(|(i, component)| {
+                component.weight > 0
+                    && components[..i].iter().all(|other| other.price_feed != component.price_feed)
+            })
+    }
+
+    /// This component's contribution to the index at `price`, rounded down
+    pub fn value(&self, price: u64) -> Result<u64> {
+        Ok(perps_math::mul_div(price, self.weight, perps_math::PRICE_SCALE, Rounding::Down)
+            .map_err(PerpsError::from)?)
+    }
+
+    /// Read this component's contribution from `price_feed`, which must be its feed
+    pub fn read(&self, price_feed: &AccountInfo, current_time: i64) -> Result<u64> {
+        require_keys_eq!(price_feed.key(), self.price_feed, PerpsError::IndexFeedMismatch);
+        self.value(pyth_price(price_feed, current_time)?)
+    }
+}
+
+/// Pyth price no older than `MAX_PRICE_AGE`, converted to 6 decimals
+pub fn pyth_price(price_feed: &AccountInfo, current_time: i64) -> Result<u64> {
+    let price_feed = load_price_feed_from_account_info(price_feed)
+        .map_err(|_| PerpsError::InvalidOraclePrice)?;
+
+    let price_data = price_feed
+        .get_price_no_older_than(current_time, MAX_PRICE_AGE)
+        .ok_or(PerpsError::StaleOraclePrice)?;
+
+    require!(price_data.price > 0, PerpsError::InvalidOraclePrice);
+
+    Ok(scale_price(price_data.price, price_data.expo, Rounding::Down).map_err(PerpsError::from)?)
+}
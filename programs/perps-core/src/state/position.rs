@@ -1,12 +1,37 @@
 use anchor_lang::prelude::*;
+use perps_math::{Bps, Price, Rounding, Size};
+use crate::errors::PerpsError;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
 pub enum Side {
     #[default]
     Long,
     Short,
 }
 
+impl Side {
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Long => Side::Short,
+            Side::Short => Side::Long,
+        }
+    }
+
+    /// Slippage check for a fill that adds exposure on this side.
+    /// Longs buy, so the price must not exceed the limit; shorts sell, so it must not fall below.
+    pub fn is_acceptable_price(&self, price: u64, acceptable_price: u64) -> bool {
+        match self {
+            Side::Long => price <= acceptable_price,
+            Side::Short => price >= acceptable_price,
+        }
+    }
+}
+
+/// Whether an instruction signed with `deadline` may still execute (0 = no deadline)
+pub fn is_within_deadline(deadline: i64, current_time: i64) -> bool {
+    deadline == 0 || current_time <= deadline
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
 pub enum PositionStatus {
     #[default]
@@ -45,7 +70,12 @@ pub struct Position {
 
     pub status: PositionStatus,
     pub execution_source: ExecutionSource,
+    pub position_id: u64,             // Per-user nonce used in the PDA seeds
+    pub margin_credit: u64,           // Collateral drawn from the owner's MarginAccount
+    pub is_wrapped: bool,             // Held as a position token; the token holder controls it
+    pub settlement_claim: u64,        // Equity owed after a global settlement, paid pro rata
     pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
 }
 
 impl Position {
@@ -63,53 +93,157 @@ impl Position {
         8 +   // last_updated_at
         1 +   // status
         1 +   // execution_source
+        8 +   // position_id
+        8 +   // margin_credit
+        1 +   // is_wrapped
+        8 +   // settlement_claim
         1 +   // bump
-        32;   // padding
+        1 +   // version
+        6;    // padding (reduced by 25 for position_id, margin_credit, is_wrapped and settlement_claim)
+
+    pub const VERSION: u8 = 2;
+
+    /// Version 2 has the version 1 layout; the bump marks positions counted in the
+    /// market's solvency aggregates
+    pub const TRACKED_VERSION: u8 = 2;
 
-    pub fn notional_value(&self) -> u64 {
-        // size * entry_price / 1_000_000 (adjust for decimals)
-        (self.size as u128)
-            .checked_mul(self.entry_price as u128)
-            .and_then(|v| v.checked_div(1_000_000))
-            .unwrap_or(0) as u64
+    /// Version 0 stored the bump where `position_id` now starts
+    pub const V0_BUMP_OFFSET: usize = 8 + 32 + 32 + 1 + 8 + 8 + 8 + 4 + 8 + 8 + 8 + 8 + 1 + 1;
+
+    /// Value at the entry price, rounded up so margin ratios err low
+    pub fn notional_value(&self) -> Result<u64> {
+        Ok(Price(self.entry_price)
+            .notional(Size(self.size), Rounding::Up)
+            .map_err(PerpsError::from)?)
     }
 
-    pub fn unrealized_pnl(&self, current_price: u64) -> i64 {
-        let entry = self.entry_price as i128;
-        let current = current_price as i128;
-        let size = self.size as i128;
+    /// PnL at `current_price`, rounded down so losses are never understated
+    pub fn unrealized_pnl(&self, current_price: u64) -> Result<i64> {
+        Ok(Size(self.size)
+            .pnl(
+                Price(self.entry_price),
+                Price(current_price),
+                self.side == Side::Long,
+                Rounding::Down,
+            )
+            .map_err(PerpsError::from)?)
+    }
 
-        let price_diff = match self.side {
-            Side::Long => current - entry,
-            Side::Short => entry - current,
+    /// Funding owed to (positive) or by the holder of `size` of this position since it
+    /// last settled at `funding_rate`, rounded down
+    pub fn funding_payment(&self, size: u64, funding_rate: i64) -> Result<i64> {
+        let funding_diff = funding_rate
+            .checked_sub(self.last_funding_payment)
+            .ok_or(PerpsError::MathOverflow)?;
+        // Longs pay a positive rate, shorts receive it
+        let diff = match self.side {
+            Side::Long => funding_diff.checked_neg().ok_or(PerpsError::MathOverflow)?,
+            Side::Short => funding_diff,
         };
+        Ok(perps_math::mul_div_signed(diff, size, perps_math::PRICE_SCALE, Rounding::Down)
+            .map_err(PerpsError::from)?)
+    }
 
-        // PnL = size * price_diff / 1_000_000
-        ((size * price_diff) / 1_000_000) as i64
+    /// Equity over entry notional, in basis points
+    pub fn margin_ratio(&self, current_price: u64) -> Result<u64> {
+        self.backed_margin_ratio(current_price, 0)
     }
 
-    pub fn margin_ratio(&self, current_price: u64) -> u32 {
-        let pnl = self.unrealized_pnl(current_price);
-        let equity = (self.collateral as i64).saturating_add(pnl);
+    /// Margin ratio once lent margin the pledged collateral no longer backs, up to
+    /// what the position drew, is taken out of its collateral
+    pub fn backed_margin_ratio(&self, current_price: u64, unbacked_credit: u64) -> Result<u64> {
+        let pnl = self.unrealized_pnl(current_price)?;
+        let collateral = self.collateral.saturating_sub(unbacked_credit.min(self.margin_credit));
+        let equity = (collateral as i64).saturating_add(pnl);
 
         if equity <= 0 {
-            return 0;
+            return Ok(0);
         }
 
-        let notional = self.notional_value();
+        let notional = self.notional_value()?;
         if notional == 0 {
-            return 0;
+            return Ok(0);
         }
 
-        // margin_ratio = equity / notional * 10000 (basis points)
-        ((equity as u64) * 10000 / notional) as u32
+        Ok(Bps::ratio(equity as u64, notional, Rounding::Down)
+            .map_err(PerpsError::from)?
+            .0)
     }
 
-    pub fn is_liquidatable(&self, current_price: u64, maintenance_margin_ratio: u32) -> bool {
-        self.margin_ratio(current_price) < maintenance_margin_ratio
+    pub fn is_liquidatable(&self, current_price: u64, maintenance_margin_ratio: u32) -> Result<bool> {
+        self.is_backed_liquidatable(current_price, maintenance_margin_ratio, 0)
+    }
+
+    pub fn is_backed_liquidatable(
+        &self,
+        current_price: u64,
+        maintenance_margin_ratio: u32,
+        unbacked_credit: u64,
+    ) -> Result<bool> {
+        Ok(self.backed_margin_ratio(current_price, unbacked_credit)? < maintenance_margin_ratio as u64)
+    }
+
+    /// Price at which equity falls to the maintenance margin, ignoring unsettled funding.
+    /// The distance from entry is rounded down so the price errs towards entry; 0 once flat.
+    pub fn liquidation_price(&self, maintenance_margin_ratio: u32) -> Result<u64> {
+        if self.size == 0 {
+            return Ok(0);
+        }
+        let maintenance_margin = Bps(maintenance_margin_ratio as u64)
+            .of(self.notional_value()?, Rounding::Up)
+            .map_err(PerpsError::from)?;
+        let excess = (self.collateral as i64)
+            .checked_sub(maintenance_margin as i64)
+            .ok_or(PerpsError::MathOverflow)?;
+        let distance = perps_math::mul_div_signed(excess, perps_math::PRICE_SCALE, self.size, Rounding::Down)
+            .map_err(PerpsError::from)?;
+        let price = match self.side {
+            Side::Long => (self.entry_price as i64).saturating_sub(distance),
+            Side::Short => (self.entry_price as i64).saturating_add(distance),
+        };
+        Ok(price.max(0) as u64)
+    }
+
+    /// Result of a fill against this position, as it stands after the fill
+    pub fn fill(
+        &self,
+        position: Pubkey,
+        fill_price: u64,
+        fill_size: u64,
+        maintenance_margin_ratio: u32,
+    ) -> Result<PositionFill> {
+        let is_open = self.status == PositionStatus::Open;
+        Ok(PositionFill {
+            position,
+            side: self.side,
+            fill_price,
+            fill_size,
+            new_size: if is_open { self.size } else { 0 },
+            collateral: if is_open { self.collateral } else { 0 },
+            fee: 0,
+            rebate: 0,
+            realized_pnl: 0,
+            liquidation_price: if is_open { self.liquidation_price(maintenance_margin_ratio)? } else { 0 },
+        })
     }
 }
 
+/// Outcome of opening, increasing, reducing or closing a position, returned as
+/// instruction return data so calling programs can act on the fill.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PositionFill {
+    pub position: Pubkey,
+    pub side: Side,
+    pub fill_price: u64,           // Oracle price the fill executed at (6 decimals)
+    pub fill_size: u64,            // Base units traded by this instruction
+    pub new_size: u64,             // Position size after the fill, 0 once closed
+    pub collateral: u64,           // Collateral left in the position, 0 once closed
+    pub fee: u64,                  // Trading fee charged
+    pub rebate: u64,               // Maker rebate paid
+    pub realized_pnl: i64,         // PnL, funding, fees and rebate settled by this fill
+    pub liquidation_price: u64,    // Maintenance-margin price of what remains, 0 once closed
+}
+
 #[account]
 #[derive(Default)]
 pub struct UserAccount {
@@ -118,9 +252,156 @@ pub struct UserAccount {
     pub total_positions: u32,
     pub total_trades: u64,
     pub realized_pnl: i64,
+    pub next_position_id: u64,        // Nonce for the next position PDA
     pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+
+    // Competition entry (added in version 2)
+    pub competition: Pubkey,          // Competition last joined
+    pub competition_end: i64,         // Its end time; results are recorded until then
 }
 
 impl UserAccount {
-    pub const LEN: usize = 8 + 32 + 8 + 4 + 8 + 8 + 1 + 16;
+    pub const LEN: usize = 8 + 32 + 8 + 4 + 8 + 8 + 8 + 1 + 1 + 32 + 8 + 7;
+
+    pub const VERSION: u8 = 2;
+
+    /// Version 0 stored the bump where `next_position_id` now starts
+    pub const V0_BUMP_OFFSET: usize = 8 + 32 + 8 + 4 + 8 + 8;
+
+    /// First position nonce for migrated accounts. Version 0 seeded positions with
+    /// `market.total_positions`, so nonces start above any count a market has reached.
+    pub const V0_FIRST_POSITION_ID: u64 = 1 << 32;
+
+    /// The competition the owner is entered in, while it is still running
+    pub fn active_competition(&self, current_time: i64) -> Option<Pubkey> {
+        (self.competition != Pubkey::default() && current_time <= self.competition_end)
+            .then_some(self.competition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position_address(owner: &Pubkey, market: &Pubkey, position_id: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"position", owner.as_ref(), market.as_ref(), &position_id.to_le_bytes()],
+            &crate::ID,
+        ).0
+    }
+
+    #[test]
+    fn test_position_seeds_use_per_user_nonce() {
+        let market = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        // Each open takes the owner's next nonce, so their positions never collide
+        assert_ne!(position_address(&owner, &market, 0), position_address(&owner, &market, 1));
+        // Owners count independently; the same nonce is a different account
+        assert_ne!(position_address(&owner, &market, 0), position_address(&other, &market, 0));
+
+        // Migrated owners start above any market count that seeded version 0 positions
+        let first = UserAccount::V0_FIRST_POSITION_ID;
+        assert!(first > u32::MAX as u64);
+        assert_ne!(position_address(&owner, &market, first), position_address(&owner, &market, first as u32 as u64));
+    }
+
+    #[test]
+    fn test_slippage_bounds() {
+        // Longs buy at or below the limit
+        assert!(Side::Long.is_acceptable_price(99_000_000, 100_000_000));
+        assert!(Side::Long.is_acceptable_price(100_000_000, 100_000_000));
+        assert!(!Side::Long.is_acceptable_price(100_000_001, 100_000_000));
+
+        // Shorts sell at or above it
+        assert!(Side::Short.is_acceptable_price(101_000_000, 100_000_000));
+        assert!(Side::Short.is_acceptable_price(100_000_000, 100_000_000));
+        assert!(!Side::Short.is_acceptable_price(99_999_999, 100_000_000));
+
+        // Closing a long sells, so it is bounded from below
+        assert!(!Side::Long.opposite().is_acceptable_price(99_999_999, 100_000_000));
+        assert!(Side::Short.opposite().is_acceptable_price(99_999_999, 100_000_000));
+    }
+
+    #[test]
+    fn test_deadline() {
+        assert!(is_within_deadline(0, i64::MAX));
+        assert!(is_within_deadline(1_000, 999));
+        assert!(is_within_deadline(1_000, 1_000));
+        assert!(!is_within_deadline(1_000, 1_001));
+    }
+
+    #[test]
+    fn test_v0_bump_offsets() {
+        // Version 0 kept the bump where the nonces now start
+        let position = Position { position_id: 0x0102_0304_0506_0708, ..Default::default() };
+        let mut data = Vec::new();
+        position.try_serialize(&mut data).unwrap();
+        let offset = Position::V0_BUMP_OFFSET;
+        assert_eq!(data[offset..offset + 8], position.position_id.to_le_bytes());
+
+        let user_account = UserAccount { next_position_id: 0x0102_0304_0506_0708, ..Default::default() };
+        let mut data = Vec::new();
+        user_account.try_serialize(&mut data).unwrap();
+        let offset = UserAccount::V0_BUMP_OFFSET;
+        assert_eq!(data[offset..offset + 8], user_account.next_position_id.to_le_bytes());
+    }
+
+    #[test]
+    fn test_fill_reports_remaining_position() {
+        let address = Pubkey::new_unique();
+        let mut position = Position {
+            side: Side::Long,
+            size: 2_000_000,
+            entry_price: 50_000_000,
+            collateral: 20_000_000,
+            ..Default::default()
+        };
+
+        // 2 units at 50 with 20 of collateral and 2.5% maintenance liquidate at 41.25
+        let fill = position.fill(address, 51_000_000, 500_000, 250).unwrap();
+        assert_eq!(fill.position, address);
+        assert_eq!(fill.side, Side::Long);
+        assert_eq!((fill.fill_price, fill.fill_size), (51_000_000, 500_000));
+        assert_eq!((fill.new_size, fill.collateral), (2_000_000, 20_000_000));
+        assert_eq!(fill.liquidation_price, 41_250_000);
+        assert_eq!((fill.fee, fill.rebate, fill.realized_pnl), (0, 0, 0));
+
+        // A closed position reports nothing left, whatever its fields still hold
+        position.status = PositionStatus::Closed;
+        let fill = position.fill(address, 51_000_000, 2_000_000, 250).unwrap();
+        assert_eq!((fill.new_size, fill.collateral, fill.liquidation_price), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_position_fill_encoding() {
+        let fill = PositionFill {
+            position: Pubkey::new_unique(),
+            side: Side::Short,
+            fill_price: 51_000_000,
+            fill_size: 500_000,
+            new_size: 1_500_000,
+            collateral: 15_000_000,
+            fee: 25_500,
+            rebate: 0,
+            realized_pnl: -525_500,
+            liquidation_price: 60_000_000,
+        };
+        let data = fill.try_to_vec().unwrap();
+
+        // Calling programs read the return data at fixed offsets
+        assert_eq!(data.len(), 32 + 1 + 8 * 8);
+        assert_eq!(&data[..32], fill.position.as_ref());
+        assert_eq!(data[32], 1);
+        assert_eq!(data[33..41], 51_000_000u64.to_le_bytes());
+        assert_eq!(data[81..89], (-525_500i64).to_le_bytes());
+        assert_eq!(data[89..97], 60_000_000u64.to_le_bytes());
+
+        let decoded = PositionFill::try_from_slice(&data).unwrap();
+        assert_eq!(decoded.side, Side::Short);
+        assert_eq!(decoded.realized_pnl, -525_500);
+        assert_eq!(decoded.liquidation_price, 60_000_000);
+    }
 }
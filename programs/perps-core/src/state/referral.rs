@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use perps_math::{Bps, Rounding};
+use crate::errors::PerpsError;
 
 /// On-chain referral code account
 /// PDA seeds: [b"referral_code", code_bytes]
@@ -40,6 +42,9 @@ pub struct ReferralCode {
 
     /// PDA bump
     pub bump: u8,
+
+    /// Layout version, 0 for accounts written before versioning
+    pub version: u8,
 }
 
 impl ReferralCode {
@@ -56,7 +61,10 @@ impl ReferralCode {
         8 +   // created_at
         1 +   // is_active
         1 +   // bump
-        32;   // padding
+        1 +   // version
+        31;   // padding
+
+    pub const VERSION: u8 = 1;
 
     pub const DEFAULT_DISCOUNT_BPS: u16 = 1000;  // 10%
     pub const DEFAULT_REWARD_BPS: u16 = 2000;    // 20%
@@ -127,6 +135,9 @@ pub struct UserReferral {
 
     /// PDA bump
     pub bump: u8,
+
+    /// Layout version, 0 for accounts written before versioning
+    pub version: u8,
 }
 
 impl UserReferral {
@@ -140,5 +151,51 @@ impl UserReferral {
         8 +   // total_referrer_rewards
         8 +   // applied_at
         1 +   // bump
-        16;   // padding
+        1 +   // version
+        15;   // padding
+
+    pub const VERSION: u8 = 1;
+}
+
+impl UserReferral {
+    /// Apply the referral discount to a trade fee and accrue the referrer's reward.
+    /// Returns (fee charged to the trader, reward credited to the referrer).
+    pub fn process_trade_fee(
+        &mut self,
+        referral_code_key: Pubkey,
+        referral_code: &mut ReferralCode,
+        base_fee: u64,
+        notional: u64,
+    ) -> Result<(u64, u64)> {
+        // Validate referral relationship
+        if self.referral_code != referral_code_key || !referral_code.is_active {
+            return Ok((base_fee, 0));
+        }
+
+        // Calculate discounted fee for user
+        let discount = Bps::from(self.discount_bps)
+            .of(base_fee, Rounding::Down)
+            .map_err(PerpsError::from)?;
+        let discounted_fee = base_fee.saturating_sub(discount);
+
+        // Calculate reward for referrer (based on discounted fee to prevent gaming)
+        let reward = Bps::from(referral_code.reward_bps)
+            .of(discounted_fee, Rounding::Down)
+            .map_err(PerpsError::from)?;
+
+        // Update user referral stats (FIXES SYBIL ATTACK - tracked on actual trades)
+        self.total_volume = self.total_volume.saturating_add(notional);
+        self.total_fees_paid = self.total_fees_paid.saturating_add(discounted_fee);
+        self.total_referrer_rewards = self.total_referrer_rewards.saturating_add(reward);
+
+        // Update referral code stats
+        referral_code.total_volume = referral_code.total_volume.saturating_add(notional);
+        referral_code.total_fees_generated = referral_code.total_fees_generated.saturating_add(discounted_fee);
+        referral_code.total_rewards_earned = referral_code.total_rewards_earned.saturating_add(reward);
+        referral_code.pending_rewards = referral_code.pending_rewards.saturating_add(reward);
+
+        msg!("Referral processed: discount={}, reward={}", discount, reward);
+
+        Ok((discounted_fee, reward))
+    }
 }
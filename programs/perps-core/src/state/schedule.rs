@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_HOLIDAYS: usize = 16;
+pub const SECONDS_PER_DAY: i64 = 86_400;
+pub const MINUTES_PER_DAY: u16 = 1_440;
+
+/// A trading window within one UTC day, in minutes after midnight: [open, close)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SessionWindow {
+    pub open_minute: u16,
+    pub close_minute: u16,
+}
+
+impl SessionWindow {
+    pub const LEN: usize = 2 + 2;
+
+    pub fn contains(&self, minute: u16) -> bool {
+        minute >= self.open_minute && minute < self.close_minute
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.open_minute <= self.close_minute && self.close_minute <= MINUTES_PER_DAY
+    }
+}
+
+/// Weekly trading hours and holiday closures for a market.
+/// Outside a session the market is reduce-only and funding does not accrue.
+/// PDA seeds: [b"trading_schedule", market]
+#[account]
+#[derive(Default)]
+pub struct TradingSchedule {
+    pub market: Pubkey,
+
+    /// Two windows per UTC weekday (0 = Sunday), enough to model a daily maintenance break
+    pub weekly_sessions: [[SessionWindow; 2]; 7],
+
+    /// UTC midnight timestamps of full-day closures (0 = unused slot)
+    pub holidays: [i64; MAX_HOLIDAYS],
+
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+}
+
+impl TradingSchedule {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // market
+        SessionWindow::LEN * 2 * 7 +  // weekly_sessions
+        8 * MAX_HOLIDAYS +  // holidays
+        1 +   // bump
+        1 +   // version
+        31;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    pub fn is_open(&self, unix_timestamp: i64) -> bool {
+        let day_start = unix_timestamp - unix_timestamp.rem_euclid(SECONDS_PER_DAY);
+        if self.holidays.iter().any(|&holiday| holiday != 0 && holiday == day_start) {
+            return false;
+        }
+
+        // 1970-01-01 was a Thursday
+        let weekday = ((unix_timestamp.div_euclid(SECONDS_PER_DAY) + 4).rem_euclid(7)) as usize;
+        let minute = (unix_timestamp.rem_euclid(SECONDS_PER_DAY) / 60) as u16;
+
+        self.weekly_sessions[weekday]
+            .iter()
+            .any(|window| window.contains(minute))
+    }
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use perps_math::Rounding;
+use super::{Market, MarketStatus};
+
+/// Emergency wind-down of a market at a fixed price.
+/// Positions are first closed out into claims; once none remain open the vault
+/// balance is snapshotted and claims are paid pro rata if it falls short.
+/// PDA seeds: [b"global_settlement", market]
+#[account]
+#[derive(Default)]
+pub struct GlobalSettlement {
+    pub market: Pubkey,
+
+    /// Price every position is closed at (6 decimals)
+    pub settlement_price: u64,
+    pub initiated_at: i64,
+
+    /// Sum of equity owed to closed-out positions
+    pub total_claims: u64,
+
+    /// Vault balance available to claims, snapshotted when the last position closes,
+    /// less free collateral, the insurance fund and referral rewards
+    pub claimable_balance: u64,
+
+    pub total_paid: u64,
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+}
+
+impl GlobalSettlement {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // market
+        8 +   // settlement_price
+        8 +   // initiated_at
+        8 +   // total_claims
+        8 +   // claimable_balance
+        8 +   // total_paid
+        1 +   // bump
+        1 +   // version
+        31;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    /// Settle the market once its last open interest has been closed out, fixing
+    /// the pot claims are paid from. Balances the market still owes outside of
+    /// positions stay in the vault and are not part of it.
+    pub fn complete_if_flat(&mut self, market: &mut Market, vault_balance: u64) {
+        if market.long_open_interest > 0 || market.short_open_interest > 0 {
+            return;
+        }
+
+        market.status = MarketStatus::Settled;
+        self.claimable_balance = vault_balance.saturating_sub(market.reserved_balance());
+        msg!(
+            "Global settlement complete: claims={}, balance={}",
+            self.total_claims,
+            self.claimable_balance
+        );
+    }
+
+    /// Amount paid for a claim, scaled down if the vault cannot cover all claims
+    pub fn payout_for(&self, claim: u64) -> u64 {
+        if self.claimable_balance >= self.total_claims {
+            return claim;
+        }
+        // Below `claim`, so it cannot overflow
+        perps_math::mul_div(claim, self.claimable_balance, self.total_claims, Rounding::Down).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete_waits_for_flat_market() {
+        let mut market = Market { long_open_interest: 1_000_000, ..Default::default() };
+        let mut settlement = GlobalSettlement { total_claims: 500_000_000, ..Default::default() };
+
+        settlement.complete_if_flat(&mut market, 800_000_000);
+        assert!(market.status != MarketStatus::Settled);
+        assert_eq!(settlement.claimable_balance, 0);
+
+        // Free collateral, the insurance fund and referral rewards stay out of the pot
+        market.long_open_interest = 0;
+        market.user_collateral = 100_000_000;
+        market.insurance_fund = 50_000_000;
+        market.referral_rewards = 10_000_000;
+        settlement.complete_if_flat(&mut market, 800_000_000);
+        assert!(market.status == MarketStatus::Settled);
+        assert_eq!(settlement.claimable_balance, 640_000_000);
+    }
+
+    #[test]
+    fn test_payout_pro_rata() {
+        // Fully covered claims are paid in full
+        let mut settlement = GlobalSettlement {
+            total_claims: 1_000_000_000,
+            claimable_balance: 1_200_000_000,
+            ..Default::default()
+        };
+        assert_eq!(settlement.payout_for(300_000_000), 300_000_000);
+
+        // A 75% shortfall pays every claim 75%, rounded down
+        settlement.claimable_balance = 750_000_000;
+        assert_eq!(settlement.payout_for(300_000_000), 225_000_000);
+        assert_eq!(settlement.payout_for(1), 0);
+        assert_eq!(settlement.payout_for(1_000_000_000), 750_000_000);
+
+        // Nothing left pays nothing
+        settlement.claimable_balance = 0;
+        assert_eq!(settlement.payout_for(300_000_000), 0);
+    }
+}
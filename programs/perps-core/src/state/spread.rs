@@ -0,0 +1,305 @@
+use anchor_lang::prelude::*;
+use perps_math::{Bps, Price, Rounding, Size};
+use crate::errors::PerpsError;
+use crate::state::{Market, PositionStatus, Side};
+
+/// Scale of `SpreadMarket::hedge_ratio`: 1_000_000 = one leg B unit per leg A unit
+pub const HEDGE_RATIO_SCALE: u64 = perps_math::PRICE_SCALE;
+
+/// A spread between two markets sharing a collateral mint, traded as one instrument.
+/// One unit of spread is one base unit of leg A against `hedge_ratio` of leg B.
+///
+/// Margin is a ratio of the gross notional of both legs, set from the spread's own
+/// volatility and never above what either leg requires outright. Collateral is held in
+/// leg A's vault. Each leg's PnL, funding and fee settle in its own market, so on exit
+/// leg B's result moves between the two vaults.
+/// PDA seeds: [b"spread_market", leg_a_market, leg_b_market]
+#[account]
+#[derive(Default)]
+pub struct SpreadMarket {
+    pub authority: Pubkey,
+    pub leg_a_market: Pubkey,
+    pub leg_b_market: Pubkey,
+
+    pub hedge_ratio: u64,               // Leg B base units per leg A unit (6 decimals)
+    pub initial_margin_ratio: u32,      // Basis points of gross notional
+    pub maintenance_margin_ratio: u32,  // Basis points of gross notional
+
+    pub open_spreads: u64,
+    pub is_active: bool,                // Opens are rejected while inactive; closes still work
+
+    pub bump: u8,
+    pub version: u8,                    // Layout version, 0 = written before versioning
+}
+
+impl SpreadMarket {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // authority
+        32 +  // leg_a_market
+        32 +  // leg_b_market
+        8 +   // hedge_ratio
+        4 +   // initial_margin_ratio
+        4 +   // maintenance_margin_ratio
+        8 +   // open_spreads
+        1 +   // is_active
+        1 +   // bump
+        1 +   // version
+        32;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    /// Spread margins sit below both legs' initial margin, maintenance below initial
+    pub fn valid_margins(
+        initial_margin_ratio: u32,
+        maintenance_margin_ratio: u32,
+        leg_a: &Market,
+        leg_b: &Market,
+    ) -> bool {
+        maintenance_margin_ratio > 0
+            && maintenance_margin_ratio < initial_margin_ratio
+            && initial_margin_ratio <= leg_a.initial_margin_ratio.min(leg_b.initial_margin_ratio)
+    }
+
+    /// Leg B size hedging `size` of leg A, rounded down
+    pub fn leg_b_size(&self, size: u64) -> Result<u64> {
+        Ok(perps_math::mul_div(size, self.hedge_ratio, HEDGE_RATIO_SCALE, Rounding::Down)
+            .map_err(PerpsError::from)?)
+    }
+
+    /// Initial margin on `gross_notional`, rounded up
+    pub fn initial_margin(&self, gross_notional: u64) -> Result<u64> {
+        Ok(Bps::from(self.initial_margin_ratio)
+            .of(gross_notional, Rounding::Up)
+            .map_err(PerpsError::from)?)
+    }
+}
+
+/// One market's side of a spread position
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SpreadLeg {
+    pub market: Pubkey,
+    pub side: Side,
+    pub size: u64,                    // Base units
+    pub entry_price: u64,             // 6 decimals
+    pub last_funding_rate: i64,       // Market funding rate at last settlement
+}
+
+impl SpreadLeg {
+    pub const LEN: usize = 32 + 1 + 8 + 8 + 8;
+
+    /// Value at the entry price, rounded up
+    pub fn notional_value(&self) -> Result<u64> {
+        Ok(Price(self.entry_price)
+            .notional(Size(self.size), Rounding::Up)
+            .map_err(PerpsError::from)?)
+    }
+
+    /// PnL at `current_price`, rounded down
+    pub fn unrealized_pnl(&self, current_price: u64) -> Result<i64> {
+        Ok(Size(self.size)
+            .pnl(
+                Price(self.entry_price),
+                Price(current_price),
+                self.side == Side::Long,
+                Rounding::Down,
+            )
+            .map_err(PerpsError::from)?)
+    }
+
+    /// Funding owed to (positive) or by the leg since it last settled, rounded down
+    pub fn funding_payment(&self, funding_rate: i64) -> Result<i64> {
+        let funding_diff = funding_rate
+            .checked_sub(self.last_funding_rate)
+            .ok_or(PerpsError::MathOverflow)?;
+        let diff = match self.side {
+            Side::Long => funding_diff.checked_neg().ok_or(PerpsError::MathOverflow)?,
+            Side::Short => funding_diff,
+        };
+        Ok(perps_math::mul_div_signed(diff, self.size, perps_math::PRICE_SCALE, Rounding::Down)
+            .map_err(PerpsError::from)?)
+    }
+}
+
+/// Both legs of a spread, opened, closed and liquidated together.
+/// A long spread is long leg A and short leg B.
+/// PDA seeds: [b"spread_position", owner, spread_market, position_id]
+#[account]
+#[derive(Default)]
+pub struct SpreadPosition {
+    pub owner: Pubkey,
+    pub spread_market: Pubkey,
+    pub side: Side,
+
+    pub leg_a: SpreadLeg,
+    pub leg_b: SpreadLeg,
+
+    pub collateral: u64,              // Held in leg A's vault
+    pub realized_pnl: i64,
+
+    pub opened_at: i64,
+    pub last_updated_at: i64,
+
+    pub status: PositionStatus,
+    pub position_id: u64,             // Per-user nonce shared with single-market positions
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+    pub settlement_claim: u64,        // Equity owed by leg A's global settlement, paid pro rata
+}
+
+impl SpreadPosition {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // owner
+        32 +  // spread_market
+        1 +   // side
+        SpreadLeg::LEN +  // leg_a
+        SpreadLeg::LEN +  // leg_b
+        8 +   // collateral
+        8 +   // realized_pnl
+        8 +   // opened_at
+        8 +   // last_updated_at
+        1 +   // status
+        8 +   // position_id
+        1 +   // bump
+        1 +   // version
+        8 +   // settlement_claim
+        24;   // padding (reduced by 8 for settlement_claim)
+
+    /// Version 2 reads `settlement_claim` from padding
+    pub const VERSION: u8 = 2;
+
+    /// Entry notional of both legs, which margin is measured against
+    pub fn gross_notional(&self) -> Result<u64> {
+        self.leg_a.notional_value()?
+            .checked_add(self.leg_b.notional_value()?)
+            .ok_or(PerpsError::MathOverflow.into())
+    }
+
+    pub fn unrealized_pnl(&self, price_a: u64, price_b: u64) -> Result<i64> {
+        self.leg_a.unrealized_pnl(price_a)?
+            .checked_add(self.leg_b.unrealized_pnl(price_b)?)
+            .ok_or(PerpsError::MathOverflow.into())
+    }
+
+    pub fn funding_payment(&self, funding_rate_a: i64, funding_rate_b: i64) -> Result<i64> {
+        self.leg_a.funding_payment(funding_rate_a)?
+            .checked_add(self.leg_b.funding_payment(funding_rate_b)?)
+            .ok_or(PerpsError::MathOverflow.into())
+    }
+
+    /// Equity over gross entry notional, in basis points
+    pub fn margin_ratio(&self, price_a: u64, price_b: u64) -> Result<u64> {
+        let equity = (self.collateral as i64).saturating_add(self.unrealized_pnl(price_a, price_b)?);
+        if equity <= 0 {
+            return Ok(0);
+        }
+
+        let notional = self.gross_notional()?;
+        if notional == 0 {
+            return Ok(0);
+        }
+
+        Ok(Bps::ratio(equity as u64, notional, Rounding::Down)
+            .map_err(PerpsError::from)?
+            .0)
+    }
+
+    pub fn is_liquidatable(&self, price_a: u64, price_b: u64, maintenance_margin_ratio: u32) -> Result<bool> {
+        Ok(self.margin_ratio(price_a, price_b)? < maintenance_margin_ratio as u64)
+    }
+
+    /// Transfer settling leg B in its own vault, positive from leg B's vault to leg A's.
+    /// `leg_a_result` and `leg_b_result` are each leg's PnL, funding and fees. A leg B
+    /// gain moves in full; a loss only as far as the collateral and leg A's result cover
+    /// it, so each vault bears its own leg's shortfall.
+    pub fn leg_b_transfer(&self, leg_a_result: i64, leg_b_result: i64) -> i64 {
+        if leg_b_result >= 0 {
+            return leg_b_result;
+        }
+        let available = (self.collateral as i64).saturating_add(leg_a_result).max(0);
+        -leg_b_result.saturating_neg().min(available)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spread(side: Side, size: u64, price_a: u64, price_b: u64, hedge_ratio: u64, collateral: u64) -> SpreadPosition {
+        let spread_market = SpreadMarket { hedge_ratio, ..Default::default() };
+        SpreadPosition {
+            side,
+            leg_a: SpreadLeg { side, size, entry_price: price_a, ..Default::default() },
+            leg_b: SpreadLeg {
+                side: side.opposite(),
+                size: spread_market.leg_b_size(size).unwrap(),
+                entry_price: price_b,
+                ..Default::default()
+            },
+            collateral,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_leg_b_size_rounds_down() {
+        let spread_market = SpreadMarket { hedge_ratio: 1_500_000, ..Default::default() };
+        assert_eq!(spread_market.leg_b_size(2_000_000).unwrap(), 3_000_000);
+        assert_eq!(spread_market.leg_b_size(1).unwrap(), 1);
+
+        // A third of a unit hedges with whatever whole base units it covers
+        let spread_market = SpreadMarket { hedge_ratio: 333_333, ..Default::default() };
+        assert_eq!(spread_market.leg_b_size(2).unwrap(), 0);
+        assert_eq!(spread_market.leg_b_size(10).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_valid_margins() {
+        let leg_a = Market { initial_margin_ratio: 1_000, ..Default::default() };
+        let leg_b = Market { initial_margin_ratio: 500, ..Default::default() };
+
+        assert!(SpreadMarket::valid_margins(500, 250, &leg_a, &leg_b));
+        // Above the tighter leg's initial margin
+        assert!(!SpreadMarket::valid_margins(600, 250, &leg_a, &leg_b));
+        // Maintenance must sit strictly below initial, and above zero
+        assert!(!SpreadMarket::valid_margins(400, 400, &leg_a, &leg_b));
+        assert!(!SpreadMarket::valid_margins(400, 0, &leg_a, &leg_b));
+    }
+
+    #[test]
+    fn test_margin_ratio_and_liquidation() {
+        // Long 1 unit of A at 100 against short 1 unit of B at 80: 180 gross notional
+        let position = spread(Side::Long, 1_000_000, 100_000_000, 80_000_000, 1_000_000, 9_000_000);
+        assert_eq!(position.gross_notional().unwrap(), 180_000_000);
+        assert_eq!(position.margin_ratio(100_000_000, 80_000_000).unwrap(), 500);
+
+        // Both legs rallying together leaves the spread unchanged
+        assert_eq!(position.margin_ratio(110_000_000, 90_000_000).unwrap(), 500);
+        assert!(!position.is_liquidatable(110_000_000, 90_000_000, 250).unwrap());
+
+        // The spread narrowing by 5 loses 5, leaving 4 of equity
+        assert_eq!(position.margin_ratio(100_000_000, 85_000_000).unwrap(), 222);
+        assert!(position.is_liquidatable(100_000_000, 85_000_000, 250).unwrap());
+
+        // Equity wiped out reads as zero rather than negative
+        assert_eq!(position.margin_ratio(90_000_000, 85_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_short_spread_pnl() {
+        let position = spread(Side::Short, 1_000_000, 100_000_000, 80_000_000, 1_000_000, 9_000_000);
+        assert_eq!(position.unrealized_pnl(100_000_000, 85_000_000).unwrap(), 5_000_000);
+        assert_eq!(position.unrealized_pnl(105_000_000, 80_000_000).unwrap(), -5_000_000);
+    }
+
+    #[test]
+    fn test_leg_b_transfer() {
+        let position = spread(Side::Long, 1_000_000, 100_000_000, 80_000_000, 1_000_000, 9_000_000);
+
+        // A leg B gain moves in full
+        assert_eq!(position.leg_b_transfer(-20_000_000, 4_000_000), 4_000_000);
+        // A loss moves as far as the collateral and leg A's result cover it
+        assert_eq!(position.leg_b_transfer(2_000_000, -5_000_000), -5_000_000);
+        assert_eq!(position.leg_b_transfer(-6_000_000, -5_000_000), -3_000_000);
+        assert_eq!(position.leg_b_transfer(-20_000_000, -5_000_000), 0);
+    }
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use super::Side;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum TriggerKind {
+    #[default]
+    TakeProfit,
+    StopLoss,
+}
+
+/// A take-profit or stop-loss on one position, closed by any keeper once the index
+/// price crosses `trigger_price`
+/// PDA seeds: [b"trigger_order", position, kind as u8]
+#[account]
+#[derive(Default)]
+pub struct TriggerOrder {
+    pub owner: Pubkey,                // Paid the rent, refunded when the order ends
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub kind: TriggerKind,
+    pub trigger_price: u64,           // 6 decimals
+    pub acceptable_price: u64,        // Worst fill accepted on execution (6 decimals)
+    pub created_at: i64,
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+}
+
+impl TriggerOrder {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // owner
+        32 +  // market
+        32 +  // position
+        1 +   // kind
+        8 +   // trigger_price
+        8 +   // acceptable_price
+        8 +   // created_at
+        1 +   // bump
+        1 +   // version
+        32;   // padding
+
+    pub const VERSION: u8 = 1;
+
+    /// Whether `price` has reached the trigger for a position on `side`: take-profits
+    /// fire once the price moves in the position's favour, stop-losses against it
+    pub fn is_triggered(&self, side: Side, price: u64) -> bool {
+        match (self.kind, side) {
+            (TriggerKind::TakeProfit, Side::Long) | (TriggerKind::StopLoss, Side::Short) => {
+                price >= self.trigger_price
+            }
+            (TriggerKind::TakeProfit, Side::Short) | (TriggerKind::StopLoss, Side::Long) => {
+                price <= self.trigger_price
+            }
+        }
+    }
+}
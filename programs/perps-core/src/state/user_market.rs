@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use crate::errors::PerpsError;
+use crate::state::{Market, Position};
+
+/// A user's footprint in one market: counted open interest and trading statistics.
+/// Statistics are recorded by every handler the account is passed to.
+/// PDA seeds: [b"user_market", market, owner]
+#[account]
+#[derive(Default)]
+pub struct UserMarketAccount {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+
+    /// Size of all open positions, both sides, in base units
+    pub open_interest: u64,
+
+    pub bump: u8,
+    pub version: u8,                  // Layout version, 0 = written before versioning
+
+    // Trading statistics (added in version 2)
+    pub volume: u64,                  // Notional traded, both legs of every fill
+    pub fees_paid: u64,
+    pub realized_pnl: i64,            // Including funding and fees, as on UserAccount
+    pub funding_paid: u64,
+    pub funding_received: u64,
+    pub total_trades: u64,
+    pub liquidations: u32,
+    pub wins: u32,                    // Positions closed with a profit
+    pub losses: u32,                  // Positions closed with a loss, liquidations included
+}
+
+impl UserMarketAccount {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // owner
+        32 +  // market
+        8 +   // open_interest
+        1 +   // bump
+        1 +   // version
+        8 +   // volume
+        8 +   // fees_paid
+        8 +   // realized_pnl
+        8 +   // funding_paid
+        8 +   // funding_received
+        8 +   // total_trades
+        4 +   // liquidations
+        4 +   // wins
+        4 +   // losses
+        64;   // padding
+
+    pub const VERSION: u8 = 2;
+
+    /// Fill in an account `init_if_needed` just created, which is all zeroes
+    pub fn initialize_if_new(&mut self, owner: Pubkey, market: Pubkey, bump: u8) {
+        if self.owner != Pubkey::default() {
+            return;
+        }
+        self.owner = owner;
+        self.market = market;
+        self.bump = bump;
+        self.version = Self::VERSION;
+    }
+
+    pub fn add_open_interest(&mut self, size: u64) -> Result<()> {
+        self.open_interest = self.open_interest
+            .checked_add(size)
+            .ok_or(PerpsError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Positions opened before the account existed were never counted, so this saturates
+    pub fn remove_open_interest(&mut self, size: u64) {
+        self.open_interest = self.open_interest.saturating_sub(size);
+    }
+
+    /// Record a fill of `notional` (6 decimals) that paid `fee`
+    pub fn record_trade(&mut self, notional: u64, fee: u64) {
+        self.volume = self.volume.saturating_add(notional);
+        self.fees_paid = self.fees_paid.saturating_add(fee);
+        self.total_trades = self.total_trades.saturating_add(1);
+    }
+
+    /// Record settled funding; positive payments were received
+    pub fn record_funding(&mut self, payment: i64) {
+        if payment >= 0 {
+            self.funding_received = self.funding_received.saturating_add(payment as u64);
+        } else {
+            self.funding_paid = self.funding_paid.saturating_add(payment.unsigned_abs());
+        }
+    }
+
+    pub fn record_pnl(&mut self, pnl: i64) {
+        self.realized_pnl = self.realized_pnl.saturating_add(pnl);
+    }
+
+    /// Count a position closed with `pnl` as a win or a loss
+    pub fn record_close(&mut self, pnl: i64) {
+        if pnl > 0 {
+            self.wins = self.wins.saturating_add(1);
+        } else if pnl < 0 {
+            self.losses = self.losses.saturating_add(1);
+        }
+    }
+
+    pub fn record_liquidation(&mut self) {
+        self.liquidations = self.liquidations.saturating_add(1);
+        self.losses = self.losses.saturating_add(1);
+    }
+}
+
+/// Move a position's size between two owners' per-user open interest. No oracle is
+/// read here, so the recipient's cap is checked at the mark price.
+pub fn move_user_open_interest(
+    market: &Market,
+    position: &Position,
+    from: Option<&mut UserMarketAccount>,
+    to: &mut UserMarketAccount,
+) -> Result<()> {
+    let price = if market.mark_price > 0 { market.mark_price } else { position.entry_price };
+    require!(
+        market.within_user_cap(to.open_interest, position.size, price),
+        PerpsError::UserOpenInterestCapExceeded
+    );
+    to.add_open_interest(position.size)?;
+
+    if let Some(from) = from {
+        from.remove_open_interest(position.size);
+    }
+    Ok(())
+}
+
+/// Another owner's user market PDA, which the caller can't be made to pay for: loaded
+/// once that owner initialized it. Changes are written back with `exit`.
+pub fn load_initialized_user_market<'info>(
+    info: &AccountInfo<'info>,
+) -> Result<Option<Account<'info, UserMarketAccount>>> {
+    if info.owner != &crate::ID {
+        return Ok(None);
+    }
+    Account::try_from(info).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(size: u64, entry_price: u64) -> Position {
+        Position { size, entry_price, ..Default::default() }
+    }
+
+    #[test]
+    fn test_initialize_if_new() {
+        let owner = Pubkey::new_unique();
+        let market = Pubkey::new_unique();
+        let mut user_market = UserMarketAccount::default();
+        user_market.initialize_if_new(owner, market, 254);
+        assert_eq!(user_market.owner, owner);
+        assert_eq!(user_market.version, UserMarketAccount::VERSION);
+
+        // An account already in use keeps its fields and statistics
+        user_market.record_trade(100_000_000, 50_000);
+        user_market.initialize_if_new(Pubkey::new_unique(), Pubkey::new_unique(), 1);
+        assert_eq!(user_market.owner, owner);
+        assert_eq!(user_market.market, market);
+        assert_eq!(user_market.bump, 254);
+        assert_eq!(user_market.total_trades, 1);
+    }
+
+    #[test]
+    fn test_trading_statistics() {
+        let mut user_market = UserMarketAccount::default();
+
+        user_market.record_trade(100_000_000, 50_000);
+        user_market.record_trade(40_000_000, 20_000);
+        assert_eq!(user_market.volume, 140_000_000);
+        assert_eq!(user_market.fees_paid, 70_000);
+        assert_eq!(user_market.total_trades, 2);
+
+        // Funding is split by direction rather than netted
+        user_market.record_funding(3_000_000);
+        user_market.record_funding(-1_000_000);
+        user_market.record_funding(0);
+        assert_eq!(user_market.funding_received, 3_000_000);
+        assert_eq!(user_market.funding_paid, 1_000_000);
+
+        user_market.record_pnl(5_000_000);
+        user_market.record_pnl(-7_000_000);
+        assert_eq!(user_market.realized_pnl, -2_000_000);
+    }
+
+    #[test]
+    fn test_wins_and_losses() {
+        let mut user_market = UserMarketAccount::default();
+        user_market.record_close(1);
+        user_market.record_close(-1);
+        // Breaking even is neither
+        user_market.record_close(0);
+        assert_eq!((user_market.wins, user_market.losses), (1, 1));
+
+        // Liquidations count as losses
+        user_market.record_liquidation();
+        assert_eq!(user_market.liquidations, 1);
+        assert_eq!((user_market.wins, user_market.losses), (1, 2));
+    }
+
+    #[test]
+    fn test_transfer_moves_open_interest() {
+        let market = Market::default();
+        let mut from = UserMarketAccount { open_interest: 3_000_000, ..Default::default() };
+        let mut to = UserMarketAccount { open_interest: 500_000, ..Default::default() };
+
+        move_user_open_interest(&market, &position(2_000_000, 50_000_000), Some(&mut from), &mut to).unwrap();
+        assert_eq!(from.open_interest, 1_000_000);
+        assert_eq!(to.open_interest, 2_500_000);
+    }
+
+    #[test]
+    fn test_unwrap_from_uncounted_owner() {
+        // A token holder takes a position whose previous owner never had it counted
+        let market = Market::default();
+        let mut to = UserMarketAccount::default();
+        move_user_open_interest(&market, &position(2_000_000, 50_000_000), None, &mut to).unwrap();
+        assert_eq!(to.open_interest, 2_000_000);
+
+        // Removing more than was counted leaves nothing rather than failing
+        let mut from = UserMarketAccount { open_interest: 1_000_000, ..Default::default() };
+        from.remove_open_interest(2_000_000);
+        assert_eq!(from.open_interest, 0);
+    }
+
+    #[test]
+    fn test_recipient_cap_at_mark_price() {
+        // 3 units at a mark of 40 is 120 against a cap of 100
+        let mut market = Market { max_user_open_interest_notional: 100_000_000, mark_price: 40_000_000, ..Default::default() };
+        let mut from = UserMarketAccount { open_interest: 2_000_000, ..Default::default() };
+        let mut to = UserMarketAccount { open_interest: 1_000_000, ..Default::default() };
+        let transferred = position(2_000_000, 10_000_000);
+
+        assert!(move_user_open_interest(&market, &transferred, Some(&mut from), &mut to).is_err());
+        assert_eq!(from.open_interest, 2_000_000);
+        assert_eq!(to.open_interest, 1_000_000);
+
+        // Without a mark yet the entry price stands in
+        market.mark_price = 0;
+        move_user_open_interest(&market, &transferred, Some(&mut from), &mut to).unwrap();
+        assert_eq!(to.open_interest, 3_000_000);
+    }
+}
@@ -46,4 +46,13 @@ pub enum PropAmmError {
 
     #[msg("Cannot withdraw with pending positions")]
     PendingPositions,
+
+    #[msg("Vault losses exceed its assets")]
+    VaultUnderwater,
+}
+
+impl From<perps_math::MathError> for PropAmmError {
+    fn from(_: perps_math::MathError) -> Self {
+        PropAmmError::MathOverflow
+    }
 }
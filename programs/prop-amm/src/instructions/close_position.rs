@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::{scale_price, Bps, Price, Rounding, Size};
 use pyth_sdk_solana::load_price_feed_from_account_info;
 use crate::state::LpVault;
 use crate::errors::PropAmmError;
@@ -70,13 +71,11 @@ pub fn handler(ctx: Context<ClosePositionAmm>, params: ClosePositionParams) -> R
 
     require!(price_data.price > 0, PropAmmError::InvalidOraclePrice);
 
-    let oracle_price = (price_data.price as u64)
-        .checked_mul(10u64.pow((6 + price_data.expo.abs() as u32) as u32))
-        .and_then(|p| p.checked_div(10u64.pow(price_data.expo.abs() as u32)))
-        .ok_or(PropAmmError::MathOverflow)? as u64;
+    let oracle_price = scale_price(price_data.price, price_data.expo, Rounding::Down)
+        .map_err(PropAmmError::from)?;
 
     // Calculate exit price with spread
-    let exit_price = vault.calculate_exit_price(oracle_price, params.is_long);
+    let exit_price = vault.calculate_exit_price(oracle_price, params.is_long)?;
 
     // Slippage protection
     if params.is_long {
@@ -86,25 +85,21 @@ pub fn handler(ctx: Context<ClosePositionAmm>, params: ClosePositionParams) -> R
     }
 
     // Calculate PnL
-    let entry = params.entry_price as i128;
-    let exit = exit_price as i128;
-    let size = params.size as i128;
-
-    let price_diff = if params.is_long {
-        exit - entry
-    } else {
-        entry - exit
-    };
-
-    let pnl = ((size * price_diff) / 1_000_000) as i64;
+    let pnl = Size(params.size)
+        .pnl(Price(params.entry_price), Price(exit_price), params.is_long, Rounding::Down)
+        .map_err(PropAmmError::from)?;
 
     // Calculate fee
-    let notional = (params.size as u128 * exit_price as u128 / 1_000_000) as u64;
-    let fee = (notional * vault.trading_fee as u64) / 10000;
+    let notional = Price(exit_price)
+        .notional(Size(params.size), Rounding::Up)
+        .map_err(PropAmmError::from)?;
+    let fee = Bps::from(vault.trading_fee)
+        .of(notional, Rounding::Up)
+        .map_err(PropAmmError::from)?;
 
     // Calculate settlement amount (PnL - fee)
     let settlement = if pnl >= 0 {
-        (pnl as u64).saturating_sub(fee)
+        pnl.unsigned_abs().saturating_sub(fee)
     } else {
         0
     };
@@ -112,7 +107,7 @@ pub fn handler(ctx: Context<ClosePositionAmm>, params: ClosePositionParams) -> R
     // Update vault
     let vault = &mut ctx.accounts.vault;
     vault.remove_position(params.size, params.is_long);
-    vault.settle_pnl(pnl, fee);
+    vault.settle_pnl(pnl, fee)?;
 
     // If trader has profit, pay from vault
     if settlement > 0 {
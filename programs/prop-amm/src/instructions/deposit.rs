@@ -51,7 +51,7 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
     let current_time = Clock::get()?.unix_timestamp;
 
     // Calculate shares to mint
-    let shares_to_mint = vault.shares_for_deposit(amount);
+    let shares_to_mint = vault.shares_for_deposit(amount)?;
     require!(shares_to_mint > 0, PropAmmError::MathOverflow);
 
     // Transfer tokens from depositor to vault
@@ -86,7 +86,7 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         amount,
         shares_to_mint,
         vault.total_assets,
-        vault.share_value()
+        vault.share_value()?
     );
 
     Ok(())
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
+use perps_math::{scale_price, Bps, Price, Rounding, Size};
 use pyth_sdk_solana::load_price_feed_from_account_info;
 use crate::state::LpVault;
 use crate::errors::PropAmmError;
@@ -74,13 +75,11 @@ pub fn handler(ctx: Context<OpenPositionAmm>, params: OpenPositionParams) -> Res
     require!(price_data.price > 0, PropAmmError::InvalidOraclePrice);
 
     // Convert Pyth price to our format (6 decimals)
-    let oracle_price = (price_data.price as u64)
-        .checked_mul(10u64.pow((6 + price_data.expo.abs() as u32) as u32))
-        .and_then(|p| p.checked_div(10u64.pow(price_data.expo.abs() as u32)))
-        .ok_or(PropAmmError::MathOverflow)? as u64;
+    let oracle_price = scale_price(price_data.price, price_data.expo, Rounding::Down)
+        .map_err(PropAmmError::from)?;
 
     // Calculate entry price with spread
-    let entry_price = vault.calculate_entry_price(oracle_price, params.is_long);
+    let entry_price = vault.calculate_entry_price(oracle_price, params.is_long)?;
 
     // Slippage protection
     if params.is_long {
@@ -90,8 +89,15 @@ pub fn handler(ctx: Context<OpenPositionAmm>, params: OpenPositionParams) -> Res
     }
 
     // Calculate notional and fee
-    let notional = (params.size as u128 * entry_price as u128 / 1_000_000) as u64;
-    let fee = (notional * vault.trading_fee as u64) / 10000;
+    let notional = Price(entry_price)
+        .notional(Size(params.size), Rounding::Up)
+        .map_err(PropAmmError::from)?;
+    let fee = Bps::from(vault.trading_fee)
+        .of(notional, Rounding::Up)
+        .map_err(PropAmmError::from)?;
+    let margin_required = Bps(1000) // 10x leverage default
+        .of(notional, Rounding::Up)
+        .map_err(PropAmmError::from)?;
 
     // For now, we return the calculated values
     // The actual margin transfer will be handled by perps-core integration
@@ -113,6 +119,6 @@ pub fn handler(ctx: Context<OpenPositionAmm>, params: OpenPositionParams) -> Res
     Ok(OpenPositionResult {
         entry_price,
         fee,
-        margin_required: notional / 10, // 10x leverage default
+        margin_required,
     })
 }
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use perps_math::Rounding;
 use crate::state::{LpVault, LpPosition};
 use crate::errors::PropAmmError;
 
@@ -57,7 +58,7 @@ pub fn handler(ctx: Context<Withdraw>, shares_to_burn: u64) -> Result<()> {
     require!(shares_to_burn > 0, PropAmmError::InvalidParameters);
 
     // Calculate assets to return
-    let assets_to_return = vault.assets_for_shares(shares_to_burn);
+    let assets_to_return = vault.assets_for_shares(shares_to_burn)?;
     require!(
         assets_to_return <= vault.total_assets,
         PropAmmError::InsufficientVaultBalance
@@ -93,8 +94,13 @@ pub fn handler(ctx: Context<Withdraw>, shares_to_burn: u64) -> Result<()> {
     lp_position.withdrawal_requested_at = 0;
 
     // Adjust deposited_amount proportionally
-    let burn_ratio = (shares_to_burn as u128 * 1_000_000) / (lp_position.shares + shares_to_burn) as u128;
-    let deposited_reduction = (lp_position.deposited_amount as u128 * burn_ratio / 1_000_000) as u64;
+    let deposited_reduction = perps_math::mul_div(
+        lp_position.deposited_amount,
+        shares_to_burn,
+        lp_position.shares + shares_to_burn,
+        Rounding::Down,
+    )
+    .map_err(PropAmmError::from)?;
     lp_position.deposited_amount = lp_position.deposited_amount.saturating_sub(deposited_reduction);
 
     let pnl = assets_to_return as i64 - deposited_reduction as i64;
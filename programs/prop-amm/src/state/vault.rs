@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use perps_math::{Bps, MathError, Price, Rounding};
+use crate::errors::PropAmmError;
 
 #[account]
 #[derive(Default)]
@@ -72,77 +74,95 @@ impl LpVault {
         1 +   // is_active
         64;   // padding
 
-    /// Calculate current spread based on exposure skew
-    pub fn calculate_spread(&self, is_long: bool) -> u32 {
+    /// Calculate current spread based on exposure skew, rounded up
+    pub fn calculate_spread(&self, is_long: bool) -> Result<u32> {
         let skew_ratio = if self.max_exposure > 0 {
-            (self.net_exposure.abs() as u64 * 10000) / self.max_exposure
+            Bps::ratio(self.net_exposure.unsigned_abs(), self.max_exposure, Rounding::Up)
+                .map_err(PropAmmError::from)?
         } else {
-            0
+            Bps::ZERO
         };
 
-        let skew_spread = (skew_ratio as u32 * self.max_skew_spread) / 10000;
-        let total_spread = self.base_spread + skew_spread;
+        let skew_spread = skew_ratio
+            .of(self.max_skew_spread as u64, Rounding::Up)
+            .map_err(PropAmmError::from)?;
+        let total_spread = (self.base_spread as u64)
+            .checked_add(skew_spread)
+            .ok_or(PropAmmError::MathOverflow)?;
 
         // Reduce spread for trades that reduce exposure
         let exposure_reducing = (is_long && self.net_exposure < 0) ||
                                 (!is_long && self.net_exposure > 0);
 
-        if exposure_reducing {
+        let spread = if exposure_reducing {
             // 50% discount for exposure-reducing trades
-            total_spread / 2
+            Bps(total_spread.div_ceil(2))
         } else {
-            total_spread
-        }
+            Bps(total_spread)
+        };
+        Ok(spread.to_u32().map_err(PropAmmError::from)?)
     }
 
-    /// Calculate entry price for a trade
-    pub fn calculate_entry_price(&self, oracle_price: u64, is_long: bool) -> u64 {
-        let spread = self.calculate_spread(is_long);
+    /// Calculate entry price for a trade, rounded against the trader
+    pub fn calculate_entry_price(&self, oracle_price: u64, is_long: bool) -> Result<u64> {
+        let spread = Bps::from(self.calculate_spread(is_long)?);
 
-        if is_long {
+        let price = if is_long {
             // Long entry: pay oracle + spread
-            oracle_price + (oracle_price * spread as u64 / 10000)
+            Price(oracle_price).add_bps(spread, Rounding::Up)
         } else {
             // Short entry: receive oracle - spread
-            oracle_price - (oracle_price * spread as u64 / 10000)
-        }
+            Price(oracle_price).sub_bps(spread, Rounding::Down)
+        };
+        Ok(price.map_err(PropAmmError::from)?.0)
     }
 
-    /// Calculate exit price for a trade
-    pub fn calculate_exit_price(&self, oracle_price: u64, is_long: bool) -> u64 {
-        let spread = self.calculate_spread(!is_long); // Opposite spread for exit
+    /// Calculate exit price for a trade, rounded against the trader
+    pub fn calculate_exit_price(&self, oracle_price: u64, is_long: bool) -> Result<u64> {
+        let spread = Bps::from(self.calculate_spread(!is_long)?); // Opposite spread for exit
 
-        if is_long {
+        let price = if is_long {
             // Long exit: receive oracle - spread
-            oracle_price - (oracle_price * spread as u64 / 10000)
+            Price(oracle_price).sub_bps(spread, Rounding::Down)
         } else {
             // Short exit: pay oracle + spread
-            oracle_price + (oracle_price * spread as u64 / 10000)
-        }
+            Price(oracle_price).add_bps(spread, Rounding::Up)
+        };
+        Ok(price.map_err(PropAmmError::from)?.0)
     }
 
-    /// Calculate LP share value (assets per share, scaled by 1e6)
-    pub fn share_value(&self) -> u64 {
+    /// Calculate LP share value (assets per share, scaled by 1e6), rounded down.
+    /// Fails while unrealized losses exceed the vault's assets.
+    pub fn share_value(&self) -> Result<u64> {
         if self.total_shares == 0 {
-            1_000_000 // 1:1 initial ratio
-        } else {
-            let total_value = (self.total_assets as i64 + self.unrealized_pnl) as u64;
-            (total_value * 1_000_000) / self.total_shares
+            return Ok(perps_math::PRICE_SCALE); // 1:1 initial ratio
         }
+        let total_value = perps_math::to_signed(self.total_assets)
+            .and_then(|assets| assets.checked_add(self.unrealized_pnl).ok_or(MathError::Overflow))
+            .and_then(perps_math::to_unsigned)
+            .map_err(|err| match err {
+                MathError::Negative => PropAmmError::VaultUnderwater,
+                _ => PropAmmError::MathOverflow,
+            })?;
+        Ok(perps_math::mul_div(total_value, perps_math::PRICE_SCALE, self.total_shares, Rounding::Down)
+            .map_err(PropAmmError::from)?)
     }
 
-    /// Calculate shares to mint for a deposit
-    pub fn shares_for_deposit(&self, amount: u64) -> u64 {
+    /// Calculate shares to mint for a deposit, rounded down
+    pub fn shares_for_deposit(&self, amount: u64) -> Result<u64> {
         if self.total_shares == 0 {
-            amount // 1:1 for first deposit
-        } else {
-            (amount * 1_000_000) / self.share_value()
+            return Ok(amount); // 1:1 for first deposit
         }
+        let share_value = self.share_value()?;
+        require!(share_value > 0, PropAmmError::VaultUnderwater);
+        Ok(perps_math::mul_div(amount, perps_math::PRICE_SCALE, share_value, Rounding::Down)
+            .map_err(PropAmmError::from)?)
     }
 
-    /// Calculate assets to return for shares
-    pub fn assets_for_shares(&self, shares: u64) -> u64 {
-        (shares * self.share_value()) / 1_000_000
+    /// Calculate assets to return for shares, rounded down
+    pub fn assets_for_shares(&self, shares: u64) -> Result<u64> {
+        Ok(perps_math::mul_div(shares, self.share_value()?, perps_math::PRICE_SCALE, Rounding::Down)
+            .map_err(PropAmmError::from)?)
     }
 
     /// Check if a trade would exceed exposure limits
@@ -164,15 +184,15 @@ impl LpVault {
         };
 
         // Check exposure limit
-        if new_exposure.abs() as u64 > self.max_exposure {
+        if new_exposure.unsigned_abs() > self.max_exposure {
             return false;
         }
 
         // Check utilization
         let total_notional = self.total_long_size + self.total_short_size + size;
-        let max_notional = (self.total_assets as u128 * self.max_utilization as u128 / 10000) as u64;
-
-        total_notional <= max_notional
+        Bps::from(self.max_utilization)
+            .of(self.total_assets, Rounding::Down)
+            .is_ok_and(|max_notional| total_notional <= max_notional)
     }
 
     /// Update exposure after opening a position
@@ -198,23 +218,26 @@ impl LpVault {
     }
 
     /// Settle PnL from a closed position
-    pub fn settle_pnl(&mut self, trader_pnl: i64, fee: u64) {
+    pub fn settle_pnl(&mut self, trader_pnl: i64, fee: u64) -> Result<()> {
         // Vault PnL is opposite of trader PnL
-        let vault_pnl = -trader_pnl;
+        let vault_pnl = trader_pnl.checked_neg().ok_or(PropAmmError::MathOverflow)?;
 
         if vault_pnl >= 0 {
             // Vault gained (trader lost)
-            self.total_assets += vault_pnl as u64;
+            self.total_assets += vault_pnl.unsigned_abs();
         } else {
             // Vault lost (trader gained)
-            self.total_assets = self.total_assets.saturating_sub((-vault_pnl) as u64);
+            self.total_assets = self.total_assets.saturating_sub(vault_pnl.unsigned_abs());
         }
 
         // Add fees
-        let lp_fee = (fee * self.lp_fee_share as u64) / 10000;
+        let lp_fee = Bps::from(self.lp_fee_share)
+            .of(fee, Rounding::Down)
+            .map_err(PropAmmError::from)?;
         self.pending_fees += lp_fee;
         self.total_assets += lp_fee;
         self.cumulative_fees += fee;
         self.cumulative_pnl += vault_pnl;
+        Ok(())
     }
 }
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use perps_math::{Bps, Price, Rounding};
 
 declare_id!("3Vft6oJxPvHXELuqrPgCdPqJf1LxGpqDxBdqNhJxLZbm");
 
@@ -29,58 +30,83 @@ pub struct Vamm {
 impl Vamm {
     pub const LEN: usize = 8 + 32 + 32 + 16 + 16 + 16 + 4 + 4 + 4 + 8 + 8 + 16 + 1 + 1 + 32;
 
-    pub fn get_price(&self) -> u64 {
+    pub fn get_price(&self) -> Result<u64> {
         // price = quote_reserve / base_reserve
-        ((self.quote_reserve * 1_000_000) / self.base_reserve) as u64
+        let price = perps_math::mul_div_wide(
+            self.quote_reserve,
+            perps_math::PRICE_SCALE as u128,
+            self.base_reserve,
+            Rounding::Down,
+        )
+        .and_then(perps_math::to_u64)
+        .map_err(ErrorCode::from)?;
+        Ok(price)
     }
 
-    pub fn get_spread(&self) -> u32 {
-        let total_oi = self.total_long + self.total_short;
-        let capacity = (self.base_reserve / 10) as u64; // 10% of base reserve
+    pub fn get_spread(&self) -> Result<u32> {
+        let total_oi = self.total_long.saturating_add(self.total_short);
+        let capacity = perps_math::to_u64(self.base_reserve / 10).map_err(ErrorCode::from)?; // 10% of base reserve
 
         if total_oi > capacity {
-            let utilization = (total_oi * 10000) / capacity;
-            let extra_spread = ((utilization - 10000) * (self.max_spread - self.base_spread) as u64 / 10000) as u32;
-            (self.base_spread + extra_spread).min(self.max_spread)
+            // Utilization and the spread it adds round up, widening the quote
+            let utilization = Bps::ratio(total_oi, capacity, Rounding::Up).map_err(ErrorCode::from)?;
+            let excess = Bps(utilization.0 - perps_math::BPS_SCALE);
+            let extra_spread = excess
+                .of(self.max_spread.saturating_sub(self.base_spread) as u64, Rounding::Up)
+                .map_err(ErrorCode::from)?;
+            Ok((self.base_spread as u64).saturating_add(extra_spread).min(self.max_spread as u64) as u32)
         } else {
-            self.base_spread
+            Ok(self.base_spread)
         }
     }
 
-    pub fn get_bid_price(&self) -> u64 {
-        let price = self.get_price();
-        let spread = self.get_spread();
-        price - (price * spread as u64 / 10000)
+    pub fn get_bid_price(&self) -> Result<u64> {
+        let price = self.get_price()?;
+        let spread = self.get_spread()?;
+        let bid = Price(price)
+            .sub_bps(Bps::from(spread), Rounding::Down)
+            .map_err(ErrorCode::from)?;
+        Ok(bid.0)
     }
 
-    pub fn get_ask_price(&self) -> u64 {
-        let price = self.get_price();
-        let spread = self.get_spread();
-        price + (price * spread as u64 / 10000)
+    pub fn get_ask_price(&self) -> Result<u64> {
+        let price = self.get_price()?;
+        let spread = self.get_spread()?;
+        let ask = Price(price)
+            .add_bps(Bps::from(spread), Rounding::Up)
+            .map_err(ErrorCode::from)?;
+        Ok(ask.0)
     }
 
-    pub fn swap_base_to_quote(&mut self, base_amount: u64) -> u64 {
+    pub fn swap_base_to_quote(&mut self, base_amount: u64) -> Result<u64> {
         // Selling base (closing long / opening short)
-        let new_base_reserve = self.base_reserve + base_amount as u128;
-        let new_quote_reserve = self.k / new_base_reserve;
-        let quote_out = self.quote_reserve - new_quote_reserve;
+        let new_base_reserve = self.base_reserve
+            .checked_add(base_amount as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        // Reserve left behind rounds up, so the trader never receives more than k allows
+        let new_quote_reserve = perps_math::mul_div_wide(self.k, 1, new_base_reserve, Rounding::Up)
+            .map_err(ErrorCode::from)?;
+        let quote_out = self.quote_reserve.saturating_sub(new_quote_reserve);
 
         self.base_reserve = new_base_reserve;
         self.quote_reserve = new_quote_reserve;
 
-        quote_out as u64
+        Ok(perps_math::to_u64(quote_out).map_err(ErrorCode::from)?)
     }
 
-    pub fn swap_quote_to_base(&mut self, quote_amount: u64) -> u64 {
+    pub fn swap_quote_to_base(&mut self, quote_amount: u64) -> Result<u64> {
         // Buying base (opening long / closing short)
-        let new_quote_reserve = self.quote_reserve + quote_amount as u128;
-        let new_base_reserve = self.k / new_quote_reserve;
-        let base_out = self.base_reserve - new_base_reserve;
+        let new_quote_reserve = self.quote_reserve
+            .checked_add(quote_amount as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let new_base_reserve = perps_math::mul_div_wide(self.k, 1, new_quote_reserve, Rounding::Up)
+            .map_err(ErrorCode::from)?;
+        let base_out = self.base_reserve.saturating_sub(new_base_reserve);
 
         self.quote_reserve = new_quote_reserve;
         self.base_reserve = new_base_reserve;
 
-        base_out as u64
+        Ok(perps_math::to_u64(base_out).map_err(ErrorCode::from)?)
     }
 }
 
@@ -111,7 +137,7 @@ pub mod vamm {
         vamm.is_active = true;
         vamm.bump = *ctx.bumps.get("vamm").unwrap();
 
-        msg!("vAMM initialized: k={}, price={}", vamm.k, vamm.get_price());
+        msg!("vAMM initialized: k={}, price={}", vamm.k, vamm.get_price()?);
         Ok(())
     }
 
@@ -119,11 +145,11 @@ pub mod vamm {
         let vamm = &mut ctx.accounts.vamm;
         require!(vamm.is_active, ErrorCode::VammPaused);
 
-        let quote_amount = vamm.swap_quote_to_base(size);
+        let quote_amount = vamm.swap_quote_to_base(size)?;
         vamm.total_long += size;
         vamm.total_volume += size as u128;
 
-        msg!("Opened long: size={}, quote_in={}, new_price={}", size, quote_amount, vamm.get_price());
+        msg!("Opened long: size={}, quote_in={}, new_price={}", size, quote_amount, vamm.get_price()?);
         Ok(())
     }
 
@@ -131,31 +157,31 @@ pub mod vamm {
         let vamm = &mut ctx.accounts.vamm;
         require!(vamm.is_active, ErrorCode::VammPaused);
 
-        let quote_amount = vamm.swap_base_to_quote(size);
+        let quote_amount = vamm.swap_base_to_quote(size)?;
         vamm.total_short += size;
         vamm.total_volume += size as u128;
 
-        msg!("Opened short: size={}, quote_out={}, new_price={}", size, quote_amount, vamm.get_price());
+        msg!("Opened short: size={}, quote_out={}, new_price={}", size, quote_amount, vamm.get_price()?);
         Ok(())
     }
 
     pub fn close_long(ctx: Context<Trade>, size: u64) -> Result<()> {
         let vamm = &mut ctx.accounts.vamm;
 
-        let quote_amount = vamm.swap_base_to_quote(size);
+        let quote_amount = vamm.swap_base_to_quote(size)?;
         vamm.total_long = vamm.total_long.saturating_sub(size);
 
-        msg!("Closed long: size={}, quote_out={}, new_price={}", size, quote_amount, vamm.get_price());
+        msg!("Closed long: size={}, quote_out={}, new_price={}", size, quote_amount, vamm.get_price()?);
         Ok(())
     }
 
     pub fn close_short(ctx: Context<Trade>, size: u64) -> Result<()> {
         let vamm = &mut ctx.accounts.vamm;
 
-        let quote_amount = vamm.swap_quote_to_base(size);
+        let quote_amount = vamm.swap_quote_to_base(size)?;
         vamm.total_short = vamm.total_short.saturating_sub(size);
 
-        msg!("Closed short: size={}, quote_in={}, new_price={}", size, quote_amount, vamm.get_price());
+        msg!("Closed short: size={}, quote_in={}, new_price={}", size, quote_amount, vamm.get_price()?);
         Ok(())
     }
 
@@ -163,8 +189,14 @@ pub mod vamm {
         let vamm = &mut ctx.accounts.vamm;
 
         // Maintain price ratio
-        let current_price = vamm.get_price();
-        let expected_quote = (base_amount * current_price as u128) / 1_000_000;
+        let current_price = vamm.get_price()?;
+        let expected_quote = perps_math::mul_div_wide(
+            base_amount,
+            current_price as u128,
+            perps_math::PRICE_SCALE as u128,
+            Rounding::Down,
+        )
+        .map_err(ErrorCode::from)?;
 
         require!(
             quote_amount >= expected_quote * 99 / 100 && quote_amount <= expected_quote * 101 / 100,
@@ -256,4 +288,12 @@ pub enum ErrorCode {
     VammPaused,
     #[msg("Invalid liquidity ratio")]
     InvalidLiquidityRatio,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+impl From<perps_math::MathError> for ErrorCode {
+    fn from(_: perps_math::MathError) -> Self {
+        ErrorCode::MathOverflow
+    }
 }